@@ -0,0 +1,260 @@
+// Native renderer for scene files, without a browser nor a GPU. It shares the
+// modules of the wasm build but only drives part of them, the rest is used by
+// the wasm API in main.rs.
+
+#[allow(dead_code)]
+mod math;
+#[allow(dead_code)]
+mod mem;
+#[allow(dead_code)]
+mod options;
+#[allow(dead_code)]
+mod performance;
+#[allow(dead_code)]
+mod render;
+#[allow(dead_code)]
+mod scene;
+#[allow(dead_code)]
+mod shapes;
+#[allow(dead_code)]
+mod state;
+#[allow(dead_code)]
+mod tiles;
+#[allow(dead_code)]
+mod utils;
+#[allow(dead_code)]
+mod uuid;
+#[allow(dead_code)]
+mod view;
+#[allow(dead_code)]
+mod wapi;
+#[allow(dead_code)]
+mod wasm;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use math::Matrix;
+use mem::SerializableResult;
+use render::RenderBackend;
+use scene::Scene;
+use shapes::{Frame, TransformEntry, Type};
+use skia_safe as skia;
+use state::State;
+pub(crate) use state::STATE;
+use utils::uuid_from_u32_quartet;
+use uuid::Uuid;
+
+const USAGE: &str =
+    "Usage: render_headless <scene> <output-dir> [--frame <id>]... [--scale <scale>]...
+
+Renders the frames of a scene file as PNG files named <id>@<scale>x.png.
+Without --frame, every top-level frame is rendered. The default scale is 1.";
+
+struct Args {
+    scene: PathBuf,
+    output: PathBuf,
+    frames: Vec<Uuid>,
+    scales: Vec<f32>,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut paths = Vec::new();
+        let mut frames = Vec::new();
+        let mut scales = Vec::new();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--frame" => {
+                    let value = args.next().ok_or("Missing frame id")?;
+                    let id = ::uuid::Uuid::parse_str(&value)
+                        .map_err(|_| format!("Invalid frame id: {}", value))?;
+                    frames.push(Uuid::from(id));
+                }
+                "--scale" => {
+                    let value = args.next().ok_or("Missing scale")?;
+                    let scale = value
+                        .parse::<f32>()
+                        .map_err(|_| format!("Invalid scale: {}", value))?;
+                    scales.push(scale);
+                }
+                "-h" | "--help" => return Err(USAGE.to_string()),
+                _ => paths.push(PathBuf::from(arg)),
+            }
+        }
+
+        let [scene, output]: [PathBuf; 2] = paths.try_into().map_err(|_| USAGE.to_string())?;
+        if scales.is_empty() {
+            scales.push(1.0);
+        }
+
+        Ok(Self {
+            scene,
+            output,
+            frames,
+            scales,
+        })
+    }
+}
+
+fn main() {
+    if let Err(error) = run() {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<(), String> {
+    let args = Args::parse(std::env::args().skip(1))?;
+
+    let bytes = fs::read(&args.scene)
+        .map_err(|e| format!("Error reading {}: {}", args.scene.display(), e))?;
+    let scene = Scene::from_bytes(&bytes)?;
+
+    fs::create_dir_all(&args.output)
+        .map_err(|e| format!("Error creating {}: {}", args.output.display(), e))?;
+
+    // The exports don't depend on the viewport, so its size doesn't matter.
+    let state_box = Box::new(State::new(1, 1, RenderBackend::raster()));
+    unsafe {
+        STATE = Some(state_box);
+    }
+
+    with_state_mut!(state, {
+        state.load_scene(scene)?;
+        reflow_layouts(state);
+
+        let frames = if args.frames.is_empty() {
+            top_level_frames(state)
+        } else {
+            args.frames
+        };
+
+        for id in frames {
+            for scale in args.scales.iter() {
+                let data = state.render_shape_image(
+                    &id,
+                    *scale,
+                    skia::EncodedImageFormat::PNG,
+                    100,
+                    None,
+                    performance::get_time(),
+                )?;
+
+                let path = args.output.join(format!("{}@{}x.png", id, scale));
+                fs::write(&path, data)
+                    .map_err(|e| format!("Error writing {}: {}", path.display(), e))?;
+                println!("{}", path.display());
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Scene files keep the positions the frontend computed, but the layouts are
+/// calculated again so the output matches the renderer, like after a reflow in
+/// the workspace.
+fn reflow_layouts(state: &mut State) {
+    let entries: Vec<TransformEntry> = state
+        .shapes
+        .iter()
+        .filter(|shape| {
+            matches!(
+                shape.shape_type,
+                Type::Frame(Frame {
+                    layout: Some(_),
+                    ..
+                })
+            )
+        })
+        .map(|shape| TransformEntry::from_input(shape.id, Matrix::new_identity()))
+        .collect();
+
+    if entries.is_empty() {
+        return;
+    }
+
+    let modifiers: HashMap<Uuid, Matrix> = shapes::propagate_modifiers(state, &entries, false)
+        .into_iter()
+        .map(|entry| (entry.id, entry.transform))
+        .collect();
+    state.set_modifiers(modifiers);
+}
+
+fn top_level_frames(state: &State) -> Vec<Uuid> {
+    let Some(root) = state.shapes.get(&Uuid::nil()) else {
+        return Vec::new();
+    };
+
+    root.children
+        .iter()
+        .rev()
+        .filter(|id| {
+            state
+                .shapes
+                .get(id)
+                .is_some_and(|shape| matches!(shape.shape_type, Type::Frame(_)))
+        })
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = parse(&["scene.bin", "out"]).unwrap();
+        assert_eq!(args.scene, PathBuf::from("scene.bin"));
+        assert_eq!(args.output, PathBuf::from("out"));
+        assert!(args.frames.is_empty());
+        assert_eq!(args.scales, vec![1.0]);
+
+        let id = "6b5fb2e8-2a2c-4d0c-9c4a-2f1d5e3b7a10";
+        let args = parse(&[
+            "--scale",
+            "2",
+            "scene.bin",
+            "--frame",
+            id,
+            "out",
+            "--scale",
+            "0.5",
+        ])
+        .unwrap();
+        assert_eq!(args.scene, PathBuf::from("scene.bin"));
+        assert_eq!(args.output, PathBuf::from("out"));
+        assert_eq!(
+            args.frames,
+            vec![Uuid::from(::uuid::Uuid::parse_str(id).unwrap())]
+        );
+        assert_eq!(args.scales, vec![2.0, 0.5]);
+    }
+
+    #[test]
+    fn test_parse_args_errors() {
+        assert_eq!(parse(&["scene.bin"]).err(), Some(USAGE.to_string()));
+        assert_eq!(parse(&["a", "b", "c"]).err(), Some(USAGE.to_string()));
+        assert_eq!(parse(&["--help"]).err(), Some(USAGE.to_string()));
+        assert_eq!(
+            parse(&["scene.bin", "out", "--frame"]).err(),
+            Some("Missing frame id".to_string())
+        );
+        assert_eq!(
+            parse(&["scene.bin", "out", "--frame", "nope"]).err(),
+            Some("Invalid frame id: nope".to_string())
+        );
+        assert_eq!(
+            parse(&["scene.bin", "out", "--scale", "big"]).err(),
+            Some("Invalid scale: big".to_string())
+        );
+    }
+}
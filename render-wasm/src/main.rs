@@ -5,98 +5,40 @@ mod mem;
 mod options;
 mod performance;
 mod render;
+mod scene;
 mod shapes;
 mod state;
 mod tiles;
 mod utils;
 mod uuid;
 mod view;
+#[cfg(test)]
+mod visual_tests;
 mod wapi;
 mod wasm;
 
 use math::{Bounds, Matrix};
 use mem::SerializableResult;
+use render::RenderBackend;
 use shapes::{StructureEntry, StructureEntryType, TransformEntry};
 use skia_safe as skia;
 use state::State;
+pub(crate) use state::STATE;
 use std::collections::HashMap;
 use utils::uuid_from_u32_quartet;
 use uuid::Uuid;
 
-pub(crate) static mut STATE: Option<Box<State>> = None;
-
-#[macro_export]
-macro_rules! with_state_mut {
-    ($state:ident, $block:block) => {{
-        let $state = unsafe {
-            #[allow(static_mut_refs)]
-            STATE.as_mut()
-        }
-        .expect("Got an invalid state pointer");
-        $block
-    }};
-}
-
-#[macro_export]
-macro_rules! with_state {
-    ($state:ident, $block:block) => {{
-        let $state = unsafe {
-            #[allow(static_mut_refs)]
-            STATE.as_ref()
-        }
-        .expect("Got an invalid state pointer");
-        $block
-    }};
-}
-
-#[macro_export]
-macro_rules! with_current_shape_mut {
-    ($state:ident, |$shape:ident: &mut Shape| $block:block) => {
-        let $state = unsafe {
-            #[allow(static_mut_refs)]
-            STATE.as_mut()
-        }
-        .expect("Got an invalid state pointer");
-
-        $state.touch_current();
-
-        if let Some($shape) = $state.current_shape_mut() {
-            $block
-        }
-    };
-}
-
-#[macro_export]
-macro_rules! with_current_shape {
-    ($state:ident, |$shape:ident: &Shape| $block:block) => {
-        let $state = unsafe {
-            #[allow(static_mut_refs)]
-            STATE.as_ref()
-        }
-        .expect("Got an invalid state pointer");
-        if let Some($shape) = $state.current_shape() {
-            $block
-        }
-    };
-}
-
-#[macro_export]
-macro_rules! with_state_mut_current_shape {
-    ($state:ident, |$shape:ident: &Shape| $block:block) => {
-        let $state = unsafe {
-            #[allow(static_mut_refs)]
-            STATE.as_mut()
-        }
-        .expect("Got an invalid state pointer");
-        if let Some($shape) = $state.current_shape() {
-            $block
-        }
-    };
+#[no_mangle]
+pub extern "C" fn init(width: i32, height: i32) {
+    let state_box = Box::new(State::new(width, height, RenderBackend::gpu()));
+    unsafe {
+        STATE = Some(state_box);
+    }
 }
 
 #[no_mangle]
-pub extern "C" fn init(width: i32, height: i32) {
-    let state_box = Box::new(State::new(width, height));
+pub extern "C" fn init_raster(width: i32, height: i32) {
+    let state_box = Box::new(State::new(width, height, RenderBackend::raster()));
     unsafe {
         STATE = Some(state_box);
     }
@@ -27,6 +27,15 @@ pub extern "C" fn alloc_bytes(len: usize) -> *mut u8 {
     }
 }
 
+/// Writes `data` preceded by its `u32` length, for results whose size the
+/// caller can't know beforehand.
+pub fn write_length_prefixed(data: &[u8]) -> *mut u8 {
+    let mut bytes = Vec::with_capacity(4 + data.len());
+    bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(data);
+    write_bytes(bytes)
+}
+
 pub fn write_bytes(mut bytes: Vec<u8>) -> *mut u8 {
     let mut guard = BUFFERU8.lock().unwrap();
 
@@ -1,4 +1,6 @@
+mod backend;
 mod debug;
+pub mod export;
 mod fills;
 pub mod filters;
 mod fonts;
@@ -6,9 +8,11 @@ mod gpu_state;
 pub mod grid_layout;
 mod images;
 mod options;
+pub mod pdf;
 mod shadows;
 mod strokes;
 mod surfaces;
+pub mod svg;
 pub mod text;
 mod ui;
 
@@ -16,13 +20,15 @@ use skia_safe::{self as skia, Matrix, RRect, Rect};
 use std::borrow::Cow;
 use std::collections::HashSet;
 
-use gpu_state::GpuState;
+pub use backend::RenderBackend;
 use options::RenderOptions;
+pub use strokes::stroke_outline;
 pub use surfaces::{SurfaceId, Surfaces};
 
 use crate::performance;
 use crate::shapes::{
-    all_with_ancestors, Blur, BlurType, Corners, Fill, Shadow, Shape, SolidColor, Stroke, Type,
+    all_with_ancestors, BlendMode, Blur, BlurType, Corners, Fill, FillLayer, Shadow, Shape,
+    SolidColor, Stroke, Type,
 };
 use crate::state::{ShapesPoolMutRef, ShapesPoolRef};
 use crate::tiles::{self, PendingTiles, TileRect};
@@ -71,6 +77,32 @@ fn get_simplified_children<'a>(tree: ShapesPoolRef<'a>, shape: &'a Shape) -> Vec
     result
 }
 
+/// Z-index ordering
+/// For reverse flex layouts with custom z-indexes, we reverse the base order
+/// so that visual stacking matches visual position
+fn sort_children_by_z_index(
+    tree: ShapesPoolRef,
+    element: &Shape,
+    mut children_ids: Vec<Uuid>,
+) -> Vec<Uuid> {
+    if !element.has_layout() {
+        return children_ids;
+    }
+
+    let has_z_index = children_ids
+        .iter()
+        .any(|id| tree.get(id).map(|s| s.has_z_index()).unwrap_or(false));
+    if element.is_flex_reverse() && has_z_index {
+        children_ids.reverse();
+    }
+    children_ids.sort_by(|id1, id2| {
+        let z1 = tree.get(id1).map(|s| s.z_index()).unwrap_or(0);
+        let z2 = tree.get(id2).map(|s| s.z_index()).unwrap_or(0);
+        z2.cmp(&z1)
+    });
+    children_ids
+}
+
 impl NodeRenderState {
     pub fn is_root(&self) -> bool {
         self.id.is_nil()
@@ -258,7 +290,7 @@ impl FocusMode {
 }
 
 pub(crate) struct RenderState {
-    gpu_state: GpuState,
+    backend: RenderBackend,
     pub options: RenderOptions,
     pub surfaces: Surfaces,
     pub fonts: FontStore,
@@ -275,6 +307,10 @@ pub(crate) struct RenderState {
     pub current_tile: Option<tiles::Tile>,
     pub sampling_options: skia::SamplingOptions,
     pub render_area: Rect,
+    // Area whose shapes are drawn for the current tile. It's the tile grown by
+    // the reach of the background blurs in it, so their backdrop doesn't end
+    // at the tile edges.
+    pub backdrop_area: Rect,
     pub tile_viewbox: tiles::TileViewbox,
     pub tiles: tiles::TileHashMap,
     pub pending_tiles: PendingTiles,
@@ -283,7 +319,7 @@ pub(crate) struct RenderState {
     // can affect its child elements if they don't specify one themselves. If the planned
     // migration to remove group-level fills is completed, this code should be removed.
     // Frames contained in groups must reset this nested_fills stack pushing a new empty vector.
-    pub nested_fills: Vec<Vec<Fill>>,
+    pub nested_fills: Vec<Vec<FillLayer>>,
     pub nested_blurs: Vec<Option<Blur>>, // FIXME: why is this an option?
     pub nested_shadows: Vec<Vec<Shadow>>,
     pub show_grid: Option<Uuid>,
@@ -317,15 +353,13 @@ pub fn get_cache_size(viewbox: Viewbox, scale: f32) -> skia::ISize {
 }
 
 impl RenderState {
-    pub fn new(width: i32, height: i32) -> RenderState {
-        // This needs to be done once per WebGL context.
-        let mut gpu_state = GpuState::new();
+    pub fn new(width: i32, height: i32, mut backend: RenderBackend) -> RenderState {
         let sampling_options =
             skia::SamplingOptions::new(skia::FilterMode::Linear, skia::MipmapMode::Nearest);
 
         let fonts = FontStore::new();
         let surfaces = Surfaces::new(
-            &mut gpu_state,
+            &mut backend,
             (width, height),
             sampling_options,
             tiles::get_tile_dimensions(),
@@ -337,14 +371,16 @@ impl RenderState {
         let viewbox = Viewbox::new(width as f32, height as f32);
         let tiles = tiles::TileHashMap::new();
 
+        let images = ImageStore::new(backend.direct_context());
+
         RenderState {
-            gpu_state: gpu_state.clone(),
+            backend,
             options: RenderOptions::default(),
             surfaces,
             fonts,
             viewbox,
             cached_viewbox: Viewbox::new(0., 0.),
-            images: ImageStore::new(gpu_state.context.clone()),
+            images,
             background_color: skia::Color::TRANSPARENT,
             render_request_id: None,
             render_in_progress: false,
@@ -352,6 +388,7 @@ impl RenderState {
             current_tile: None,
             sampling_options,
             render_area: Rect::new_empty(),
+            backdrop_area: Rect::new_empty(),
             tiles,
             tile_viewbox: tiles::TileViewbox::new_with_interest(
                 viewbox,
@@ -378,6 +415,9 @@ impl RenderState {
     ///   3. Convert the total variance back to a radius with `blur_from_variance`.
     ///
     /// This keeps blur math consistent everywhere we need to merge blur sources.
+    ///
+    /// A progressive shape blur keeps its progression, with the inherited
+    /// variance added to both of its radii.
     fn combined_layer_blur(&self, shape_blur: Option<Blur>) -> Option<Blur> {
         let mut total = 0.;
 
@@ -385,6 +425,16 @@ impl RenderState {
             total += Self::blur_variance(Some(*nested_blur));
         }
 
+        if let Some(mut blur) = shape_blur
+            .filter(|blur| blur.progression.is_some() && Self::blur_variance(Some(*blur)) > 0.)
+        {
+            blur.value = (blur.value.powi(2) + total).sqrt();
+            if let Some(progression) = blur.progression.as_mut() {
+                progression.start_value = (progression.start_value.powi(2) + total).sqrt();
+            }
+            return Some(blur);
+        }
+
         total += Self::blur_variance(shape_blur);
 
         Self::blur_from_variance(total)
@@ -392,11 +442,11 @@ impl RenderState {
 
     /// Returns the variance (radius²) for a visible layer blur, or zero if the
     /// blur is hidden/absent. Working in variance space lets us add multiple
-    /// blur radii correctly.
+    /// blur radii correctly. Progressive blurs count with their largest radius.
     fn blur_variance(blur: Option<Blur>) -> f32 {
         match blur {
             Some(blur) if !blur.hidden && blur.blur_type == BlurType::LayerBlur => {
-                blur.value.powi(2)
+                blur.max_value().powi(2)
             }
             _ => 0.,
         }
@@ -495,14 +545,14 @@ impl RenderState {
         let dpr_width = (width as f32 * self.options.dpr()).floor() as i32;
         let dpr_height = (height as f32 * self.options.dpr()).floor() as i32;
         self.surfaces
-            .resize(&mut self.gpu_state, dpr_width, dpr_height);
+            .resize(&mut self.backend, dpr_width, dpr_height);
         self.viewbox.set_wh(width as f32, height as f32);
         self.tile_viewbox.update(self.viewbox, self.get_scale());
     }
 
     pub fn flush_and_submit(&mut self) {
         self.surfaces
-            .flush_and_submit(&mut self.gpu_state, SurfaceId::Target);
+            .flush_and_submit(&mut self.backend, SurfaceId::Target);
     }
 
     pub fn reset_canvas(&mut self) {
@@ -607,7 +657,7 @@ impl RenderState {
             .nested_shadows
             .iter()
             .flat_map(|shadows| shadows.iter())
-            .filter(|shadow| !shadow.hidden() && shadow.style() == crate::shapes::ShadowStyle::Drop)
+            .filter(|shadow| !shadow.hidden() && shadow.is_drop())
             .collect();
 
         if drop_shadows.is_empty() {
@@ -617,12 +667,7 @@ impl RenderState {
         Some(
             drop_shadows
                 .into_iter()
-                .map(|shadow| {
-                    let mut paint = skia_safe::Paint::default();
-                    let filter = shadow.get_drop_shadow_filter();
-                    paint.set_image_filter(filter);
-                    paint
-                })
+                .map(|shadow| shadow.get_drop_shadow_paint())
                 .collect(),
         )
     }
@@ -674,6 +719,7 @@ impl RenderState {
             && shape.blur.is_none()
             && !has_inherited_blur
             && shape.shadows.is_empty()
+            && shape.noise.is_none()
             && shape.transform.is_identity()
             && matches!(
                 shape.shape_type,
@@ -1040,6 +1086,7 @@ impl RenderState {
                             stroke,
                             antialias,
                             innershadows_surface_id,
+                            strokes_surface_id,
                         );
                     }
                 }
@@ -1050,12 +1097,22 @@ impl RenderState {
                         shape,
                         antialias,
                         innershadows_surface_id,
+                        fills_surface_id,
                     );
                 }
                 // bools::debug_render_bool_paths(self, shape, shapes, modifiers, structure);
             }
         };
 
+        // The noise is drawn over the fills surface, so it only covers the fills.
+        if !fast_mode {
+            if let Some(paint) = shape.noise_paint() {
+                self.surfaces
+                    .canvas_and_mark_dirty(fills_surface_id)
+                    .draw_paint(&paint);
+            }
+        }
+
         if self.options.is_debug_visible() {
             let shape_selrect_bounds = self.get_shape_selrect_bounds(&shape);
             debug::render_debug_shape(self, Some(shape_selrect_bounds), None);
@@ -1076,10 +1133,45 @@ impl RenderState {
     pub fn update_render_context(&mut self, tile: tiles::Tile) {
         self.current_tile = Some(tile);
         self.render_area = tiles::get_tile_rect(tile, self.get_scale());
+        self.backdrop_area = self.render_area;
         self.surfaces
             .update_render_context(self.render_area, self.get_scale());
     }
 
+    /// Grows the area drawn for `tile` by the reach of the background blurs in
+    /// it. Returns the shapes of the tiles around it within that area.
+    pub fn update_backdrop_area(
+        &mut self,
+        tile: tiles::Tile,
+        tree: ShapesPoolRef,
+    ) -> HashSet<Uuid> {
+        let Some(ids) = self.tiles.get_shapes_at(tile) else {
+            return HashSet::new();
+        };
+        let mut ids = ids.clone();
+
+        let reach = ids
+            .iter()
+            .filter_map(|id| tree.get(id)?.background_blur())
+            .map(|blur| blur.reach())
+            .fold(0., f32::max);
+        if reach <= 0. {
+            return ids;
+        }
+
+        self.backdrop_area.outset((reach, reach));
+        let tile_size = tiles::get_tile_size(self.get_scale());
+        let TileRect(sx, sy, ex, ey) = tiles::get_tiles_for_rect(self.backdrop_area, tile_size);
+        for x in sx..=ex {
+            for y in sy..=ey {
+                if let Some(shapes) = self.tiles.get_shapes_at(tiles::Tile::from(x, y)) {
+                    ids.extend(shapes.iter().copied());
+                }
+            }
+        }
+        ids
+    }
+
     pub fn cancel_animation_frame(&mut self) {
         if self.render_in_progress {
             if let Some(frame_id) = self.render_request_id {
@@ -1279,7 +1371,12 @@ impl RenderState {
     }
 
     #[inline]
-    pub fn render_shape_enter(&mut self, element: &Shape, mask: bool) {
+    pub fn render_shape_enter(
+        &mut self,
+        element: &Shape,
+        mask: bool,
+        clip_bounds: Option<&ClipStack>,
+    ) {
         // Masked groups needs two rendering passes, the first one rendering
         // the content and the second one rendering the mask so we need to do
         // an extra save_layer to keep all the masked group separate from
@@ -1315,6 +1412,12 @@ impl RenderState {
                 .save_layer(&mask_rec);
         }
 
+        // The backdrop is blurred before the shape layer is created, so the
+        // shape opacity and blend mode don't apply to it.
+        if !mask {
+            self.render_background_blur(element, clip_bounds);
+        }
+
         // Only create save_layer if actually needed
         // For simple shapes with default opacity and blend mode, skip expensive save_layer
         // Groups with masks need a layer to properly handle the mask rendering
@@ -1327,8 +1430,18 @@ impl RenderState {
 
             if let Some(frame_blur) = Self::frame_clip_layer_blur(element) {
                 let scale = self.get_scale();
-                let sigma = frame_blur.value * scale;
-                if let Some(filter) = skia::image_filters::blur((sigma, sigma), None, None, None) {
+                let translation = self
+                    .surfaces
+                    .get_render_context_translation(self.render_area, scale);
+                // The layer has no transform, so a progression is laid over the
+                // frame bounds in the current render context.
+                let center = element.center();
+                let mut matrix = element.transform;
+                matrix.post_translate(center);
+                matrix.pre_translate(-center);
+                matrix.post_translate(translation);
+                let (rect, _) = matrix.map_rect(element.selrect);
+                if let Some(filter) = frame_blur.layer_filter(&rect, scale) {
                     paint.set_image_filter(filter);
                 }
             }
@@ -1342,6 +1455,113 @@ impl RenderState {
         self.focus_mode.enter(&element.id);
     }
 
+    /// Replaces what is already drawn below the shape with its blurred version,
+    /// clipped to the shape geometry and to the parent clips.
+    fn render_background_blur(&mut self, element: &Shape, clip_bounds: Option<&ClipStack>) {
+        if self.options.is_fast_mode() {
+            return;
+        }
+        let Some(blur) = element.background_blur() else {
+            return;
+        };
+        let Some(path) = element.geometry_path() else {
+            return;
+        };
+
+        let Some(filter) = blur.backdrop_filter(&element.selrect) else {
+            return;
+        };
+        let scale = self.get_scale();
+        let translation = self
+            .surfaces
+            .get_render_context_translation(self.render_area, scale);
+        let antialias = element.should_use_antialias(scale);
+
+        let center = element.center();
+        let mut matrix = element.transform;
+        matrix.post_translate(center);
+        matrix.pre_translate(-center);
+
+        let canvas = self.surfaces.canvas(SurfaceId::Current);
+        canvas.save();
+        canvas.scale((scale, scale));
+        canvas.translate(translation);
+
+        for (bounds, corners, transform) in clip_bounds.into_iter().flatten() {
+            canvas.concat(transform);
+            if let Some(corners) = corners {
+                let rrect = RRect::new_rect_radii(*bounds, corners);
+                canvas.clip_rrect(rrect, skia::ClipOp::Intersect, antialias);
+            } else {
+                canvas.clip_rect(*bounds, skia::ClipOp::Intersect, antialias);
+            }
+            canvas.concat(&transform.invert().unwrap_or_default());
+        }
+
+        canvas.concat(&matrix);
+        canvas.clip_path(&path, skia::ClipOp::Intersect, antialias);
+
+        // The filter is in shape coordinates, so the layer keeps the shape matrix.
+        let layer_rec = skia::canvas::SaveLayerRec::default().backdrop(&filter);
+        canvas.save_layer(&layer_rec);
+        canvas.restore();
+        canvas.restore();
+    }
+
+    /// Draws the drop shadows surface into the current one, clipped by the
+    /// parent clips of the shape.
+    fn composite_drop_shadows(
+        &mut self,
+        element: &Shape,
+        clip_bounds: Option<&ClipStack>,
+        scale: f32,
+        translation: (f32, f32),
+        paint: &skia::Paint,
+    ) {
+        let Some(clips) = clip_bounds else {
+            self.surfaces
+                .draw_into(SurfaceId::DropShadows, SurfaceId::Current, Some(paint));
+            return;
+        };
+        let antialias = element.should_use_antialias(scale);
+
+        self.surfaces.canvas(SurfaceId::Current).save();
+        for (bounds, corners, transform) in clips.iter() {
+            let mut total_matrix = Matrix::new_identity();
+            total_matrix.pre_scale((scale, scale), None);
+            total_matrix.pre_translate((translation.0, translation.1));
+            total_matrix.pre_concat(transform);
+
+            self.surfaces
+                .canvas(SurfaceId::Current)
+                .concat(&total_matrix);
+
+            if let Some(corners) = corners {
+                let rrect = RRect::new_rect_radii(*bounds, corners);
+                self.surfaces.canvas(SurfaceId::Current).clip_rrect(
+                    rrect,
+                    skia::ClipOp::Intersect,
+                    antialias,
+                );
+            } else {
+                self.surfaces.canvas(SurfaceId::Current).clip_rect(
+                    *bounds,
+                    skia::ClipOp::Intersect,
+                    antialias,
+                );
+            }
+
+            self.surfaces
+                .canvas(SurfaceId::Current)
+                .concat(&total_matrix.invert().unwrap_or_default());
+        }
+
+        self.surfaces
+            .draw_into(SurfaceId::DropShadows, SurfaceId::Current, Some(paint));
+
+        self.surfaces.canvas(SurfaceId::Current).restore();
+    }
+
     #[inline]
     pub fn render_shape_exit(
         &mut self,
@@ -1488,6 +1708,77 @@ impl RenderState {
         self.get_aligned_tile_bounds(self.current_tile.unwrap())
     }
 
+    /// Copy of the shape painted in black, without shadows or blur, so the
+    /// opacity of its fills and strokes doesn't affect its shadows.
+    fn black_silhouette(shape: &Shape) -> Shape {
+        let mut plain_shape = shape.clone();
+        plain_shape.clear_fills();
+        if shape.has_fills() {
+            plain_shape.add_fill(Fill::Solid(SolidColor(skia::Color::BLACK)));
+        }
+
+        plain_shape.clear_strokes();
+        for stroke in shape.strokes.iter() {
+            plain_shape.add_stroke(Stroke {
+                fill: Fill::Solid(SolidColor(skia::Color::BLACK)),
+                blend_mode: BlendMode::default(),
+                opacity: 1.,
+                width: stroke.width,
+                style: stroke.style,
+                dash: stroke.dash.clone(),
+                width_profile: stroke.width_profile.clone(),
+                sides: stroke.sides,
+                join: stroke.join,
+                miter_limit: stroke.miter_limit,
+                cap_end: stroke.cap_end,
+                cap_start: stroke.cap_start,
+                cap_start_scale: stroke.cap_start_scale,
+                cap_end_scale: stroke.cap_end_scale,
+                kind: stroke.kind,
+            });
+        }
+
+        plain_shape.clear_shadows();
+        plain_shape.blur = None;
+        plain_shape.noise = None;
+        plain_shape
+    }
+
+    /// Removes the part of the drop shadows surface below the shape, so its
+    /// shadow doesn't show through semi-transparent fills.
+    fn knock_out_drop_shadow(
+        &mut self,
+        shape: &Shape,
+        clip_bounds: Option<ClipStack>,
+        scale: f32,
+        translation: (f32, f32),
+    ) {
+        let silhouette = Self::black_silhouette(shape);
+        let mut paint = skia::Paint::default();
+        paint.set_blend_mode(skia::BlendMode::DstOut);
+        let layer_rec = skia::canvas::SaveLayerRec::default().paint(&paint);
+
+        let canvas = self.surfaces.canvas(SurfaceId::DropShadows);
+        canvas.save_layer(&layer_rec);
+        canvas.scale((scale, scale));
+        canvas.translate(translation);
+
+        self.with_nested_blurs_suppressed(|state| {
+            state.render_shape(
+                &silhouette,
+                clip_bounds,
+                SurfaceId::DropShadows,
+                SurfaceId::DropShadows,
+                SurfaceId::DropShadows,
+                SurfaceId::DropShadows,
+                false,
+                None,
+                None,
+            );
+        });
+        self.surfaces.canvas(SurfaceId::DropShadows).restore();
+    }
+
     /// Renders a drop shadow effect for the given shape.
     ///
     /// Creates a black shadow by converting the original shadow color to black,
@@ -1507,7 +1798,6 @@ impl RenderState {
         transformed_shadow.to_mut().offset = (0.0, 0.0);
         transformed_shadow.to_mut().color = skia::Color::BLACK;
 
-        let mut plain_shape = Cow::Borrowed(shape);
         let combined_blur =
             Self::combine_blur_values(self.combined_layer_blur(shape.blur), extra_layer_blur);
         let blur_filter = combined_blur
@@ -1525,29 +1815,7 @@ impl RenderState {
         let mapped = transform_matrix.map_vector((shadow.offset.0, shadow.offset.1));
         let world_offset = (mapped.x, mapped.y);
 
-        // The opacity of fills and strokes shouldn't affect the shadow,
-        // so we paint everything black with the same opacity
-        plain_shape.to_mut().clear_fills();
-        if shape.has_fills() {
-            plain_shape
-                .to_mut()
-                .add_fill(Fill::Solid(SolidColor(skia::Color::BLACK)));
-        }
-
-        plain_shape.to_mut().clear_strokes();
-        for stroke in shape.strokes.iter() {
-            plain_shape.to_mut().add_stroke(Stroke {
-                fill: Fill::Solid(SolidColor(skia::Color::BLACK)),
-                width: stroke.width,
-                style: stroke.style,
-                cap_end: stroke.cap_end,
-                cap_start: stroke.cap_start,
-                kind: stroke.kind,
-            });
-        }
-
-        plain_shape.to_mut().clear_shadows();
-        plain_shape.to_mut().blur = None;
+        let plain_shape = Self::black_silhouette(shape);
 
         let Some(drop_filter) = transformed_shadow.get_drop_shadow_filter() else {
             return;
@@ -1685,11 +1953,11 @@ impl RenderState {
                 let is_visible = if is_container || has_effects {
                     let element_extrect =
                         extrect.get_or_insert_with(|| transformed_element.extrect(tree, scale));
-                    element_extrect.intersects(self.render_area)
+                    element_extrect.intersects(self.backdrop_area)
                         && !transformed_element.visually_insignificant(scale, tree)
                 } else {
                     let selrect = transformed_element.selrect();
-                    selrect.intersects(self.render_area)
+                    selrect.intersects(self.backdrop_area)
                         && !transformed_element.visually_insignificant(scale, tree)
                 };
 
@@ -1707,7 +1975,7 @@ impl RenderState {
             // If a container was flattened, it doesn't affect children visually, so we skip
             // the expensive enter/exit operations and process children directly
             if !element.can_flatten() {
-                self.render_shape_enter(element, mask);
+                self.render_shape_enter(element, mask, clip_bounds.as_ref());
             }
 
             if !node_render_state.is_root() && self.focus_mode.is_active() {
@@ -1841,51 +2109,50 @@ impl RenderState {
                             .canvas(SurfaceId::DropShadows)
                             .draw_paint(&paint);
 
-                        self.surfaces.canvas(SurfaceId::DropShadows).restore();
-                    }
-                }
-
-                if let Some(clips) = clip_bounds.as_ref() {
-                    let antialias = element.should_use_antialias(scale);
-
-                    self.surfaces.canvas(SurfaceId::Current).save();
-                    for (bounds, corners, transform) in clips.iter() {
-                        let mut total_matrix = Matrix::new_identity();
-                        total_matrix.pre_scale((scale, scale), None);
-                        total_matrix.pre_translate((translation.0, translation.1));
-                        total_matrix.pre_concat(transform);
-
-                        self.surfaces
-                            .canvas(SurfaceId::Current)
-                            .concat(&total_matrix);
-
-                        if let Some(corners) = corners {
-                            let rrect = RRect::new_rect_radii(*bounds, corners);
-                            self.surfaces.canvas(SurfaceId::Current).clip_rrect(
-                                rrect,
-                                skia::ClipOp::Intersect,
-                                antialias,
-                            );
-                        } else {
-                            self.surfaces.canvas(SurfaceId::Current).clip_rect(
-                                *bounds,
-                                skia::ClipOp::Intersect,
-                                antialias,
+                        if !shadow.show_behind {
+                            self.knock_out_drop_shadow(
+                                element,
+                                clip_bounds.clone(),
+                                scale,
+                                translation,
                             );
+                            if !matches!(element.shape_type, Type::Bool(_)) {
+                                for child_id in element.children.iter() {
+                                    let Some(child) = tree.get(child_id) else {
+                                        continue;
+                                    };
+                                    if child.hidden || matches!(child.shape_type, Type::Text(_)) {
+                                        continue;
+                                    }
+                                    let clip_bounds = node_render_state
+                                        .get_nested_shadow_clip_bounds(element, shadow);
+                                    self.knock_out_drop_shadow(
+                                        child,
+                                        clip_bounds,
+                                        scale,
+                                        translation,
+                                    );
+                                }
+                            }
                         }
 
+                        self.surfaces.canvas(SurfaceId::DropShadows).restore();
+
+                        // Every shadow is composited on its own, so its blend mode
+                        // applies to what is drawn below instead of the other shadows.
+                        let mut paint = skia::Paint::default();
+                        paint.set_blend_mode(shadow.blend_mode.into());
+                        self.composite_drop_shadows(
+                            element,
+                            clip_bounds.as_ref(),
+                            scale,
+                            translation,
+                            &paint,
+                        );
                         self.surfaces
-                            .canvas(SurfaceId::Current)
-                            .concat(&total_matrix.invert().unwrap_or_default());
+                            .canvas(SurfaceId::DropShadows)
+                            .clear(skia::Color::TRANSPARENT);
                     }
-
-                    self.surfaces
-                        .draw_into(SurfaceId::DropShadows, SurfaceId::Current, None);
-
-                    self.surfaces.canvas(SurfaceId::Current).restore();
-                } else {
-                    self.surfaces
-                        .draw_into(SurfaceId::DropShadows, SurfaceId::Current, None);
                 }
 
                 self.surfaces
@@ -1946,26 +2213,7 @@ impl RenderState {
                     element.children_ids_iter(false).copied().collect()
                 };
 
-                // Z-index ordering
-                // For reverse flex layouts with custom z-indexes, we reverse the base order
-                // so that visual stacking matches visual position
-                let children_ids = if element.has_layout() {
-                    let mut ids = children_ids;
-                    let has_z_index = ids
-                        .iter()
-                        .any(|id| tree.get(id).map(|s| s.has_z_index()).unwrap_or(false));
-                    if element.is_flex_reverse() && has_z_index {
-                        ids.reverse();
-                    }
-                    ids.sort_by(|id1, id2| {
-                        let z1 = tree.get(id1).map(|s| s.z_index()).unwrap_or(0);
-                        let z2 = tree.get(id2).map(|s| s.z_index()).unwrap_or(0);
-                        z2.cmp(&z1)
-                    });
-                    ids
-                } else {
-                    children_ids
-                };
+                let children_ids = sort_children_by_z_index(tree, element, children_ids);
 
                 for child_id in children_ids.iter() {
                     self.pending_nodes.push(NodeRenderState {
@@ -2067,25 +2315,23 @@ impl RenderState {
                 self.update_render_context(next_tile);
 
                 if !self.surfaces.has_cached_tile_surface(next_tile) {
-                    if let Some(ids) = self.tiles.get_shapes_at(next_tile) {
-                        // We only need first level shapes, in the same order as the parent node
-                        let mut valid_ids = Vec::with_capacity(ids.len());
-                        for root_id in root_ids.iter() {
-                            if ids.contains(root_id) {
-                                valid_ids.push(*root_id);
-                            }
+                    let ids = self.update_backdrop_area(next_tile, tree);
+                    // We only need first level shapes, in the same order as the parent node
+                    let mut valid_ids = Vec::with_capacity(ids.len());
+                    for root_id in root_ids.iter() {
+                        if ids.contains(root_id) {
+                            valid_ids.push(*root_id);
                         }
+                    }
 
-                        self.pending_nodes.extend(valid_ids.into_iter().map(|id| {
-                            NodeRenderState {
-                                id,
-                                visited_children: false,
-                                clip_bounds: None,
-                                visited_mask: false,
-                                mask: false,
-                            }
+                    self.pending_nodes
+                        .extend(valid_ids.into_iter().map(|id| NodeRenderState {
+                            id,
+                            visited_children: false,
+                            clip_bounds: None,
+                            visited_mask: false,
+                            mask: false,
                         }));
-                    }
                 }
             } else {
                 should_stop = true;
@@ -2122,7 +2368,13 @@ impl RenderState {
      */
     pub fn get_tiles_for_shape(&mut self, shape: &Shape, tree: ShapesPoolRef) -> TileRect {
         let scale = self.get_scale();
-        let extrect = self.get_cached_extrect(shape, tree, scale);
+        let mut extrect = self.get_cached_extrect(shape, tree, scale);
+        // Background blurs read what is drawn around them, so they are also
+        // kept in the tiles within the blur radius.
+        if let Some(blur) = shape.background_blur() {
+            let reach = blur.reach();
+            extrect.outset((reach, reach));
+        }
         let tile_size = tiles::get_tile_size(scale);
         let shape_tiles = tiles::get_tiles_for_rect(extrect, tile_size);
         let interest_rect = &self.tile_viewbox.interest_rect;
@@ -2281,6 +2533,7 @@ impl RenderState {
                 }
             }
         }
+        self.add_background_blur_tiles(&mut all_tiles, tree);
 
         // Update the changed tiles
         for tile in all_tiles {
@@ -2308,12 +2561,37 @@ impl RenderState {
                 all_tiles.extend(self.update_shape_tiles(shape, tree));
             }
         }
+        self.add_background_blur_tiles(&mut all_tiles, tree);
         for tile in all_tiles {
             self.remove_cached_tile(tile);
         }
         performance::end_measure!("invalidate_and_update_tiles");
     }
 
+    /// Background blurs depend on what is drawn below them, so a change in one
+    /// of their tiles invalidates every tile they are in.
+    fn add_background_blur_tiles(
+        &mut self,
+        all_tiles: &mut HashSet<tiles::Tile>,
+        tree: ShapesPoolRef,
+    ) {
+        let mut blurred = HashSet::<Uuid>::new();
+        for tile in all_tiles.iter() {
+            if let Some(ids) = self.tiles.get_shapes_at(*tile) {
+                blurred.extend(ids.iter().filter(|id| {
+                    tree.get(id)
+                        .is_some_and(|shape| shape.background_blur().is_some())
+                }));
+            }
+        }
+
+        for id in blurred {
+            if let Some(tiles) = self.tiles.get_tiles_of(id) {
+                all_tiles.extend(tiles.iter().copied());
+            }
+        }
+    }
+
     /// Rebuilds tiles for shapes with modifiers and processes their ancestors
     ///
     /// This function applies transformation modifiers to shapes and updates their tiles.
@@ -2357,3 +2635,79 @@ impl RenderState {
         self.viewbox.set_all(zoom, x, y);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shapes::{self, Blur, BlurType, Frame, Type};
+    use crate::state::ShapesPool;
+    use crate::tiles::Tile;
+
+    /// A shape with a background blur reaching 30 units, from the first tile into
+    /// the second one, a shape in the second tile and a shape in the third one.
+    fn blurred_tree() -> (ShapesPool, [Uuid; 3]) {
+        let ids = [Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()];
+        let mut tree = ShapesPool::new();
+
+        let root = tree.add_shape(Uuid::nil());
+        root.set_shape_type(Type::Frame(Frame::default()));
+        for id in ids {
+            root.add_child(id);
+        }
+
+        let rects = [
+            (400., 100., 500., 200.),
+            (520., 100., 600., 200.),
+            (1100., 100., 1200., 200.),
+        ];
+        for (id, (left, top, right, bottom)) in ids.iter().zip(rects) {
+            let shape = tree.add_shape(*id);
+            shape.set_selrect(left, top, right, bottom);
+            shape.set_shape_type(Type::Rect(shapes::Rect::default()));
+            shape.set_parent(Uuid::nil());
+        }
+        if let Some(shape) = tree.get_mut(&ids[0]) {
+            shape.set_blur(Some(Blur::new(BlurType::BackgroundBlur, false, 10.)));
+        }
+
+        (tree, ids)
+    }
+
+    #[test]
+    fn test_backdrop_area() {
+        let (tree, [blurred, near, far]) = blurred_tree();
+        let mut render_state = RenderState::new(1536, 512, RenderBackend::raster());
+        render_state.rebuild_tiles_from(&tree, None);
+
+        render_state.update_render_context(Tile::from(0, 0));
+        let ids = render_state.update_backdrop_area(Tile::from(0, 0), &tree);
+        assert_eq!(
+            render_state.backdrop_area,
+            Rect::from_ltrb(-30., -30., 542., 542.)
+        );
+        assert!(ids.contains(&blurred) && ids.contains(&near));
+        assert!(!ids.contains(&far));
+
+        // Tiles without background blurs only draw their own shapes.
+        render_state.update_render_context(Tile::from(2, 0));
+        let ids = render_state.update_backdrop_area(Tile::from(2, 0), &tree);
+        assert_eq!(render_state.backdrop_area, render_state.render_area);
+        assert_eq!(ids, HashSet::from([far]));
+    }
+
+    #[test]
+    fn test_background_blur_tile_invalidation() {
+        let (tree, _) = blurred_tree();
+        let mut render_state = RenderState::new(1536, 512, RenderBackend::raster());
+        render_state.rebuild_tiles_from(&tree, None);
+
+        // A change next to the blur invalidates every tile the blur is in.
+        let mut tiles = HashSet::from([Tile::from(1, 0)]);
+        render_state.add_background_blur_tiles(&mut tiles, &tree);
+        assert_eq!(tiles, HashSet::from([Tile::from(0, 0), Tile::from(1, 0)]));
+
+        let mut tiles = HashSet::from([Tile::from(2, 0)]);
+        render_state.add_background_blur_tiles(&mut tiles, &tree);
+        assert_eq!(tiles, HashSet::from([Tile::from(2, 0)]));
+    }
+}
@@ -0,0 +1,128 @@
+use skia_safe::gpu::DirectContext;
+use skia_safe::{self as skia, ISize};
+
+use super::gpu_state::GpuState;
+
+/// Selects where the render surfaces live.
+///
+/// `Gpu` is the regular WebGL path used in the browser. `Raster` allocates every
+/// surface in CPU memory, so the same render pipeline can run headless (unit
+/// tests, native tools, server-side jobs) without a GL context.
+#[derive(Debug, Clone)]
+pub enum RenderBackend {
+    Gpu(GpuState),
+    Raster,
+}
+
+impl RenderBackend {
+    /// Creates a GPU backend. This needs to be done once per WebGL context.
+    pub fn gpu() -> Self {
+        RenderBackend::Gpu(GpuState::new())
+    }
+
+    pub fn raster() -> Self {
+        RenderBackend::Raster
+    }
+
+    pub fn direct_context(&self) -> Option<DirectContext> {
+        match self {
+            RenderBackend::Gpu(gpu_state) => Some(gpu_state.context.clone()),
+            RenderBackend::Raster => None,
+        }
+    }
+
+    pub fn create_surface_with_isize(&mut self, label: String, size: ISize) -> skia::Surface {
+        self.create_surface_with_dimensions(label, size.width, size.height)
+    }
+
+    pub fn create_surface_with_dimensions(
+        &mut self,
+        label: String,
+        width: i32,
+        height: i32,
+    ) -> skia::Surface {
+        match self {
+            RenderBackend::Gpu(gpu_state) => {
+                gpu_state.create_surface_with_dimensions(label, width, height)
+            }
+            RenderBackend::Raster => create_raster_surface(width, height),
+        }
+    }
+
+    /// Create a Skia surface that will be used for rendering.
+    pub fn create_target_surface(&mut self, width: i32, height: i32) -> skia::Surface {
+        match self {
+            RenderBackend::Gpu(gpu_state) => gpu_state.create_target_surface(width, height),
+            RenderBackend::Raster => create_raster_surface(width, height),
+        }
+    }
+
+    /// Raster surfaces draw directly into their pixels, so there is nothing
+    /// to submit for them.
+    pub fn flush_and_submit(&mut self, surface: &mut skia::Surface) {
+        if let RenderBackend::Gpu(gpu_state) = self {
+            gpu_state.context.flush_and_submit_surface(surface, None);
+        }
+    }
+}
+
+fn create_raster_surface(width: i32, height: i32) -> skia::Surface {
+    skia::surfaces::raster_n32_premul((width.max(1), height.max(1)))
+        .expect("Failed to create raster surface")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::{RenderState, SurfaceId};
+    use crate::shapes::{self, Fill, Frame, SolidColor, Type};
+    use crate::state::ShapesPool;
+    use crate::uuid::Uuid;
+
+    fn pixel(image: &skia::Image, x: i32, y: i32) -> [u8; 4] {
+        let info = skia::ImageInfo::new(
+            (1, 1),
+            skia::ColorType::RGBA8888,
+            skia::AlphaType::Unpremul,
+            None,
+        );
+        let mut pixel = [0; 4];
+        assert!(image.read_pixels(
+            &info,
+            &mut pixel,
+            4,
+            (x, y),
+            skia::image::CachingHint::Allow
+        ));
+        pixel
+    }
+
+    #[test]
+    fn test_raster_render() {
+        let rect_id = Uuid::new_v4();
+        let mut tree = ShapesPool::new();
+
+        let root = tree.add_shape(Uuid::nil());
+        root.set_shape_type(Type::Frame(Frame::default()));
+        root.add_child(rect_id);
+
+        let rect = tree.add_shape(rect_id);
+        rect.set_selrect(16.0, 16.0, 48.0, 48.0);
+        rect.set_shape_type(Type::Rect(shapes::Rect::default()));
+        rect.add_fill(Fill::Solid(SolidColor(skia::Color::RED)));
+        rect.set_parent(Uuid::nil());
+
+        let mut render_state = RenderState::new(64, 64, RenderBackend::raster());
+        assert!(render_state.backend.direct_context().is_none());
+
+        render_state.rebuild_tiles_from(&tree, None);
+        render_state
+            .start_render_loop(None, &tree, 0, true)
+            .expect("the raster backend should render synchronously");
+
+        let image = render_state.surfaces.snapshot(SurfaceId::Target);
+        assert_eq!((image.width(), image.height()), (64, 64));
+        assert_eq!(pixel(&image, 32, 32), [255, 0, 0, 255]);
+        assert_eq!(pixel(&image, 4, 4)[3], 0);
+    }
+}
@@ -0,0 +1,352 @@
+use skia_safe::{self as skia, EncodedImageFormat, IRect, Matrix, Rect};
+
+use super::{
+    sort_children_by_z_index, FocusMode, NodeRenderState, RenderOptions, RenderState, SurfaceId,
+};
+use crate::shapes::{FillLayer, Shape, Type};
+use crate::state::ShapesPoolRef;
+use crate::tiles::{self, TileHashMap, TileRect};
+use crate::uuid::Uuid;
+
+/// Renders the subtree of `id` off-screen at `scale` and returns it encoded in `format`.
+///
+/// The export doesn't depend on the workspace viewbox nor on the tile cache: the shape
+/// extrect is split in tiles that are rendered with the regular pipeline and composited
+/// into a surface with the exact size of the shape. `background` is painted below the
+/// shape; without it the transparent areas are kept (or blended on black for JPEG).
+#[allow(clippy::too_many_arguments)]
+pub fn render_shape_image(
+    render_state: &mut RenderState,
+    tree: ShapesPoolRef,
+    id: &Uuid,
+    scale: f32,
+    format: EncodedImageFormat,
+    quality: u32,
+    background: Option<skia::Color>,
+    timestamp: i32,
+) -> Result<Vec<u8>, String> {
+    let mut surface = render_shape_surface(render_state, tree, id, scale, background, timestamp)?;
+
+    let image = surface.image_snapshot();
+    let mut context = surface.direct_context();
+    let data = image
+        .encode(context.as_mut(), format, quality)
+        .ok_or("Error encoding the exported image")?;
+
+    Ok(data.as_bytes().to_vec())
+}
+
+/// Renders the subtree of `id` into a new surface, in device pixels, using the
+/// same backend as the render target.
+pub fn render_shape_surface(
+    render_state: &mut RenderState,
+    tree: ShapesPoolRef,
+    id: &Uuid,
+    scale: f32,
+    background: Option<skia::Color>,
+    timestamp: i32,
+) -> Result<skia::Surface, String> {
+    if !scale.is_finite() || scale <= 0.0 {
+        return Err(format!("Invalid export scale: {}", scale));
+    }
+
+    let Some(shape) = tree.get(id) else {
+        return Err(format!("Shape {} not found", id));
+    };
+
+    // Snap the exported area to the pixel grid so the tiles land on whole pixels.
+    let extrect = shape.extrect(tree, scale);
+    let area = IRect::from_ltrb(
+        (extrect.left * scale).floor() as i32,
+        (extrect.top * scale).floor() as i32,
+        (extrect.right * scale).ceil() as i32,
+        (extrect.bottom * scale).ceil() as i32,
+    );
+
+    if area.is_empty() {
+        return Err(format!("Shape {} has nothing to render", id));
+    }
+
+    let mut surface = render_state
+        .surfaces
+        .new_surface_with_dimensions(area.size())
+        .ok_or("Error creating the export surface")?;
+
+    surface
+        .canvas()
+        .clear(background.unwrap_or(skia::Color::TRANSPARENT));
+
+    let was_rendering = render_state.render_in_progress;
+    render_state.cancel_animation_frame();
+
+    let saved = ExportContext::enter(render_state, scale);
+    let result = render_tiles(
+        render_state,
+        tree,
+        id,
+        extrect,
+        area,
+        &mut surface,
+        timestamp,
+    );
+    saved.exit(render_state);
+
+    // The export reuses the render surfaces, so an interrupted workspace render has
+    // to start again. Cached tiles are still valid, so this is cheap.
+    if was_rendering {
+        render_state.start_render_loop(None, tree, timestamp, false)?;
+    }
+
+    result.map(|_| surface)
+}
+
+fn render_tiles(
+    render_state: &mut RenderState,
+    tree: ShapesPoolRef,
+    id: &Uuid,
+    extrect: Rect,
+    area: IRect,
+    surface: &mut skia::Surface,
+    timestamp: i32,
+) -> Result<(), String> {
+    let tile_size = tiles::get_tile_size(render_state.get_scale());
+    let TileRect(sx, sy, ex, ey) = tiles::get_tiles_for_rect(extrect, tile_size);
+    let reach = background_blur_reach(tree, id);
+
+    for tile_y in sy..=ey {
+        for tile_x in sx..=ex {
+            let tile = tiles::Tile::from(tile_x, tile_y);
+            render_state.update_render_context(tile);
+            render_state.backdrop_area.outset((reach, reach));
+            render_state
+                .surfaces
+                .canvas(SurfaceId::Current)
+                .clear(skia::Color::TRANSPARENT);
+
+            render_state.pending_nodes.push(NodeRenderState {
+                id: *id,
+                visited_children: false,
+                clip_bounds: None,
+                visited_mask: false,
+                mask: false,
+            });
+            render_state.render_shape_tree_partial_uncached(tree, timestamp, false)?;
+
+            let tile_rect = Rect::from_xywh(
+                tile_x as f32 * tiles::TILE_SIZE - area.left as f32,
+                tile_y as f32 * tiles::TILE_SIZE - area.top as f32,
+                tiles::TILE_SIZE,
+                tiles::TILE_SIZE,
+            );
+            render_state
+                .surfaces
+                .draw_current_tile_into(surface.canvas(), &tile_rect);
+        }
+    }
+
+    Ok(())
+}
+
+/// Largest reach of the background blurs in the subtree of `id`. Every tile
+/// draws the shapes within it, so the backdrops don't end at the tile edges.
+pub fn background_blur_reach(tree: ShapesPoolRef, id: &Uuid) -> f32 {
+    let mut reach: f32 = 0.;
+    let mut nodes = vec![*id];
+    while let Some(id) = nodes.pop() {
+        if let Some(shape) = tree.get(&id) {
+            if let Some(blur) = shape.background_blur() {
+                reach = reach.max(blur.reach());
+            }
+            nodes.extend(shape.children_ids_iter(false).copied());
+        }
+    }
+    reach
+}
+
+/// Keeps the parts of the render state that an export overrides, so the workspace
+/// render is not affected by it.
+struct ExportContext {
+    options: RenderOptions,
+    viewbox_zoom: f32,
+    tiles: TileHashMap,
+    focus_mode: FocusMode,
+    current_tile: Option<tiles::Tile>,
+    render_area: Rect,
+    backdrop_area: Rect,
+}
+
+impl ExportContext {
+    fn enter(render_state: &mut RenderState, scale: f32) -> Self {
+        render_state.reset_canvas();
+        render_state.pending_nodes.clear();
+        render_state.nested_fills.clear();
+        render_state.nested_blurs.clear();
+        render_state.nested_shadows.clear();
+        render_state.render_in_progress = false;
+
+        let saved = ExportContext {
+            // Exports are always rendered at full quality, without debug overlays
+            // and ignoring the device pixel ratio.
+            options: std::mem::take(&mut render_state.options),
+            viewbox_zoom: render_state.viewbox.zoom,
+            // The tile index is scale dependent, we use a throwaway one.
+            tiles: std::mem::replace(&mut render_state.tiles, TileHashMap::new()),
+            focus_mode: std::mem::replace(&mut render_state.focus_mode, FocusMode::new()),
+            current_tile: render_state.current_tile,
+            render_area: render_state.render_area,
+            backdrop_area: render_state.backdrop_area,
+        };
+        render_state.viewbox.zoom = scale;
+        saved
+    }
+
+    fn exit(self, render_state: &mut RenderState) {
+        render_state.pending_nodes.clear();
+        render_state.options = self.options;
+        render_state.viewbox.zoom = self.viewbox_zoom;
+        render_state.tiles = self.tiles;
+        render_state.focus_mode = self.focus_mode;
+        render_state.current_tile = self.current_tile;
+        render_state.render_area = self.render_area;
+        render_state.backdrop_area = self.backdrop_area;
+        render_state.reset_canvas();
+    }
+}
+
+// Helpers shared by the vector exports (PDF and SVG), which walk the tree on their
+// own instead of going through the render loop.
+
+/// Transform of the shape around its center, like the renderer applies it.
+pub fn shape_matrix(shape: &Shape) -> Matrix {
+    let center = shape.center();
+    let mut matrix = shape.transform;
+    matrix.post_translate(center);
+    matrix.pre_translate(-center);
+    matrix
+}
+
+/// Children of `shape` in the order they're drawn.
+pub fn children<'a>(tree: ShapesPoolRef<'a>, shape: &Shape) -> Vec<&'a Shape> {
+    let children_ids = shape.children_ids_iter(false).copied().collect();
+    let children_ids = sort_children_by_z_index(tree, shape, children_ids);
+
+    // The render loop pops the children from a stack, so they're drawn in reverse.
+    children_ids
+        .iter()
+        .rev()
+        .filter_map(|id| tree.get(id))
+        .collect()
+}
+
+/// Fills painted by `shape`, in drawing order. Shapes without fills take the
+/// ones of the group they're in, `nested_fills`.
+pub fn shape_fills<'a>(shape: &'a Shape, nested_fills: &'a [FillLayer]) -> Vec<&'a FillLayer> {
+    let inherits_fills = shape.fills.is_empty()
+        && !matches!(shape.shape_type, Type::Group(_) | Type::Frame(_))
+        && !shape
+            .svg_attrs
+            .as_ref()
+            .is_some_and(|attrs| attrs.fill_none);
+
+    if inherits_fills {
+        nested_fills.iter().collect()
+    } else {
+        shape.fills().rev().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem;
+    use crate::render::RenderBackend;
+    use crate::shapes::{self, Fill, SolidColor};
+    use crate::state::ShapesPool;
+
+    fn decode(data: &[u8]) -> skia::Image {
+        skia::Image::from_encoded(skia::Data::new_copy(data)).expect("the export should decode")
+    }
+
+    fn center_pixel(image: &skia::Image) -> [u8; 4] {
+        let info = skia::ImageInfo::new(
+            (1, 1),
+            skia::ColorType::RGBA8888,
+            skia::AlphaType::Unpremul,
+            None,
+        );
+        let mut pixel = [0; 4];
+        let center = (image.width() / 2, image.height() / 2);
+        assert!(image.read_pixels(
+            &info,
+            &mut pixel,
+            4,
+            center,
+            skia::image::CachingHint::Allow
+        ));
+        pixel
+    }
+
+    #[test]
+    fn test_encoded_export_roundtrip() {
+        let id = Uuid::new_v4();
+        let mut tree = ShapesPool::new();
+        let shape = tree.add_shape(id);
+        shape.set_selrect(10.0, 10.0, 50.0, 30.0);
+        shape.set_shape_type(Type::Rect(shapes::Rect::default()));
+        shape.add_fill(Fill::Solid(SolidColor(skia::Color::RED)));
+
+        let mut render_state = RenderState::new(1, 1, RenderBackend::raster());
+        let data = render_shape_image(
+            &mut render_state,
+            &tree,
+            &id,
+            2.0,
+            EncodedImageFormat::PNG,
+            100,
+            None,
+            0,
+        )
+        .unwrap();
+
+        // The wasm API returns the encoded bytes after their length.
+        mem::write_length_prefixed(&data);
+        let bytes = mem::bytes();
+        let (length, encoded) = bytes.split_at(4);
+        assert_eq!(
+            u32::from_le_bytes(length.try_into().unwrap()) as usize,
+            data.len()
+        );
+        assert_eq!(encoded, data.as_slice());
+
+        let image = decode(encoded);
+        assert_eq!((image.width(), image.height()), (80, 40));
+        assert_eq!(center_pixel(&image), [255, 0, 0, 255]);
+
+        let data = render_shape_image(
+            &mut render_state,
+            &tree,
+            &id,
+            1.0,
+            EncodedImageFormat::JPEG,
+            90,
+            Some(skia::Color::WHITE),
+            0,
+        )
+        .unwrap();
+        let image = decode(&data);
+        assert_eq!((image.width(), image.height()), (40, 20));
+        let [r, g, b, a] = center_pixel(&image);
+        assert!(r > 240 && g < 16 && b < 16 && a == 255);
+    }
+
+    #[test]
+    fn test_export_errors() {
+        let mut render_state = RenderState::new(1, 1, RenderBackend::raster());
+        let tree = ShapesPool::new();
+        let id = Uuid::new_v4();
+        let result = render_shape_surface(&mut render_state, &tree, &id, 0.0, None, 0);
+        assert_eq!(result.err(), Some("Invalid export scale: 0".to_string()));
+        let result = render_shape_surface(&mut render_state, &tree, &id, 1.0, None, 0);
+        assert_eq!(result.err(), Some(format!("Shape {} not found", id)));
+    }
+}
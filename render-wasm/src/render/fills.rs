@@ -1,32 +1,25 @@
 use skia_safe::{self as skia, Paint, RRect};
 
-use super::{filters, RenderState, SurfaceId};
-use crate::render::get_source_rect;
-use crate::shapes::{Fill, Frame, ImageFill, Rect, Shape, Type};
+use super::{filters, surfaces, ImageStore, RenderState, SurfaceId};
+use crate::shapes::{Fill, FillLayer, Frame, ImageFill, Rect, Shape, Type};
 
 fn draw_image_fill(
-    render_state: &mut RenderState,
+    canvas: &skia::Canvas,
+    image: &skia::Image,
+    sampling_options: skia::SamplingOptions,
     shape: &Shape,
     image_fill: &ImageFill,
     paint: &Paint,
     antialias: bool,
-    surface_id: SurfaceId,
 ) {
-    let image = render_state.images.get(&image_fill.id());
-    if image.is_none() {
-        return;
-    }
-
-    let size = image.unwrap().dimensions();
-    let canvas = render_state.surfaces.canvas_and_mark_dirty(surface_id);
     let container = &shape.selrect;
     let path_transform = shape.to_path_transform();
 
-    let src_rect = get_source_rect(size, container, image_fill);
-    let dest_rect = container;
-
+    // The layer blends the image with the fills below, so the image itself is
+    // drawn normally inside it.
     let mut image_paint = skia::Paint::default();
     image_paint.set_anti_alias(antialias);
+    image_paint.set_blend_mode(paint.blend_mode_or(skia::BlendMode::SrcOver));
     if let Some(filter) = shape.image_filter(1.) {
         image_paint.set_image_filter(filter.clone());
     }
@@ -73,32 +66,32 @@ fn draw_image_fill(
         Type::Text(_) => unimplemented!("TODO"),
     }
 
-    // Draw the image with the calculated destination rectangle
-    if let Some(image) = image {
-        canvas.draw_image_rect_with_sampling_options(
-            image,
-            Some((&src_rect, skia::canvas::SrcRectConstraint::Strict)),
-            dest_rect,
-            render_state.sampling_options,
-            paint,
-        );
-    }
+    // Paint the container with the image laid over it
+    let mut paint = paint.clone();
+    paint.set_blend_mode(skia::BlendMode::SrcOver);
+    paint.set_shader(image_fill.to_shader(image, container, sampling_options));
+    canvas.draw_rect(container, &paint);
 
     // Restore the canvas to remove the clipping
     canvas.restore();
 }
 
 /**
- * This SHOULD be the only public function in this module.
+ * This SHOULD be the only public function in this module, along with its
+ * `render_to_canvas` counterpart for exports.
+ *
+ * Fills are composited in order, each one blended over the ones already drawn
+ * with the blend mode and opacity of its layer.
  */
 pub fn render(
     render_state: &mut RenderState,
     shape: &Shape,
-    fill: &Fill,
+    layer: &FillLayer,
     antialias: bool,
     surface_id: SurfaceId,
 ) {
-    let mut paint = fill.to_paint(&shape.selrect, antialias);
+    let fill = &layer.fill;
+    let mut paint = layer.to_paint(&shape.selrect, antialias);
     if let Some(image_filter) = shape.image_filter(1.) {
         let bounds = image_filter.compute_fast_bounds(shape.selrect);
         if filters::render_with_filter_surface(
@@ -127,28 +120,75 @@ fn draw_fill_to_surface(
     antialias: bool,
     surface_id: SurfaceId,
     paint: &Paint,
+) {
+    draw_fill(
+        render_state.surfaces.canvas_and_mark_dirty(surface_id),
+        &mut render_state.images,
+        render_state.sampling_options,
+        shape,
+        fill,
+        antialias,
+        paint,
+    );
+}
+
+/// Draws a fill straight into `canvas`, outside of the render surfaces. Used by
+/// the document exports, where the canvas belongs to the output document.
+pub fn render_to_canvas(
+    render_state: &mut RenderState,
+    canvas: &skia::Canvas,
+    shape: &Shape,
+    layer: &FillLayer,
+    antialias: bool,
+) {
+    let fill = &layer.fill;
+    let mut paint = layer.to_paint(&shape.selrect, antialias);
+    if let Some(image_filter) = shape.image_filter(1.) {
+        paint.set_image_filter(image_filter);
+    }
+
+    draw_fill(
+        canvas,
+        &mut render_state.images,
+        render_state.sampling_options,
+        shape,
+        fill,
+        antialias,
+        &paint,
+    );
+}
+
+fn draw_fill(
+    canvas: &skia::Canvas,
+    images: &mut ImageStore,
+    sampling_options: skia::SamplingOptions,
+    shape: &Shape,
+    fill: &Fill,
+    antialias: bool,
+    paint: &Paint,
 ) {
     match (fill, &shape.shape_type) {
         (Fill::Image(image_fill), _) => {
-            draw_image_fill(
-                render_state,
-                shape,
-                image_fill,
-                paint,
-                antialias,
-                surface_id,
-            );
+            if let Some(image) = images.get(&image_fill.id()) {
+                draw_image_fill(
+                    canvas,
+                    image,
+                    sampling_options,
+                    shape,
+                    image_fill,
+                    paint,
+                    antialias,
+                );
+            }
         }
         (_, Type::Rect(_) | Type::Frame(_)) => {
-            render_state.surfaces.draw_rect_to(surface_id, shape, paint);
+            surfaces::draw_rect_on_canvas(canvas, shape, paint);
         }
         (_, Type::Circle) => {
-            render_state
-                .surfaces
-                .draw_circle_to(surface_id, shape, paint);
+            surfaces::draw_circle_on_canvas(canvas, shape, paint);
         }
         (_, Type::Path(_)) | (_, Type::Bool(_)) => {
-            render_state.surfaces.draw_path_to(surface_id, shape, paint);
+            surfaces::draw_path_on_canvas(canvas, shape, paint);
         }
         (_, Type::Group(_)) => {
             // Groups can have fills but they propagate them to their children
@@ -23,6 +23,8 @@ pub struct FontStore {
     font_collection: textlayout::FontCollection,
     debug_font: Font,
     fallback_fonts: HashSet<String>,
+    // Uploaded families and whether they are the emoji font.
+    families: Vec<(FontFamily, bool)>,
 }
 
 impl FontStore {
@@ -44,6 +46,7 @@ impl FontStore {
             font_collection,
             debug_font,
             fallback_fonts: HashSet::new(),
+            families: Vec::new(),
         }
     }
 
@@ -94,6 +97,8 @@ impl FontStore {
             self.fallback_fonts.insert(alias);
         }
 
+        self.families.push((family, is_emoji));
+
         Ok(())
     }
 
@@ -107,6 +112,14 @@ impl FontStore {
         self.font_provider.family_names().any(|x| x == font_name)
     }
 
+    /// Families added with `add`, with their `is_emoji` and `is_fallback` flags.
+    pub fn families(&self) -> impl Iterator<Item = (FontFamily, bool, bool)> + '_ {
+        self.families.iter().map(|(family, is_emoji)| {
+            let is_fallback = self.fallback_fonts.contains(&family.alias());
+            (*family, *is_emoji, is_fallback)
+        })
+    }
+
     pub fn get_fallback(&self) -> &HashSet<String> {
         &self.fallback_fonts
     }
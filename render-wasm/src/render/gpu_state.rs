@@ -1,5 +1,5 @@
+use skia_safe as skia;
 use skia_safe::gpu::{self, gl::FramebufferInfo, gl::TextureInfo, DirectContext};
-use skia_safe::{self as skia, ISize};
 
 #[derive(Debug, Clone)]
 pub struct GpuState {
@@ -56,10 +56,6 @@ impl GpuState {
         texture_id
     }
 
-    pub fn create_surface_with_isize(&mut self, label: String, size: ISize) -> skia::Surface {
-        self.create_surface_with_dimensions(label, size.width, size.height)
-    }
-
     pub fn create_surface_with_dimensions(
         &mut self,
         label: String,
@@ -1,9 +1,8 @@
 use crate::math::Rect as MathRect;
-use crate::shapes::ImageFill;
 use crate::uuid::Uuid;
 
 use skia_safe::gpu::{surfaces, Budgeted, DirectContext};
-use skia_safe::{self as skia, Codec, ISize};
+use skia_safe::{self as skia, Codec};
 use std::collections::HashMap;
 
 pub type Image = skia::Image;
@@ -17,41 +16,6 @@ pub fn get_dest_rect(container: &MathRect, delta: f32) -> MathRect {
     )
 }
 
-pub fn get_source_rect(size: ISize, container: &MathRect, image_fill: &ImageFill) -> MathRect {
-    let image_width = size.width as f32;
-    let image_height = size.height as f32;
-
-    // Container size
-    let container_width = container.width();
-    let container_height = container.height();
-
-    let mut source_width = image_width;
-    let mut source_height = image_height;
-    let mut source_x = 0.;
-    let mut source_y = 0.;
-
-    let source_scale_y = image_height / container_height;
-    let source_scale_x = image_width / container_width;
-
-    if image_fill.keep_aspect_ratio() {
-        // Calculate scale to ensure the image covers the container
-        let image_aspect_ratio = image_width / image_height;
-        let container_aspect_ratio = container_width / container_height;
-
-        if image_aspect_ratio > container_aspect_ratio {
-            // Image is taller, scale based on width to cover container
-            source_width = container_width * source_scale_y;
-            source_x = (image_width - source_width) / 2.0;
-        } else {
-            // Image is wider, scale based on height to cover container
-            source_height = container_height * source_scale_x;
-            source_y = (image_height - source_height) / 2.0;
-        };
-    }
-
-    MathRect::from_xywh(source_x, source_y, source_width, source_height)
-}
-
 enum StoredImage {
     Raw(Vec<u8>),
     Gpu(Image),
@@ -59,7 +23,8 @@ enum StoredImage {
 
 pub struct ImageStore {
     images: HashMap<(Uuid, bool), StoredImage>,
-    context: Box<DirectContext>,
+    // `None` when rendering with the raster backend.
+    context: Option<Box<DirectContext>>,
 }
 
 /// Creates a Skia image from an existing WebGL texture.
@@ -104,8 +69,8 @@ fn create_image_from_gl_texture(
     Ok(image)
 }
 
-// Decode and upload to GPU
-fn decode_image(context: &mut Box<DirectContext>, raw_data: &[u8]) -> Option<Image> {
+// Decode and upload to GPU, or into a raster image when there is no GPU context
+fn decode_image(context: Option<&mut Box<DirectContext>>, raw_data: &[u8]) -> Option<Image> {
     let data = unsafe { skia::Data::new_bytes(raw_data) };
     let codec = Codec::from_data(&data)?;
     let image = Image::from_encoded(&data)?;
@@ -118,16 +83,19 @@ fn decode_image(context: &mut Box<DirectContext>, raw_data: &[u8]) -> Option<Ima
 
     let image_info = skia::ImageInfo::new_n32_premul(dimensions, None);
 
-    let mut surface = surfaces::render_target(
-        context,
-        Budgeted::Yes,
-        &image_info,
-        None,
-        None,
-        None,
-        true,
-        false,
-    )?;
+    let mut surface = match context {
+        Some(context) => surfaces::render_target(
+            context,
+            Budgeted::Yes,
+            &image_info,
+            None,
+            None,
+            None,
+            true,
+            false,
+        )?,
+        None => skia::surfaces::raster(&image_info, None, None)?,
+    };
 
     let dest_rect: MathRect =
         MathRect::from_xywh(0.0, 0.0, dimensions.width as f32, dimensions.height as f32);
@@ -140,10 +108,10 @@ fn decode_image(context: &mut Box<DirectContext>, raw_data: &[u8]) -> Option<Ima
 }
 
 impl ImageStore {
-    pub fn new(context: DirectContext) -> Self {
+    pub fn new(context: Option<DirectContext>) -> Self {
         Self {
             images: HashMap::with_capacity(2048),
-            context: Box::new(context),
+            context: context.map(Box::new),
         }
     }
 
@@ -156,7 +124,7 @@ impl ImageStore {
 
         let raw_data = image_data.to_vec();
 
-        if let Some(gpu_image) = decode_image(&mut self.context, &raw_data) {
+        if let Some(gpu_image) = decode_image(self.context.as_mut(), &raw_data) {
             self.images.insert(key, StoredImage::Gpu(gpu_image));
         } else {
             self.images.insert(key, StoredImage::Raw(raw_data));
@@ -181,8 +149,12 @@ impl ImageStore {
             return Err("Image already exists".to_string());
         }
 
+        let Some(context) = self.context.as_mut() else {
+            return Err("GL textures are not supported by the raster backend".to_string());
+        };
+
         // Create a Skia image from the existing GL texture
-        let image = create_image_from_gl_texture(&mut self.context, texture_id, width, height)?;
+        let image = create_image_from_gl_texture(context, texture_id, width, height)?;
         self.images.insert(key, StoredImage::Gpu(image));
 
         Ok(())
@@ -192,6 +164,11 @@ impl ImageStore {
         self.images.contains_key(&(*id, is_thumbnail))
     }
 
+    /// Ids of the stored images and whether they are thumbnails.
+    pub fn ids(&self) -> impl Iterator<Item = (Uuid, bool)> + '_ {
+        self.images.keys().copied()
+    }
+
     pub fn get(&mut self, id: &Uuid) -> Option<&Image> {
         // Try to get full image first, fallback to thumbnail
         let has_full = self.images.contains_key(&(*id, false));
@@ -202,6 +179,17 @@ impl ImageStore {
         }
     }
 
+    /// Returns the image encoded as PNG, reading it back from the GPU if needed.
+    pub fn get_png_data(&mut self, id: &Uuid) -> Option<Vec<u8>> {
+        let image = self.get(id)?.clone();
+        let data = image.encode(
+            self.context.as_deref_mut(),
+            skia::EncodedImageFormat::PNG,
+            None,
+        )?;
+        Some(data.as_bytes().to_vec())
+    }
+
     fn get_internal(&mut self, id: &Uuid, is_thumbnail: bool) -> Option<&Image> {
         let key = (*id, is_thumbnail);
         // Use entry API to mutate the HashMap in-place if needed
@@ -209,7 +197,7 @@ impl ImageStore {
             match entry {
                 StoredImage::Gpu(ref img) => Some(img),
                 StoredImage::Raw(raw_data) => {
-                    let gpu_image = decode_image(&mut self.context, raw_data)?;
+                    let gpu_image = decode_image(self.context.as_mut(), raw_data)?;
                     *entry = StoredImage::Gpu(gpu_image);
 
                     if let StoredImage::Gpu(ref img) = entry {
@@ -0,0 +1,416 @@
+use skia_safe::{self as skia, canvas::SaveLayerRec, pdf, Canvas, Paint, RRect};
+use std::borrow::Cow;
+
+use super::export::{self, shape_matrix};
+use super::{fills, shadows, strokes, text, RenderState};
+use crate::shapes::{BlurType, FillLayer, ParagraphBuilderGroup, Shape, Type};
+use crate::state::ShapesPoolRef;
+use crate::uuid::Uuid;
+
+/// Renders every frame in `ids` as a page of a PDF document and returns the encoded
+/// document.
+///
+/// Pages are drawn with the same fill, stroke, text and shadow code as the workspace,
+/// but straight into the PDF canvas instead of the render surfaces, so paths are kept
+/// as vectors and text stays selectable. Only the effects PDF can't express (blurs and
+/// shadows) get rasterized by skia. Pages are sized in points, one per unit.
+pub fn render_frames(
+    render_state: &mut RenderState,
+    tree: ShapesPoolRef,
+    ids: &[Uuid],
+) -> Result<Vec<u8>, String> {
+    if ids.is_empty() {
+        return Err("No frames to export".to_string());
+    }
+
+    // The stroke code adjusts some widths to the render scale, the document is
+    // always drawn at 1:1.
+    let options = std::mem::take(&mut render_state.options);
+    let zoom = render_state.viewbox.zoom;
+    render_state.viewbox.zoom = 1.0;
+
+    let result = render_document(render_state, tree, ids);
+
+    render_state.options = options;
+    render_state.viewbox.zoom = zoom;
+    result
+}
+
+fn render_document(
+    render_state: &mut RenderState,
+    tree: ShapesPoolRef,
+    ids: &[Uuid],
+) -> Result<Vec<u8>, String> {
+    let mut data: Vec<u8> = Vec::new();
+    let mut document = pdf::new_document(&mut data, None);
+
+    for id in ids {
+        let Some(frame) = tree.get(id) else {
+            return Err(format!("Shape {} not found", id));
+        };
+
+        if !matches!(frame.shape_type, Type::Frame(_)) {
+            return Err(format!("Shape {} is not a frame", id));
+        }
+
+        let bounds = frame.selrect;
+        let mut page = document.begin_page((bounds.width(), bounds.height()), None);
+        let canvas = page.canvas();
+
+        // Rotated frames are printed upright.
+        canvas.translate((-bounds.left, -bounds.top));
+        canvas.concat(&shape_matrix(frame).invert().unwrap_or_default());
+        render_node(render_state, tree, canvas, frame, &[])?;
+
+        document = page.end_page();
+    }
+
+    document.close();
+    Ok(data)
+}
+
+fn render_node(
+    render_state: &mut RenderState,
+    tree: ShapesPoolRef,
+    canvas: &Canvas,
+    shape: &Shape,
+    nested_fills: &[FillLayer],
+) -> Result<(), String> {
+    if shape.hidden() {
+        return Ok(());
+    }
+
+    let mut shape: Cow<Shape> = Cow::Borrowed(shape);
+
+    // Containers blur their whole content, so the blur goes to the layer instead of
+    // being applied again by every fill and stroke.
+    let container_blur = match shape.shape_type {
+        Type::Frame(_) | Type::Group(_) => shape
+            .blur
+            .filter(|blur| {
+                !blur.hidden && blur.blur_type == BlurType::LayerBlur && blur.value > 0.0
+            })
+            .and_then(|blur| blur.layer_filter(&shape.selrect, 1.)),
+        _ => None,
+    };
+    if container_blur.is_some() {
+        shape.to_mut().set_blur(None);
+    }
+
+    let needs_layer = shape.needs_layer() || container_blur.is_some();
+    if needs_layer {
+        let mut paint = Paint::default();
+        paint.set_blend_mode(shape.blend_mode().into());
+        paint.set_alpha_f(shape.opacity());
+        if let Some(filter) = container_blur {
+            paint.set_image_filter(filter);
+        }
+        canvas.save_layer(&SaveLayerRec::default().paint(&paint));
+    }
+
+    // Text shadows are drawn along with the glyphs.
+    let mut result = Ok(());
+    if !matches!(shape.shape_type, Type::Text(_)) {
+        shadows::render_drop_shadows_to_canvas(canvas, &shape, |canvas| {
+            if result.is_ok() {
+                result = render_shape(render_state, tree, canvas, &shape, nested_fills);
+            }
+        });
+    }
+
+    if result.is_ok() {
+        result = render_shape(render_state, tree, canvas, &shape, nested_fills);
+    }
+
+    if needs_layer {
+        canvas.restore();
+    }
+    result
+}
+
+fn render_shape(
+    render_state: &mut RenderState,
+    tree: ShapesPoolRef,
+    canvas: &Canvas,
+    shape: &Shape,
+    nested_fills: &[FillLayer],
+) -> Result<(), String> {
+    let antialias = shape.should_use_antialias(1.);
+    let matrix = shape_matrix(shape);
+
+    match &shape.shape_type {
+        Type::Group(group) => {
+            // Groups propagate their fills to the children without fills.
+            render_children(render_state, tree, canvas, shape, &shape.fills)?;
+
+            if group.masked {
+                if let Some(mask) = shape.mask_id().and_then(|id| tree.get(id)) {
+                    let mut mask_paint = Paint::default();
+                    mask_paint.set_blend_mode(skia::BlendMode::DstIn);
+                    canvas.save_layer(&SaveLayerRec::default().paint(&mask_paint));
+                    let result = render_node(render_state, tree, canvas, mask, &shape.fills);
+                    canvas.restore();
+                    result?;
+                }
+            }
+        }
+
+        Type::Frame(frame) => {
+            canvas.save();
+            canvas.concat(&matrix);
+            render_fills(render_state, canvas, shape, &[], antialias);
+            // In clipped content strokes are drawn over the contained elements
+            if !shape.clip() {
+                render_strokes(render_state, canvas, shape, antialias);
+            }
+            shadows::render_fill_inner_shadows_to_canvas(canvas, shape, antialias);
+
+            canvas.save();
+            if shape.clip() {
+                if let Some(corners) = frame.corners {
+                    let rrect = RRect::new_rect_radii(shape.selrect, &corners);
+                    canvas.clip_rrect(rrect, skia::ClipOp::Intersect, antialias);
+                } else {
+                    canvas.clip_rect(shape.selrect, skia::ClipOp::Intersect, antialias);
+                }
+            }
+            // Children carry their own transforms.
+            canvas.concat(&matrix.invert().unwrap_or_default());
+            let result = render_children(render_state, tree, canvas, shape, &[]);
+            canvas.restore();
+
+            if shape.clip() {
+                render_strokes(render_state, canvas, shape, antialias);
+            }
+            canvas.restore();
+            result?;
+        }
+
+        Type::Text(text_content) => {
+            canvas.save();
+            canvas.concat(&matrix);
+
+            let text_content = text_content.new_bounds(shape.selrect());
+            let count_inner_strokes = shape.count_visible_inner_strokes();
+            let blur_filter = shape.image_filter(1.);
+            let mut paragraph_builders = text_content.paragraph_builder_group_from_text(None);
+            let mut paragraphs_with_shadows =
+                text_content.paragraph_builder_group_from_text(Some(true));
+
+            if !shape.has_visible_strokes() {
+                for shadow in shape.drop_shadow_paints() {
+                    render_text(
+                        canvas,
+                        shape,
+                        &mut paragraphs_with_shadows,
+                        Some(&shadow),
+                        blur_filter.as_ref(),
+                    );
+                }
+            }
+
+            render_text(
+                canvas,
+                shape,
+                &mut paragraph_builders,
+                None,
+                blur_filter.as_ref(),
+            );
+
+            for stroke in shape.visible_strokes().rev() {
+                let mut stroke_paragraphs = text::stroke_paragraph_builder_group_from_text(
+                    &text_content,
+                    stroke,
+                    &shape.selrect(),
+                    count_inner_strokes,
+                    None,
+                );
+                render_text(
+                    canvas,
+                    shape,
+                    &mut stroke_paragraphs,
+                    None,
+                    blur_filter.as_ref(),
+                );
+            }
+
+            if !shape.has_visible_strokes() {
+                for shadow in shape.inner_shadow_paints() {
+                    render_text(
+                        canvas,
+                        shape,
+                        &mut paragraphs_with_shadows,
+                        Some(&shadow),
+                        blur_filter.as_ref(),
+                    );
+                }
+            }
+
+            canvas.restore();
+        }
+
+        Type::SVGRaw(sr) => {
+            let mut matrix = matrix;
+            if let Some(svg_transform) = shape.svg_transform() {
+                matrix.pre_concat(&svg_transform);
+            }
+
+            canvas.save();
+            canvas.concat(&matrix);
+            let result = match shape.svg.as_ref() {
+                Some(svg) => {
+                    svg.render(canvas);
+                    Ok(())
+                }
+                None => {
+                    let font_manager =
+                        skia::FontMgr::from(render_state.fonts().font_provider().clone());
+                    skia::svg::Dom::from_str(&sr.content, font_manager)
+                        .map(|dom| dom.render(canvas))
+                        .map_err(|e| format!("Error parsing the SVG of shape {}: {}", shape.id, e))
+                }
+            };
+            canvas.restore();
+            result?;
+        }
+
+        _ => {
+            canvas.save();
+            canvas.concat(&matrix);
+            render_fills(render_state, canvas, shape, nested_fills, antialias);
+            render_strokes(render_state, canvas, shape, antialias);
+            shadows::render_fill_inner_shadows_to_canvas(canvas, shape, antialias);
+            canvas.restore();
+        }
+    }
+    Ok(())
+}
+
+fn render_children(
+    render_state: &mut RenderState,
+    tree: ShapesPoolRef,
+    canvas: &Canvas,
+    shape: &Shape,
+    nested_fills: &[FillLayer],
+) -> Result<(), String> {
+    for child in export::children(tree, shape) {
+        render_node(render_state, tree, canvas, child, nested_fills)?;
+    }
+    Ok(())
+}
+
+fn render_fills(
+    render_state: &mut RenderState,
+    canvas: &Canvas,
+    shape: &Shape,
+    nested_fills: &[FillLayer],
+    antialias: bool,
+) {
+    // The noise is drawn over a layer with the fills, so it only covers them.
+    let noise_paint = shape.noise_paint();
+    if noise_paint.is_some() {
+        canvas.save_layer(&SaveLayerRec::default());
+    }
+
+    for fill in export::shape_fills(shape, nested_fills) {
+        fills::render_to_canvas(render_state, canvas, shape, fill, antialias);
+    }
+
+    if let Some(paint) = noise_paint {
+        canvas.draw_paint(&paint);
+        canvas.restore();
+    }
+}
+
+fn render_strokes(render_state: &mut RenderState, canvas: &Canvas, shape: &Shape, antialias: bool) {
+    for stroke in shape.visible_strokes().rev() {
+        strokes::render_to_canvas(render_state, canvas, shape, stroke, None, antialias);
+        shadows::render_stroke_inner_shadows_to_canvas(
+            render_state,
+            canvas,
+            shape,
+            stroke,
+            antialias,
+        );
+    }
+}
+
+fn render_text(
+    canvas: &Canvas,
+    shape: &Shape,
+    paragraph_builders: &mut [ParagraphBuilderGroup],
+    shadow: Option<&Paint>,
+    blur: Option<&skia::ImageFilter>,
+) {
+    // The text renderer restores the canvas when it's done.
+    canvas.save();
+    text::render(
+        None,
+        Some(canvas),
+        shape,
+        paragraph_builders,
+        None,
+        shadow,
+        blur,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::RenderBackend;
+    use crate::shapes::{Fill, Frame, SolidColor};
+    use crate::state::ShapesPool;
+
+    fn add_frame(tree: &mut ShapesPool, id: Uuid, width: f32, height: f32) -> &mut Shape {
+        let frame = tree.add_shape(id);
+        frame.set_selrect(0.0, 0.0, width, height);
+        frame.set_shape_type(Type::Frame(Frame::default()));
+        frame.add_fill(Fill::Solid(SolidColor(skia::Color::WHITE)));
+        frame
+    }
+
+    fn count_pages(data: &[u8]) -> usize {
+        let text = String::from_utf8_lossy(data);
+        text.match_indices("/Type /Page")
+            .filter(|(index, pattern)| !text[index + pattern.len()..].starts_with('s'))
+            .count()
+    }
+
+    #[test]
+    fn test_one_page_per_frame() {
+        let ids = [Uuid::new_v4(), Uuid::new_v4()];
+        let mut tree = ShapesPool::new();
+        add_frame(&mut tree, ids[0], 100.0, 50.0);
+        add_frame(&mut tree, ids[1], 30.0, 40.0);
+
+        let mut render_state = RenderState::new(1, 1, RenderBackend::raster());
+        let data = render_frames(&mut render_state, &tree, &ids).unwrap();
+        assert!(data.starts_with(b"%PDF-"));
+        assert_eq!(count_pages(&data), 2);
+
+        let data = render_frames(&mut render_state, &tree, &ids[..1]).unwrap();
+        assert_eq!(count_pages(&data), 1);
+    }
+
+    #[test]
+    fn test_export_errors() {
+        let frame_id = Uuid::new_v4();
+        let svg_id = Uuid::new_v4();
+        let mut tree = ShapesPool::new();
+        add_frame(&mut tree, frame_id, 100.0, 50.0).add_child(svg_id);
+        let svg = tree.add_shape(svg_id);
+        svg.set_selrect(0.0, 0.0, 10.0, 10.0);
+        svg.set_svg_raw_content("not an svg".to_string()).unwrap();
+        svg.set_parent(frame_id);
+
+        let mut render_state = RenderState::new(1, 1, RenderBackend::raster());
+        assert!(render_frames(&mut render_state, &tree, &[]).is_err());
+        assert_eq!(
+            render_frames(&mut render_state, &tree, &[svg_id]).err(),
+            Some(format!("Shape {} is not a frame", svg_id))
+        );
+        let error = render_frames(&mut render_state, &tree, &[frame_id]).unwrap_err();
+        assert!(error.starts_with(&format!("Error parsing the SVG of shape {}", svg_id)));
+    }
+}
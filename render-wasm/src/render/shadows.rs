@@ -1,20 +1,49 @@
-use super::{RenderState, SurfaceId};
+use super::{surfaces, RenderState, SurfaceId};
 use crate::render::strokes;
-use crate::shapes::{ParagraphBuilderGroup, Shadow, Shape, Stroke, Type};
-use skia_safe::{canvas::SaveLayerRec, Paint, Path};
+use crate::shapes::{BlendMode, ParagraphBuilderGroup, Shadow, Shape, Stroke, Type};
+use skia_safe::{canvas::SaveLayerRec, Canvas, Paint, Path};
+use std::borrow::Cow;
 
 use crate::render::text;
 
+/// Inner shadows with a blend mode are drawn over the fills or the strokes,
+/// instead of their own surface, so they blend with them.
+fn inner_shadow_surface(
+    shadow: &Shadow,
+    surface_id: SurfaceId,
+    shape_surface_id: SurfaceId,
+) -> SurfaceId {
+    if shadow.blend_mode == BlendMode::default() {
+        surface_id
+    } else {
+        shape_surface_id
+    }
+}
+
+/// Stroke used to draw an inner shadow, with the blend mode of the shadow when
+/// it has one.
+fn inner_shadow_stroke<'a>(stroke: &'a Stroke, shadow: &Shadow) -> Cow<'a, Stroke> {
+    if shadow.blend_mode == BlendMode::default() {
+        return Cow::Borrowed(stroke);
+    }
+    let mut stroke = stroke.clone();
+    stroke.blend_mode = shadow.blend_mode;
+    stroke.opacity = 1.;
+    Cow::Owned(stroke)
+}
+
 // Fill Shadows
 pub fn render_fill_inner_shadows(
     render_state: &mut RenderState,
     shape: &Shape,
     antialias: bool,
     surface_id: SurfaceId,
+    fills_surface_id: SurfaceId,
 ) {
     if shape.has_fills() {
         for shadow in shape.inner_shadows_visible() {
-            render_fill_inner_shadow(render_state, shape, shadow, antialias, surface_id);
+            let target = inner_shadow_surface(shadow, surface_id, fills_surface_id);
+            render_fill_inner_shadow(render_state, shape, shadow, antialias, target);
         }
     }
 }
@@ -36,6 +65,7 @@ pub fn render_stroke_inner_shadows(
     stroke: &Stroke,
     antialias: bool,
     surface_id: SurfaceId,
+    strokes_surface_id: SurfaceId,
 ) {
     if !shape.has_fills() {
         for shadow in shape.inner_shadows_visible() {
@@ -43,8 +73,8 @@ pub fn render_stroke_inner_shadows(
             strokes::render(
                 render_state,
                 shape,
-                stroke,
-                Some(surface_id),
+                &inner_shadow_stroke(stroke, shadow),
+                Some(inner_shadow_surface(shadow, surface_id, strokes_surface_id)),
                 filter.as_ref(),
                 antialias,
             )
@@ -104,22 +134,73 @@ fn render_shadow_paint(
     paint: &Paint,
     surface_id: SurfaceId,
 ) {
+    draw_shadow_paint(
+        render_state.surfaces.canvas_and_mark_dirty(surface_id),
+        shape,
+        paint,
+    );
+}
+
+fn draw_shadow_paint(canvas: &Canvas, shape: &Shape, paint: &Paint) {
     match &shape.shape_type {
         Type::Rect(_) | Type::Frame(_) => {
-            render_state.surfaces.draw_rect_to(surface_id, shape, paint);
+            surfaces::draw_rect_on_canvas(canvas, shape, paint);
         }
         Type::Circle => {
-            render_state
-                .surfaces
-                .draw_circle_to(surface_id, shape, paint);
+            surfaces::draw_circle_on_canvas(canvas, shape, paint);
         }
         Type::Path(_) | Type::Bool(_) => {
-            render_state.surfaces.draw_path_to(surface_id, shape, paint);
+            surfaces::draw_path_on_canvas(canvas, shape, paint);
         }
         _ => {}
     }
 }
 
+// Shadows drawn straight into a canvas, used by the document exports.
+pub fn render_drop_shadows_to_canvas(
+    canvas: &Canvas,
+    shape: &Shape,
+    mut draw_shape: impl FnMut(&Canvas),
+) {
+    for shadow in shape.drop_shadows_visible() {
+        let paint = shadow.get_drop_shadow_paint();
+        canvas.save_layer(&SaveLayerRec::default().paint(&paint));
+        draw_shape(canvas);
+        canvas.restore();
+    }
+}
+
+pub fn render_fill_inner_shadows_to_canvas(canvas: &Canvas, shape: &Shape, antialias: bool) {
+    if shape.has_fills() {
+        for shadow in shape.inner_shadows_visible() {
+            let paint = &shadow.get_inner_shadow_paint(antialias, shape.image_filter(1.).as_ref());
+            draw_shadow_paint(canvas, shape, paint);
+        }
+    }
+}
+
+pub fn render_stroke_inner_shadows_to_canvas(
+    render_state: &mut RenderState,
+    canvas: &Canvas,
+    shape: &Shape,
+    stroke: &Stroke,
+    antialias: bool,
+) {
+    if !shape.has_fills() {
+        for shadow in shape.inner_shadows_visible() {
+            let filter = shadow.get_inner_shadow_filter();
+            strokes::render_to_canvas(
+                render_state,
+                canvas,
+                shape,
+                &inner_shadow_stroke(stroke, shadow),
+                filter.as_ref(),
+                antialias,
+            );
+        }
+    }
+}
+
 pub fn render_text_shadows(
     render_state: &mut RenderState,
     shape: &Shape,
@@ -5,9 +5,9 @@ use crate::shapes::{
 };
 use skia_safe::{self as skia, ImageFilter, RRect};
 
-use super::{filters, RenderState, SurfaceId};
+use super::{filters, ImageStore, RenderState, SurfaceId};
 use crate::render::filters::compose_filters;
-use crate::render::{get_dest_rect, get_source_rect};
+use crate::render::get_dest_rect;
 
 // FIXME: See if we can simplify these arguments
 #[allow(clippy::too_many_arguments)]
@@ -34,10 +34,31 @@ fn draw_stroke_on_rect(
     let filter = compose_filters(blur, shadow);
     paint.set_image_filter(filter);
 
-    match corners {
-        Some(radii) => {
-            let radii = stroke.outer_corners(radii);
-            let rrect = RRect::new_rect_radii(stroke_rect, &radii);
+    // Strokes with different side widths fill the area between their edges.
+    if let Some((outer, inner)) = stroke.side_rrects(rect, corners.as_ref()) {
+        paint.set_style(skia::PaintStyle::Fill);
+        paint.set_path_effect(None);
+        canvas.draw_drrect(outer, inner, &paint);
+        return;
+    }
+
+    let rrect = corners
+        .as_ref()
+        .map(|radii| RRect::new_rect_radii(stroke_rect, &stroke.outer_corners(radii)));
+
+    if has_computed_path(stroke) {
+        let path = match &rrect {
+            Some(rrect) => skia::Path::rrect(rrect, None),
+            None => skia::Path::rect(stroke_rect, None),
+        };
+        if let Some(stroked_path) = computed_stroke_path(stroke, &path, &mut paint) {
+            canvas.draw_path(&stroked_path, &paint);
+        }
+        return;
+    }
+
+    match rrect {
+        Some(rrect) => {
             canvas.draw_rrect(rrect, &paint);
         }
         None => {
@@ -70,12 +91,45 @@ fn draw_stroke_on_circle(
     let filter = compose_filters(blur, shadow);
     paint.set_image_filter(filter);
 
+    if has_computed_path(stroke) {
+        let path = skia::Path::oval(stroke_rect, None);
+        if let Some(stroked_path) = computed_stroke_path(stroke, &path, &mut paint) {
+            canvas.draw_path(&stroked_path, &paint);
+        }
+        return;
+    }
+
     canvas.draw_oval(stroke_rect, &paint);
 }
 
+fn has_computed_path(stroke: &Stroke) -> bool {
+    stroke.width_profile.is_some() || stroke.adjusted_dash().is_some()
+}
+
+/// Path drawn instead of `path` for the strokes the paint can't draw by itself:
+/// variable widths are filled outlines and adjusted dashes are paths of their
+/// own. The paint is updated to draw it.
+fn computed_stroke_path(
+    stroke: &Stroke,
+    path: &skia::Path,
+    paint: &mut skia::Paint,
+) -> Option<skia::Path> {
+    if let Some(profile) = &stroke.width_profile {
+        let outline = profile.outline(path, paint.stroke_width());
+        paint.set_style(skia::PaintStyle::Fill);
+        paint.set_path_effect(None);
+        return Some(outline);
+    }
+
+    let dashed = stroke.adjusted_dash()?.adjusted_path(path)?;
+    paint.set_path_effect(None);
+    Some(dashed)
+}
+
 fn draw_outer_stroke_path(
     canvas: &skia::Canvas,
     path: &skia::Path,
+    stroked_path: &skia::Path,
     paint: &skia::Paint,
     blur: Option<&ImageFilter>,
     antialias: bool,
@@ -90,7 +144,7 @@ fn draw_outer_stroke_path(
 
     let layer_rec = skia::canvas::SaveLayerRec::default().paint(&outer_paint);
     canvas.save_layer(&layer_rec);
-    canvas.draw_path(path, paint);
+    canvas.draw_path(stroked_path, paint);
 
     let mut clear_paint = skia::Paint::default();
     clear_paint.set_blend_mode(skia::BlendMode::Clear);
@@ -104,6 +158,7 @@ fn draw_outer_stroke_path(
 fn draw_inner_stroke_path(
     canvas: &skia::Canvas,
     path: &skia::Path,
+    stroked_path: &skia::Path,
     paint: &skia::Paint,
     blur: Option<&ImageFilter>,
     antialias: bool,
@@ -117,7 +172,7 @@ fn draw_inner_stroke_path(
     let layer_rec = skia::canvas::SaveLayerRec::default().paint(&inner_paint);
     canvas.save_layer(&layer_rec);
     canvas.clip_path(path, skia::ClipOp::Intersect, antialias);
-    canvas.draw_path(path, paint);
+    canvas.draw_path(stroked_path, paint);
     canvas.restore();
 }
 
@@ -147,72 +202,198 @@ pub fn draw_stroke_on_path(
     let filter = compose_filters(blur, shadow);
     paint.set_image_filter(filter);
 
+    // Inner and outer strokes are still clipped by the original path.
+    let computed_path = computed_stroke_path(stroke, &skia_path, &mut paint);
+    let stroked_path = computed_path.as_ref().unwrap_or(&skia_path);
+
     match stroke.render_kind(is_open) {
         StrokeKind::Inner => {
-            draw_inner_stroke_path(canvas, &skia_path, &paint, blur, antialias);
+            draw_inner_stroke_path(canvas, &skia_path, stroked_path, &paint, blur, antialias);
         }
         StrokeKind::Center => {
-            canvas.draw_path(&skia_path, &paint);
+            canvas.draw_path(stroked_path, &paint);
         }
         StrokeKind::Outer => {
-            draw_outer_stroke_path(canvas, &skia_path, &paint, blur, antialias);
+            draw_outer_stroke_path(canvas, &skia_path, stroked_path, &paint, blur, antialias);
         }
     }
 
     handle_stroke_caps(
-        &mut skia_path,
-        stroke,
-        selrect,
-        canvas,
-        is_open,
-        svg_attrs,
-        scale,
-        blur,
-        antialias,
+        &skia_path, stroke, selrect, canvas, is_open, svg_attrs, scale, blur, antialias,
     );
 }
 
+/// Filled geometry of a stroke, in the coordinates of the shape: the area the
+/// stroke covers when it's drawn, with its kind, width, joins, dashes and caps.
+pub fn stroke_outline(shape: &Shape, stroke: &Stroke) -> Option<skia::Path> {
+    let geometry = shape.geometry_path()?;
+
+    if let Type::Rect(_) | Type::Frame(_) = shape.shape_type {
+        if let Some((outer, inner)) =
+            stroke.side_rrects(&shape.selrect, shape.shape_type.corners().as_ref())
+        {
+            let outer = skia::Path::rrect(outer, None);
+            return outer.op(&skia::Path::rrect(inner, None), skia::PathOp::Difference);
+        }
+    }
+
+    let is_open = shape.is_open();
+    let kind = stroke.render_kind(is_open);
+    let mut paint =
+        stroke.to_stroked_paint(is_open, &shape.selrect, shape.svg_attrs.as_ref(), 1., true);
+    // Without the extra width outer strokes have on screen.
+    paint.set_stroke_width(match kind {
+        StrokeKind::Center => stroke.width,
+        StrokeKind::Inner | StrokeKind::Outer => stroke.width * 2.,
+    });
+    let cap_paint = paint.clone();
+
+    let stroked_path = computed_stroke_path(stroke, &geometry, &mut paint);
+    let stroked_path = stroked_path.as_ref().unwrap_or(&geometry);
+    let fill = fill_path(stroked_path, &paint);
+
+    let mut outline = match kind {
+        StrokeKind::Inner => fill.op(&geometry, skia::PathOp::Intersect)?,
+        StrokeKind::Center => fill.simplify()?,
+        StrokeKind::Outer => fill.op(&geometry, skia::PathOp::Difference)?,
+    };
+
+    if let (true, Some([start, end])) = (is_open, cap_anchors(&geometry)) {
+        let caps = [
+            (stroke.cap_start, stroke.cap_start_scale, start),
+            (stroke.cap_end, stroke.cap_end_scale, end),
+        ];
+        for (cap, scale, (p1, p2)) in caps {
+            let Some(cap) = cap else {
+                continue;
+            };
+            for (cap_path, style) in cap_paths(cap, stroke.width, scale, &p1, &p2) {
+                let mut cap_paint = cap_paint.clone();
+                cap_paint.set_style(style);
+                outline = outline.op(&fill_path(&cap_path, &cap_paint), skia::PathOp::Union)?;
+            }
+        }
+    }
+    Some(outline)
+}
+
+/// Area covered by drawing `path` with `paint`.
+fn fill_path(path: &skia::Path, paint: &skia::Paint) -> skia::Path {
+    if paint.style() == skia::PaintStyle::Fill && paint.path_effect().is_none() {
+        return path.clone();
+    }
+    let mut fill = skia::Path::new();
+    skia::path_utils::fill_path_with_paint(path, paint, &mut fill, None, None);
+    fill
+}
+
 fn handle_stroke_cap(
     canvas: &skia::Canvas,
     cap: StrokeCap,
     width: f32,
+    scale: f32,
     paint: &mut skia::Paint,
     p1: &Point,
     p2: &Point,
 ) {
-    paint.set_style(skia::PaintStyle::Fill);
+    for (path, style) in cap_paths(cap, width, scale, p1, p2) {
+        paint.set_style(style);
+        canvas.draw_path(&path, paint);
+    }
+}
+
+/// Geometry of a stroke cap placed at `p1`, pointing away from `p2`, along with
+/// the style it has to be painted with. `scale` is the size of the cap relative
+/// to the width of the stroke.
+pub fn cap_paths(
+    cap: StrokeCap,
+    width: f32,
+    scale: f32,
+    p1: &Point,
+    p2: &Point,
+) -> Vec<(skia::Path, skia::PaintStyle)> {
+    let size = width * scale;
+    // Reversed arrows point towards the path.
+    let reversed = Point::new(2. * p1.x - p2.x, 2. * p1.y - p2.y);
+
     match cap {
-        StrokeCap::LineArrow => {
+        StrokeCap::LineArrow => vec![
             // We also draw this square cap to fill the gap between the path and the arrow
-            draw_square_cap(canvas, paint, p1, p2, width, 0.);
-            paint.set_style(skia::PaintStyle::Stroke);
-            draw_arrow_cap(canvas, paint, p1, p2, width * 4.);
-        }
+            (square_cap_path(p1, p2, width, 0.), skia::PaintStyle::Fill),
+            (
+                arrow_cap_path(p1, p2, size * 4., false),
+                skia::PaintStyle::Stroke,
+            ),
+        ],
         StrokeCap::TriangleArrow => {
-            draw_triangle_cap(canvas, paint, p1, p2, width * 4.);
-        }
-        StrokeCap::SquareMarker => {
-            draw_square_cap(canvas, paint, p1, p2, width * 4., 0.);
-        }
-        StrokeCap::CircleMarker => {
-            canvas.draw_circle((p1.x, p1.y), width * 2., paint);
+            vec![(triangle_cap_path(p1, p2, size * 4.), skia::PaintStyle::Fill)]
         }
-        StrokeCap::DiamondMarker => {
-            draw_square_cap(canvas, paint, p1, p2, width * 4., 45.);
-        }
-        StrokeCap::Round => {
-            canvas.draw_circle((p1.x, p1.y), width / 2.0, paint);
-        }
-        StrokeCap::Square => {
-            draw_square_cap(canvas, paint, p1, p2, width, 0.);
+        StrokeCap::SquareMarker => vec![(
+            square_cap_path(p1, p2, size * 4., 0.),
+            skia::PaintStyle::Fill,
+        )],
+        StrokeCap::CircleMarker => vec![(circle_cap_path(p1, size * 2.), skia::PaintStyle::Fill)],
+        StrokeCap::DiamondMarker => vec![(
+            square_cap_path(p1, p2, size * 4., 45.),
+            skia::PaintStyle::Fill,
+        )],
+        StrokeCap::Round => vec![(circle_cap_path(p1, size / 2.0), skia::PaintStyle::Fill)],
+        StrokeCap::Square => vec![(square_cap_path(p1, p2, size, 0.), skia::PaintStyle::Fill)],
+        StrokeCap::Bar => vec![(
+            bar_cap_path(p1, p2, width, size * 4.),
+            skia::PaintStyle::Fill,
+        )],
+        StrokeCap::ReversedArrow => vec![
+            (square_cap_path(p1, p2, width, 0.), skia::PaintStyle::Fill),
+            (
+                arrow_cap_path(p1, &reversed, size * 4., false),
+                skia::PaintStyle::Stroke,
+            ),
+        ],
+        StrokeCap::HalfArrow => vec![
+            (square_cap_path(p1, p2, width, 0.), skia::PaintStyle::Fill),
+            (
+                arrow_cap_path(p1, p2, size * 4., true),
+                skia::PaintStyle::Stroke,
+            ),
+        ],
+        StrokeCap::CircleOutline => {
+            vec![(circle_cap_path(p1, size * 2.), skia::PaintStyle::Stroke)]
         }
+        StrokeCap::DiamondOutline => vec![(
+            square_cap_path(p1, p2, size * 4., 45.),
+            skia::PaintStyle::Stroke,
+        )],
+        StrokeCap::TriangleOutline => vec![(
+            triangle_cap_path(p1, p2, size * 4.),
+            skia::PaintStyle::Stroke,
+        )],
     }
 }
 
+/// Start and end points of a path, each one along with its neighbour, which
+/// gives the direction of the cap placed there.
+pub fn cap_anchors(path: &skia::Path) -> Option<[(Point, Point); 2]> {
+    let mut points = vec![Point::default(); path.count_points()];
+    path.get_points(&mut points);
+    // Curves can have duplicated points, so let's remove consecutive duplicated points
+    points.dedup();
+    let c_points = points.len();
+
+    if c_points < 2 {
+        return None;
+    }
+
+    Some([
+        (points[0], points[1]),
+        (points[c_points - 1], points[c_points - 2]),
+    ])
+}
+
 // FIXME: See if we can simplify these arguments
 #[allow(clippy::too_many_arguments)]
 fn handle_stroke_caps(
-    path: &mut skia::Path,
+    path: &skia::Path,
     stroke: &Stroke,
     selrect: &Rect,
     canvas: &skia::Canvas,
@@ -222,17 +403,12 @@ fn handle_stroke_caps(
     blur: Option<&ImageFilter>,
     antialias: bool,
 ) {
-    let mut points = vec![Point::default(); path.count_points()];
-    path.get_points(&mut points);
-    // Curves can have duplicated points, so let's remove consecutive duplicated points
-    points.dedup();
-    let c_points = points.len();
-
     // Closed shapes don't have caps
-    if c_points >= 2 && is_open {
-        let first_point = points.first().unwrap();
-        let last_point = points.last().unwrap();
+    if !is_open {
+        return;
+    }
 
+    if let Some([(first_point, next_point), (last_point, prev_point)]) = cap_anchors(path) {
         let mut paint_stroke =
             stroke.to_stroked_paint(is_open, selrect, svg_attrs, scale, antialias);
 
@@ -245,9 +421,10 @@ fn handle_stroke_caps(
                 canvas,
                 cap,
                 stroke.width,
+                stroke.cap_start_scale,
                 &mut paint_stroke,
-                first_point,
-                &points[1],
+                &first_point,
+                &next_point,
             );
         }
 
@@ -256,22 +433,27 @@ fn handle_stroke_caps(
                 canvas,
                 cap,
                 stroke.width,
+                stroke.cap_end_scale,
                 &mut paint_stroke,
-                last_point,
-                &points[c_points - 2],
+                &last_point,
+                &prev_point,
             );
         }
     }
 }
 
-fn draw_square_cap(
-    canvas: &skia::Canvas,
-    paint: &skia::Paint,
+fn circle_cap_path(center: &Point, radius: f32) -> skia::Path {
+    let mut path = skia::Path::new();
+    path.add_circle((center.x, center.y), radius, None);
+    path
+}
+
+fn square_cap_path(
     center: &Point,
     direction: &Point,
     size: f32,
     extra_rotation: f32,
-) {
+) -> skia::Path {
     let dx = direction.x - center.x;
     let dy = direction.y - center.y;
     let angle = dy.atan2(dx);
@@ -302,16 +484,12 @@ fn draw_square_cap(
     path.line_to(transformed_points[2]);
     path.line_to(transformed_points[3]);
     path.close();
-    canvas.draw_path(&path, paint);
+    path
 }
 
-fn draw_arrow_cap(
-    canvas: &skia::Canvas,
-    paint: &skia::Paint,
-    center: &Point,
-    direction: &Point,
-    size: f32,
-) {
+/// Arrow pointing away from `direction`. Half arrows only have the wing on the
+/// left of the path.
+fn arrow_cap_path(center: &Point, direction: &Point, size: f32, half: bool) -> skia::Path {
     let dx = direction.x - center.x;
     let dy = direction.y - center.y;
     let angle = dy.atan2(dx);
@@ -332,20 +510,35 @@ fn draw_arrow_cap(
     let mut path = skia::Path::new();
     path.move_to(transformed_points[1]);
     path.line_to(transformed_points[0]);
-    path.line_to(transformed_points[2]);
+    if !half {
+        path.line_to(transformed_points[2]);
+    }
     path.move_to(Point::new(center.x, center.y));
     path.line_to(transformed_points[0]);
+    path
+}
+
+/// Bar across the end of the path, `thickness` wide along it.
+fn bar_cap_path(center: &Point, direction: &Point, thickness: f32, length: f32) -> skia::Path {
+    let dx = direction.x - center.x;
+    let dy = direction.y - center.y;
+    let angle = dy.atan2(dx);
+
+    let mut matrix = Matrix::new_identity();
+    matrix.pre_rotate(angle.to_degrees(), Point::new(center.x, center.y));
 
-    canvas.draw_path(&path, paint);
+    let rect = Rect::from_xywh(
+        center.x - thickness / 2.,
+        center.y - length / 2.,
+        thickness,
+        length,
+    );
+    let mut path = skia::Path::rect(rect, None);
+    path.transform(&matrix);
+    path
 }
 
-fn draw_triangle_cap(
-    canvas: &skia::Canvas,
-    paint: &skia::Paint,
-    center: &Point,
-    direction: &Point,
-    size: f32,
-) {
+fn triangle_cap_path(center: &Point, direction: &Point, size: f32) -> skia::Path {
     let dx = direction.x - center.x;
     let dy = direction.y - center.y;
     let angle = dy.atan2(dx);
@@ -368,26 +561,20 @@ fn draw_triangle_cap(
     path.line_to(transformed_points[1]);
     path.line_to(transformed_points[2]);
     path.close();
-
-    canvas.draw_path(&path, paint);
+    path
 }
 
+#[allow(clippy::too_many_arguments)]
 fn draw_image_stroke_in_container(
-    render_state: &mut RenderState,
+    canvas: &skia::Canvas,
+    image: &skia::Image,
+    sampling_options: skia::SamplingOptions,
+    scale: f32,
     shape: &Shape,
     stroke: &Stroke,
     image_fill: &ImageFill,
     antialias: bool,
-    surface_id: SurfaceId,
 ) {
-    let scale = render_state.get_scale();
-    let image = render_state.images.get(&image_fill.id());
-    if image.is_none() {
-        return;
-    }
-
-    let size = image.unwrap().dimensions();
-    let canvas = render_state.surfaces.canvas_and_mark_dirty(surface_id);
     let container = &shape.selrect;
     let path_transform = shape.to_path_transform();
     let svg_attrs = shape.svg_attrs.as_ref();
@@ -460,7 +647,7 @@ fn draw_image_stroke_in_container(
                     canvas.draw_path(&path, &paint);
                 }
                 handle_stroke_caps(
-                    &mut path,
+                    &path,
                     stroke,
                     &outer_rect,
                     canvas,
@@ -487,17 +674,12 @@ fn draw_image_stroke_in_container(
         image_paint.set_image_filter(filter);
     }
 
-    let src_rect = get_source_rect(size, container, image_fill);
+    // The image is laid over the stroke bounds, so it covers outer strokes too.
     let dest_rect = get_dest_rect(container, stroke.delta());
+    image_paint.set_shader(image_fill.to_shader(image, &dest_rect, sampling_options));
 
     canvas.clip_rect(dest_rect, skia::ClipOp::Intersect, antialias);
-    canvas.draw_image_rect_with_sampling_options(
-        image.unwrap(),
-        Some((&src_rect, skia::canvas::SrcRectConstraint::Strict)),
-        dest_rect,
-        render_state.sampling_options,
-        &image_paint,
-    );
+    canvas.draw_rect(dest_rect, &image_paint);
 
     // Clear outer stroke for paths if necessary. When adding an outer stroke we need to empty the stroke added too in the inner area.
     if let Type::Path(p) = &shape.shape_type {
@@ -606,24 +788,80 @@ fn render_internal(
 
     let scale = render_state.get_scale();
     let target_surface = surface_id.unwrap_or(SurfaceId::Strokes);
-    let canvas = render_state.surfaces.canvas_and_mark_dirty(target_surface);
+    draw_stroke(
+        render_state.surfaces.canvas_and_mark_dirty(target_surface),
+        &mut render_state.images,
+        render_state.sampling_options,
+        scale,
+        shape,
+        stroke,
+        shadow,
+        antialias,
+    );
+}
+
+/// Draws a stroke straight into `canvas`, outside of the render surfaces. Used by
+/// the document exports, where the canvas belongs to the output document.
+pub fn render_to_canvas(
+    render_state: &mut RenderState,
+    canvas: &skia::Canvas,
+    shape: &Shape,
+    stroke: &Stroke,
+    shadow: Option<&ImageFilter>,
+    antialias: bool,
+) {
+    let scale = render_state.get_scale();
+    draw_stroke(
+        canvas,
+        &mut render_state.images,
+        render_state.sampling_options,
+        scale,
+        shape,
+        stroke,
+        shadow,
+        antialias,
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_stroke(
+    canvas: &skia::Canvas,
+    images: &mut ImageStore,
+    sampling_options: skia::SamplingOptions,
+    scale: f32,
+    shape: &Shape,
+    stroke: &Stroke,
+    shadow: Option<&ImageFilter>,
+    antialias: bool,
+) {
     let selrect = shape.selrect;
     let path_transform = shape.to_path_transform();
     let svg_attrs = shape.svg_attrs.as_ref();
 
+    // Inner and outer strokes are drawn in their own layers, so the blend mode
+    // and opacity of the stroke are applied to a layer wrapping all of them.
+    let layer_paint = stroke.layer_paint();
+    if let Some(layer_paint) = &layer_paint {
+        canvas.save_layer(&skia::canvas::SaveLayerRec::default().paint(layer_paint));
+    }
+
     if !matches!(shape.shape_type, Type::Text(_))
         && shadow.is_none()
         && matches!(stroke.fill, Fill::Image(_))
     {
         if let Fill::Image(image_fill) = &stroke.fill {
-            draw_image_stroke_in_container(
-                render_state,
-                shape,
-                stroke,
-                image_fill,
-                antialias,
-                target_surface,
-            );
+            if let Some(image) = images.get(&image_fill.id()) {
+                draw_image_stroke_in_container(
+                    canvas,
+                    image,
+                    sampling_options,
+                    scale,
+                    shape,
+                    stroke,
+                    image_fill,
+                    antialias,
+                );
+            }
         }
     } else {
         match &shape.shape_type {
@@ -672,6 +910,10 @@ fn render_internal(
             _ => unreachable!("This shape should not have strokes"),
         }
     }
+
+    if layer_paint.is_some() {
+        canvas.restore();
+    }
 }
 
 // Render text paths (unused)
@@ -701,7 +943,7 @@ pub fn render_text_paths(
     match stroke.render_kind(false) {
         StrokeKind::Inner => {
             for (path, _) in paths {
-                draw_inner_stroke_path(canvas, path, &paint, None, antialias);
+                draw_inner_stroke_path(canvas, path, path, &paint, None, antialias);
             }
         }
         StrokeKind::Center => {
@@ -711,7 +953,134 @@ pub fn render_text_paths(
         }
         StrokeKind::Outer => {
             for (path, _) in paths {
-                draw_outer_stroke_path(canvas, path, &paint, None, antialias);
+                draw_outer_stroke_path(canvas, path, path, &paint, None, antialias);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shapes::{self, Fill, SolidColor, StrokeStyle};
+    use crate::uuid::Uuid;
+
+    fn rect_shape() -> Shape {
+        let mut shape = Shape::new(Uuid::new_v4());
+        shape.set_shape_type(Type::Rect(shapes::Rect::default()));
+        shape.set_selrect(0.0, 0.0, 100.0, 50.0);
+        shape
+    }
+
+    fn outline(stroke: Stroke) -> skia::Path {
+        stroke_outline(&rect_shape(), &stroke).expect("the rect should have an outline")
+    }
+
+    #[test]
+    fn test_outline_kind_and_width() {
+        let center = outline(Stroke::new_center_stroke(
+            4.0,
+            StrokeStyle::Solid,
+            None,
+            None,
+        ));
+        assert_eq!(center.bounds(), &Rect::from_ltrb(-2.0, -2.0, 102.0, 52.0));
+        assert!(center.contains((1.0, 25.0)) && center.contains((-1.0, 25.0)));
+        assert!(!center.contains((3.0, 25.0)));
+
+        let inner = outline(Stroke::new_inner_stroke(
+            4.0,
+            StrokeStyle::Solid,
+            None,
+            None,
+        ));
+        assert_eq!(inner.bounds(), &Rect::from_ltrb(0.0, 0.0, 100.0, 50.0));
+        assert!(inner.contains((3.0, 25.0)));
+        assert!(!inner.contains((-1.0, 25.0)) && !inner.contains((5.0, 25.0)));
+
+        let outer = outline(Stroke::new_outer_stroke(
+            4.0,
+            StrokeStyle::Solid,
+            None,
+            None,
+        ));
+        assert_eq!(outer.bounds(), &Rect::from_ltrb(-4.0, -4.0, 104.0, 54.0));
+        assert!(outer.contains((-3.0, 25.0)));
+        assert!(!outer.contains((1.0, 25.0)) && !outer.contains((-5.0, 25.0)));
+    }
+
+    #[test]
+    fn test_outline_dashes() {
+        // Points along the middle of the top side.
+        let covered = |path: &skia::Path| {
+            (5..95)
+                .filter(|x| path.contains((*x as f32 + 0.5, 0.0)))
+                .count()
+        };
+
+        let solid = outline(Stroke::new_center_stroke(
+            2.0,
+            StrokeStyle::Solid,
+            None,
+            None,
+        ));
+        let dashed = outline(Stroke::new_center_stroke(
+            2.0,
+            StrokeStyle::Dashed,
+            None,
+            None,
+        ));
+        assert_eq!(covered(&solid), 90);
+        assert!(covered(&dashed) > 20 && covered(&dashed) < 70);
+    }
+
+    #[test]
+    fn test_caps_are_within_their_bounds_margin() {
+        let caps = [
+            StrokeCap::LineArrow,
+            StrokeCap::TriangleArrow,
+            StrokeCap::SquareMarker,
+            StrokeCap::CircleMarker,
+            StrokeCap::DiamondMarker,
+            StrokeCap::Round,
+            StrokeCap::Square,
+            StrokeCap::Bar,
+            StrokeCap::ReversedArrow,
+            StrokeCap::HalfArrow,
+            StrokeCap::CircleOutline,
+            StrokeCap::DiamondOutline,
+            StrokeCap::TriangleOutline,
+        ];
+        let end = Point::new(0., 0.);
+        let rect = Rect::from_xywh(-10., -10., 10., 10.);
+
+        for cap in caps {
+            for scale in [0.5, 1., 2.] {
+                let mut stroke =
+                    Stroke::new_center_stroke(2., StrokeStyle::Solid, Some(cap), Some(cap));
+                stroke.fill = Fill::Solid(SolidColor(skia::Color::BLACK));
+                stroke.cap_start_scale = scale;
+                stroke.cap_end_scale = scale;
+                let margin = stroke.cap_bounds_margin();
+                let mut paint = stroke.to_stroked_paint(true, &rect, None, 1., true);
+
+                // Horizontal and vertical paths ending at `end`.
+                for previous in [Point::new(-10., 0.), Point::new(0., 10.)] {
+                    for (path, style) in cap_paths(cap, stroke.width, scale, &end, &previous) {
+                        paint.set_style(style);
+                        let bounds = fill_path(&path, &paint).compute_tight_bounds();
+                        assert!(
+                            Rect::from_ltrb(-margin, -margin, margin, margin)
+                                .with_outset((0.01, 0.01))
+                                .contains(bounds),
+                            "{:?} at scale {} covers {:?}, beyond its margin {}",
+                            cap,
+                            scale,
+                            bounds,
+                            margin
+                        );
+                    }
+                }
             }
         }
     }
@@ -3,7 +3,7 @@ use crate::shapes::Shape;
 
 use skia_safe::{self as skia, IRect, Paint, RRect};
 
-use super::{gpu_state::GpuState, tiles::Tile, tiles::TileViewbox, tiles::TILE_SIZE};
+use super::{backend::RenderBackend, tiles::Tile, tiles::TileViewbox, tiles::TILE_SIZE};
 
 use base64::{engine::general_purpose, Engine as _};
 use std::collections::{HashMap, HashSet};
@@ -62,7 +62,7 @@ pub struct Surfaces {
 #[allow(dead_code)]
 impl Surfaces {
     pub fn new(
-        gpu_state: &mut GpuState,
+        backend: &mut RenderBackend,
         (width, height): (i32, i32),
         sampling_options: skia::SamplingOptions,
         tile_dims: skia::ISize,
@@ -73,23 +73,23 @@ impl Surfaces {
         );
         let margins = skia::ISize::new(extra_tile_dims.width / 4, extra_tile_dims.height / 4);
 
-        let target = gpu_state.create_target_surface(width, height);
-        let filter = gpu_state.create_surface_with_dimensions("filter".to_string(), width, height);
-        let cache = gpu_state.create_surface_with_dimensions("cache".to_string(), width, height);
-        let current = gpu_state.create_surface_with_isize("current".to_string(), extra_tile_dims);
+        let target = backend.create_target_surface(width, height);
+        let filter = backend.create_surface_with_dimensions("filter".to_string(), width, height);
+        let cache = backend.create_surface_with_dimensions("cache".to_string(), width, height);
+        let current = backend.create_surface_with_isize("current".to_string(), extra_tile_dims);
         let drop_shadows =
-            gpu_state.create_surface_with_isize("drop_shadows".to_string(), extra_tile_dims);
+            backend.create_surface_with_isize("drop_shadows".to_string(), extra_tile_dims);
         let inner_shadows =
-            gpu_state.create_surface_with_isize("inner_shadows".to_string(), extra_tile_dims);
+            backend.create_surface_with_isize("inner_shadows".to_string(), extra_tile_dims);
         let text_drop_shadows =
-            gpu_state.create_surface_with_isize("text_drop_shadows".to_string(), extra_tile_dims);
+            backend.create_surface_with_isize("text_drop_shadows".to_string(), extra_tile_dims);
         let shape_fills =
-            gpu_state.create_surface_with_isize("shape_fills".to_string(), extra_tile_dims);
+            backend.create_surface_with_isize("shape_fills".to_string(), extra_tile_dims);
         let shape_strokes =
-            gpu_state.create_surface_with_isize("shape_strokes".to_string(), extra_tile_dims);
+            backend.create_surface_with_isize("shape_strokes".to_string(), extra_tile_dims);
 
-        let ui = gpu_state.create_surface_with_dimensions("ui".to_string(), width, height);
-        let debug = gpu_state.create_surface_with_dimensions("debug".to_string(), width, height);
+        let ui = backend.create_surface_with_dimensions("ui".to_string(), width, height);
+        let debug = backend.create_surface_with_dimensions("debug".to_string(), width, height);
 
         let tiles = TileTextureCache::new();
         Surfaces {
@@ -115,8 +115,8 @@ impl Surfaces {
         self.tiles.clear();
     }
 
-    pub fn resize(&mut self, gpu_state: &mut GpuState, new_width: i32, new_height: i32) {
-        self.reset_from_target(gpu_state.create_target_surface(new_width, new_height));
+    pub fn resize(&mut self, backend: &mut RenderBackend, new_width: i32, new_height: i32) {
+        self.reset_from_target(backend.create_target_surface(new_width, new_height));
     }
 
     pub fn snapshot(&mut self, id: SurfaceId) -> skia::Image {
@@ -199,9 +199,9 @@ impl Surfaces {
         self.dirty_surfaces = 0;
     }
 
-    pub fn flush_and_submit(&mut self, gpu_state: &mut GpuState, id: SurfaceId) {
+    pub fn flush_and_submit(&mut self, backend: &mut RenderBackend, id: SurfaceId) {
         let surface = self.get_mut(id);
-        gpu_state.context.flush_and_submit_surface(surface, None);
+        backend.flush_and_submit(surface);
     }
 
     pub fn draw_into(&mut self, from: SurfaceId, to: SurfaceId, paint: Option<&skia::Paint>) {
@@ -356,24 +356,15 @@ impl Surfaces {
     }
 
     pub fn draw_rect_to(&mut self, id: SurfaceId, shape: &Shape, paint: &Paint) {
-        if let Some(corners) = shape.shape_type.corners() {
-            let rrect = RRect::new_rect_radii(shape.selrect, &corners);
-            self.canvas_and_mark_dirty(id).draw_rrect(rrect, paint);
-        } else {
-            self.canvas_and_mark_dirty(id)
-                .draw_rect(shape.selrect, paint);
-        }
+        draw_rect_on_canvas(self.canvas_and_mark_dirty(id), shape, paint);
     }
 
     pub fn draw_circle_to(&mut self, id: SurfaceId, shape: &Shape, paint: &Paint) {
-        self.canvas_and_mark_dirty(id)
-            .draw_oval(shape.selrect, paint);
+        draw_circle_on_canvas(self.canvas_and_mark_dirty(id), shape, paint);
     }
 
     pub fn draw_path_to(&mut self, id: SurfaceId, shape: &Shape, paint: &Paint) {
-        if let Some(path) = shape.get_skia_path() {
-            self.canvas_and_mark_dirty(id).draw_path(&path, paint);
-        }
+        draw_path_on_canvas(self.canvas_and_mark_dirty(id), shape, paint);
     }
 
     pub fn reset(&mut self, color: skia::Color) {
@@ -502,6 +493,31 @@ impl Surfaces {
         );
     }
 
+    /// Draws the current tile into an external canvas, clipped to `tile_rect` so the
+    /// margins rendered around the tile don't overlap its neighbours. Used by off-screen
+    /// exports that composite every tile of a shape into a single surface.
+    pub fn draw_current_tile_into(&mut self, canvas: &skia::Canvas, tile_rect: &skia::Rect) {
+        let sampling_options = self.sampling_options;
+
+        canvas.save();
+        canvas.clip_rect(tile_rect, skia::ClipOp::Intersect, false);
+        self.current.clone().draw(
+            canvas,
+            (
+                tile_rect.left - self.margins.width as f32,
+                tile_rect.top - self.margins.height as f32,
+            ),
+            sampling_options,
+            None,
+        );
+        canvas.restore();
+    }
+
+    /// Creates an off-screen surface compatible with the target surface backend.
+    pub fn new_surface_with_dimensions(&mut self, dims: skia::ISize) -> Option<skia::Surface> {
+        self.target.new_surface_with_dimensions(dims)
+    }
+
     pub fn remove_cached_tiles(&mut self, color: skia::Color) {
         self.tiles.clear();
         self.cache.canvas().clear(color);
@@ -512,6 +528,25 @@ impl Surfaces {
     }
 }
 
+pub fn draw_rect_on_canvas(canvas: &skia::Canvas, shape: &Shape, paint: &Paint) {
+    if let Some(corners) = shape.shape_type.corners() {
+        let rrect = RRect::new_rect_radii(shape.selrect, &corners);
+        canvas.draw_rrect(rrect, paint);
+    } else {
+        canvas.draw_rect(shape.selrect, paint);
+    }
+}
+
+pub fn draw_circle_on_canvas(canvas: &skia::Canvas, shape: &Shape, paint: &Paint) {
+    canvas.draw_oval(shape.selrect, paint);
+}
+
+pub fn draw_path_on_canvas(canvas: &skia::Canvas, shape: &Shape, paint: &Paint) {
+    if let Some(path) = shape.get_skia_path() {
+        canvas.draw_path(&path, paint);
+    }
+}
+
 pub struct TileTextureCache {
     grid: HashMap<Tile, skia::Image>,
     removed: HashSet<Tile>,
@@ -0,0 +1,1323 @@
+use base64::{engine::general_purpose, Engine as _};
+use skia_safe::{self as skia, textlayout::TextDecoration, Matrix};
+
+use super::export::{self, shape_matrix};
+use super::{strokes, RenderState};
+use crate::math::Rect;
+use crate::shapes::text_paths::TextPaths;
+use crate::shapes::{
+    get_fill_shader, BlendMode, Blur, BlurType, Fill, FillLayer, Gradient, GradientInterpolation,
+    ImageScaleMode, Noise, NoiseType, Shadow, Shape, SolidColor, Stroke, StrokeDash, StrokeKind,
+    StrokeLineCap, StrokeLineJoin, StrokeStyle, TextContent, Type, DEFAULT_MITER_LIMIT,
+    NOISE_OCTAVES,
+};
+use crate::state::ShapesPoolRef;
+use crate::uuid::Uuid;
+
+/// Writes the subtree of `id` as an SVG document, in workspace coordinates and
+/// sized to the shape extrect.
+///
+/// The document follows the structure the renderer draws: every shape is a group
+/// with its transform, opacity and blend mode, drop shadows and layer blurs are
+/// filters, background blurs draw again the shapes below them, blurred and clipped
+/// to the shape, inner and outer strokes are clipped like in `render::strokes` and
+/// masked groups use SVG masks. With `text_as_paths` texts are written as outlines,
+/// so the document doesn't depend on the fonts available where it's opened.
+pub fn render_shape_svg(
+    render_state: &mut RenderState,
+    tree: ShapesPoolRef,
+    id: &Uuid,
+    text_as_paths: bool,
+) -> Result<String, String> {
+    let Some(shape) = tree.get(id) else {
+        return Err(format!("Shape {} not found", id));
+    };
+
+    let extrect = shape.extrect(tree, 1.);
+    if extrect.is_empty() {
+        return Err(format!("Shape {} has nothing to render", id));
+    }
+
+    let mut writer = SvgWriter {
+        render_state,
+        tree,
+        text_as_paths,
+        defs: String::new(),
+        next_id: 0,
+        backdrop: (export::background_blur_reach(tree, id) > 0.).then(Vec::new),
+    };
+    let body = writer.node(shape, &[]);
+
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="{}" height="{}" viewBox="{} {} {} {}" fill="none">"#,
+        extrect.width(),
+        extrect.height(),
+        extrect.left,
+        extrect.top,
+        extrect.width(),
+        extrect.height(),
+    );
+    if !writer.defs.is_empty() {
+        svg.push_str("<defs>");
+        svg.push_str(&writer.defs);
+        svg.push_str("</defs>");
+    }
+    svg.push_str(&body);
+    svg.push_str("</svg>");
+
+    Ok(svg)
+}
+
+struct SvgWriter<'a> {
+    render_state: &'a mut RenderState,
+    tree: ShapesPoolRef<'a>,
+    text_as_paths: bool,
+    // Gradients, patterns, clips, masks and filters referenced from the body.
+    defs: String,
+    next_id: usize,
+    // Ids of the groups drawn below the next shape. Only kept when the document
+    // has background blurs, which reference them.
+    backdrop: Option<Vec<String>>,
+}
+
+impl SvgWriter<'_> {
+    fn new_id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{}-{}", prefix, self.next_id)
+    }
+
+    /// Id of a group that background blurs may draw again, when the document
+    /// has any.
+    fn backdrop_group_id(&mut self) -> Option<String> {
+        self.backdrop.is_some().then(|| self.new_id("shape"))
+    }
+
+    fn node(&mut self, shape: &Shape, nested_fills: &[FillLayer]) -> String {
+        if shape.hidden() {
+            return String::new();
+        }
+
+        let below = self.backdrop.as_ref().map(Vec::len);
+        let group_id = self.backdrop_group_id();
+
+        let mut attrs = String::new();
+        if let Some(group_id) = &group_id {
+            attrs.push_str(&format!(r#" id="{}""#, group_id));
+        }
+        if shape.opacity() < 1.0 {
+            attrs.push_str(&format!(r#" opacity="{}""#, shape.opacity()));
+        }
+        attrs.push_str(&blend_style(shape.blend_mode()));
+        if let Some(filter_id) = self.effects_filter(shape) {
+            attrs.push_str(&format!(r#" filter="url(#{})""#, filter_id));
+        }
+
+        // Like in the renderer, the backdrop is outside of the shape opacity and
+        // blend mode.
+        let blurred_backdrop = self.background_blur(shape).unwrap_or_default();
+        let content = self.shape_content(shape, nested_fills);
+
+        // The shape replaces what was added while writing its content.
+        if let (Some(backdrop), Some(below), Some(group_id)) =
+            (self.backdrop.as_mut(), below, group_id)
+        {
+            backdrop.truncate(below);
+            backdrop.push(group_id);
+        }
+
+        format!("{}<g{}>{}</g>", blurred_backdrop, attrs, content)
+    }
+
+    fn shape_content(&mut self, shape: &Shape, nested_fills: &[FillLayer]) -> String {
+        let transform = transform_attr(&shape_matrix(shape));
+
+        match &shape.shape_type {
+            Type::Group(group) => {
+                // Groups propagate their fills to the children without fills.
+                let children = self.children(shape, &shape.fills);
+
+                let mask = shape.mask_id().and_then(|id| self.tree.get(id));
+                match mask {
+                    Some(mask) if group.masked => {
+                        let mask_content = self.node(mask, &shape.fills);
+                        let mask_id = self.new_id("mask");
+                        let region = shape.extrect(self.tree, 1.);
+                        self.defs.push_str(&format!(
+                            r#"<mask id="{}" maskUnits="userSpaceOnUse"{} style="mask-type:alpha">{}</mask>"#,
+                            mask_id,
+                            rect_attrs(&region),
+                            mask_content
+                        ));
+                        format!(r#"<g mask="url(#{})">{}</g>"#, mask_id, children)
+                    }
+                    _ => children,
+                }
+            }
+
+            Type::Frame(_) => {
+                let Some(path) = shape.geometry_path() else {
+                    return String::new();
+                };
+
+                // The frame fills are below the children.
+                let mut attrs = transform.clone();
+                let group_id = self.backdrop_group_id();
+                if let Some(group_id) = &group_id {
+                    attrs.push_str(&format!(r#" id="{}""#, group_id));
+                }
+                if let (Some(backdrop), Some(group_id)) = (self.backdrop.as_mut(), group_id) {
+                    backdrop.push(group_id);
+                }
+
+                let mut content = format!("<g{}>", attrs);
+                content.push_str(&self.fills(shape, &path, &[]));
+                // In clipped content strokes are drawn over the contained elements
+                if !shape.clip() {
+                    content.push_str(&self.strokes(shape, &path));
+                }
+                content.push_str("</g>");
+
+                let children = self.children(shape, &[]);
+                if shape.clip() {
+                    let clip_id = self.new_id("clip");
+                    self.defs.push_str(&format!(
+                        r#"<clipPath id="{}"><path d="{}"{}/></clipPath>"#,
+                        clip_id,
+                        path.to_svg(),
+                        transform
+                    ));
+                    content.push_str(&format!(
+                        r#"<g clip-path="url(#{})">{}</g>"#,
+                        clip_id, children
+                    ));
+                    content.push_str(&format!(
+                        "<g{}>{}</g>",
+                        transform,
+                        self.strokes(shape, &path)
+                    ));
+                } else {
+                    content.push_str(&children);
+                }
+                content
+            }
+
+            Type::Text(text_content) => {
+                format!("<g{}>{}</g>", transform, self.text(shape, text_content))
+            }
+
+            Type::SVGRaw(sr) => {
+                let mut matrix = shape_matrix(shape);
+                if let Some(svg_transform) = shape.svg_transform() {
+                    matrix.pre_concat(&svg_transform);
+                }
+
+                // The raw content is a document of its own, it's nested as is.
+                let content = match sr.content.find("?>") {
+                    Some(end) if sr.content.trim_start().starts_with("<?xml") => {
+                        &sr.content[end + 2..]
+                    }
+                    _ => &sr.content,
+                };
+                format!("<g{}>{}</g>", transform_attr(&matrix), content)
+            }
+
+            _ => {
+                let Some(path) = shape.geometry_path() else {
+                    return String::new();
+                };
+
+                format!(
+                    "<g{}>{}{}</g>",
+                    transform,
+                    self.fills(shape, &path, nested_fills),
+                    self.strokes(shape, &path)
+                )
+            }
+        }
+    }
+
+    fn children(&mut self, shape: &Shape, nested_fills: &[FillLayer]) -> String {
+        let mut content = String::new();
+        for child in export::children(self.tree, shape) {
+            content.push_str(&self.node(child, nested_fills));
+        }
+        content
+    }
+
+    fn fills(&mut self, shape: &Shape, path: &skia::Path, nested_fills: &[FillLayer]) -> String {
+        let fills = export::shape_fills(shape, nested_fills);
+
+        let d = path.to_svg();
+        let fill_rule = match path.fill_type() {
+            skia::PathFillType::EvenOdd => r#" fill-rule="evenodd""#,
+            _ => "",
+        };
+
+        let mut content = String::new();
+        for layer in fills.iter() {
+            if let Some((paint, opacity)) = self.paint_server(&layer.fill, &shape.selrect) {
+                content.push_str(&format!(
+                    r#"<path d="{}" fill="{}" fill-opacity="{}"{}{}/>"#,
+                    d,
+                    paint,
+                    opacity * layer.opacity,
+                    fill_rule,
+                    blend_style(layer.blend_mode)
+                ));
+            }
+        }
+
+        // Like in the renderer, fills only blend with the other fills of the shape.
+        let blends = fills
+            .iter()
+            .any(|layer| !blend_style(layer.blend_mode).is_empty());
+        if blends && !content.is_empty() {
+            content = format!(r#"<g style="isolation:isolate">{}</g>"#, content);
+        }
+
+        if content.is_empty() {
+            return content;
+        }
+        if let Some(filter_id) = self.noise_filter(shape) {
+            content = format!(r#"<g filter="url(#{})">{}</g>"#, filter_id, content);
+        }
+        if !shape.has_fills() {
+            return content;
+        }
+
+        match self.inner_shadows_filter(shape) {
+            Some(filter_id) => format!(r#"<g filter="url(#{})">{}</g>"#, filter_id, content),
+            None => content,
+        }
+    }
+
+    fn strokes(&mut self, shape: &Shape, path: &skia::Path) -> String {
+        let is_open = shape.is_open();
+        let d = path.to_svg();
+
+        let mut content = String::new();
+        for stroke in shape.visible_strokes().rev() {
+            let Some((paint, opacity)) = self.paint_server(&stroke.fill, &shape.selrect) else {
+                continue;
+            };
+
+            // Strokes with different side widths are exported as the area
+            // between their edges, which is already inside or outside.
+            let side_rrects = match &shape.shape_type {
+                Type::Rect(_) | Type::Frame(_) => {
+                    stroke.side_rrects(&shape.selrect, shape.shape_type.corners().as_ref())
+                }
+                _ => None,
+            };
+            let kind = match side_rrects {
+                Some(_) => StrokeKind::Center,
+                None => stroke.render_kind(is_open),
+            };
+            let width = match kind {
+                StrokeKind::Center => stroke.width,
+                StrokeKind::Inner | StrokeKind::Outer => stroke.width * 2.,
+            };
+
+            let opacity = opacity * stroke.opacity;
+            // Variable widths are exported as the filled outline and adjusted
+            // dashes as the path of the dashes.
+            let element = match (side_rrects, &stroke.width_profile) {
+                (Some((outer, inner)), _) => {
+                    let mut edges = skia::Path::rrect(outer, None);
+                    edges.add_rrect(inner, None);
+                    format!(
+                        r#"<path d="{}" fill-rule="evenodd" fill="{}" fill-opacity="{}"/>"#,
+                        edges.to_svg(),
+                        paint,
+                        opacity
+                    )
+                }
+                (None, Some(profile)) => format!(
+                    r#"<path d="{}" fill="{}" fill-opacity="{}"/>"#,
+                    profile.outline(path, width).to_svg(),
+                    paint,
+                    opacity
+                ),
+                (None, None) => {
+                    let dashed = stroke
+                        .adjusted_dash()
+                        .and_then(|dash| dash.adjusted_path(path));
+                    format!(
+                        r#"<path d="{}"{}/>"#,
+                        dashed
+                            .as_ref()
+                            .map_or_else(|| d.clone(), |dashed| dashed.to_svg()),
+                        stroke_attrs(shape, stroke, &paint, opacity, width, dashed.is_some())
+                    )
+                }
+            };
+
+            let mut stroke_content = String::new();
+
+            match kind {
+                StrokeKind::Inner => {
+                    let clip_id = self.new_id("clip");
+                    self.defs.push_str(&format!(
+                        r#"<clipPath id="{}"><path d="{}"/></clipPath>"#,
+                        clip_id, d
+                    ));
+                    stroke_content.push_str(&format!(
+                        r#"<g clip-path="url(#{})">{}</g>"#,
+                        clip_id, element
+                    ));
+                }
+                StrokeKind::Outer => {
+                    let mask_id = self.new_id("mask");
+                    let region = path.bounds().with_outset((width, width));
+                    self.defs.push_str(&format!(
+                        r#"<mask id="{}" maskUnits="userSpaceOnUse"{}><rect{} fill="white"/><path d="{}" fill="black"/></mask>"#,
+                        mask_id,
+                        rect_attrs(&region),
+                        rect_attrs(&region),
+                        d
+                    ));
+                    stroke_content
+                        .push_str(&format!(r#"<g mask="url(#{})">{}</g>"#, mask_id, element));
+                }
+                StrokeKind::Center => stroke_content.push_str(&element),
+            }
+
+            if is_open && (stroke.cap_start.is_some() || stroke.cap_end.is_some()) {
+                stroke_content.push_str(&stroke_caps(path, stroke, &paint, opacity, width));
+            }
+
+            let blend = blend_style(stroke.blend_mode);
+            if blend.is_empty() {
+                content.push_str(&stroke_content);
+            } else {
+                content.push_str(&format!(r#"<g{}>{}</g>"#, blend, stroke_content));
+            }
+        }
+
+        if content.is_empty() || shape.has_fills() {
+            return content;
+        }
+
+        match self.inner_shadows_filter(shape) {
+            Some(filter_id) => format!(r#"<g filter="url(#{})">{}</g>"#, filter_id, content),
+            None => content,
+        }
+    }
+
+    fn text(&mut self, shape: &Shape, text_content: &TextContent) -> String {
+        let text_paths = TextPaths::new(text_content.new_bounds(shape.selrect()));
+
+        let mut glyphs = String::new();
+        let mut outlines = String::new();
+        if self.text_as_paths {
+            for (path, fills) in text_paths.get_fill_paths(true) {
+                let d = path.to_svg();
+                // The first fill of a span is drawn on top, like in `merge_fills`.
+                for fill in fills.iter().rev() {
+                    if let Some((paint, opacity)) = self.paint_server(fill, &shape.selrect) {
+                        glyphs.push_str(&format!(
+                            r#"<path d="{}" fill="{}" fill-opacity="{}"/>"#,
+                            d, paint, opacity
+                        ));
+                    }
+                }
+                outlines.push_str(&format!(r#"<path d="{}"/>"#, d));
+            }
+        } else {
+            for run in text_paths.get_runs() {
+                let typeface = run.font.typeface();
+                let font_style = typeface.font_style();
+
+                let decoration = if run.decoration.contains(TextDecoration::UNDERLINE) {
+                    r#" text-decoration="underline""#
+                } else if run.decoration.contains(TextDecoration::LINE_THROUGH) {
+                    r#" text-decoration="line-through""#
+                } else {
+                    ""
+                };
+                let italic = match font_style.slant() {
+                    skia::font_style::Slant::Upright => "",
+                    _ => r#" font-style="italic""#,
+                };
+
+                let element = format!(
+                    r#"<text x="{}" y="{}" font-family="{}" font-size="{}" font-weight="{}"{}{} xml:space="preserve""#,
+                    run.position.x,
+                    run.position.y,
+                    escape(&typeface.family_name()),
+                    run.font.size(),
+                    *font_style.weight(),
+                    italic,
+                    decoration,
+                );
+                for fill in run.fills.iter().rev() {
+                    if let Some((paint, opacity)) = self.paint_server(fill, &shape.selrect) {
+                        glyphs.push_str(&format!(
+                            r#"{} fill="{}" fill-opacity="{}">{}</text>"#,
+                            element,
+                            paint,
+                            opacity,
+                            escape(&run.text)
+                        ));
+                    }
+                }
+                outlines.push_str(&format!("{}>{}</text>", element, escape(&run.text)));
+            }
+        }
+
+        // Text strokes are drawn centered over the glyphs.
+        let mut content = glyphs;
+        for stroke in shape.visible_strokes().rev() {
+            if let Some((paint, opacity)) = self.paint_server(&stroke.fill, &shape.selrect) {
+                content.push_str(&format!(
+                    "<g{}>{}</g>",
+                    stroke_attrs(shape, stroke, &paint, opacity, stroke.width, false),
+                    outlines
+                ));
+            }
+        }
+
+        match self.inner_shadows_filter(shape) {
+            Some(filter_id) => format!(r#"<g filter="url(#{})">{}</g>"#, filter_id, content),
+            None => content,
+        }
+    }
+
+    /// Returns the value for a `fill` or `stroke` attribute painting `fill` over
+    /// `rect`, and its opacity. Gradients and images are added to the defs.
+    fn paint_server(&mut self, fill: &Fill, rect: &Rect) -> Option<(String, f32)> {
+        match fill {
+            Fill::Solid(SolidColor(color)) => Some(color_attrs(*color)),
+
+            // SVG can't interpolate colors in OKLab nor OKLCH, so those
+            // gradients are rasterized.
+            Fill::LinearGradient(gradient) | Fill::RadialGradient(gradient)
+                if !is_svg_interpolation(gradient) =>
+            {
+                let id = self.raster_pattern(get_fill_shader(fill, rect)?, rect)?;
+                Some((format!("url(#{})", id), gradient.opacity() as f32 / 255.))
+            }
+
+            Fill::LinearGradient(gradient) => {
+                let id = self.new_id("gradient");
+                let (start, end) = gradient.linear_points(rect);
+                self.defs.push_str(&format!(
+                    r#"<linearGradient id="{}" gradientUnits="userSpaceOnUse" x1="{}" y1="{}" x2="{}" y2="{}"{}>{}</linearGradient>"#,
+                    id,
+                    start.x,
+                    start.y,
+                    end.x,
+                    end.y,
+                    color_interpolation_attr(gradient),
+                    gradient_stops(gradient.stops())
+                ));
+                Some((format!("url(#{})", id), gradient.opacity() as f32 / 255.))
+            }
+
+            Fill::RadialGradient(gradient) => {
+                let id = self.new_id("gradient");
+                let (center, radius, transform) = gradient.radial_geometry(rect);
+                self.defs.push_str(&format!(
+                    r#"<radialGradient id="{}" gradientUnits="userSpaceOnUse" cx="{}" cy="{}" r="{}" gradientTransform="{}"{}>{}</radialGradient>"#,
+                    id,
+                    center.x,
+                    center.y,
+                    radius,
+                    matrix_value(&transform),
+                    color_interpolation_attr(gradient),
+                    gradient_stops(gradient.stops())
+                ));
+                Some((format!("url(#{})", id), gradient.opacity() as f32 / 255.))
+            }
+
+            // SVG has no conic or mesh gradients, so they're rasterized into
+            // a pattern instead.
+            Fill::AngularGradient(gradient) => {
+                let id = self.raster_pattern(gradient.to_angular_shader(rect)?, rect)?;
+                Some((format!("url(#{})", id), gradient.opacity() as f32 / 255.))
+            }
+
+            Fill::MeshGradient(mesh) => {
+                let id = self.raster_pattern(mesh.to_shader(rect)?, rect)?;
+                Some((format!("url(#{})", id), mesh.opacity() as f32 / 255.))
+            }
+
+            // The pattern is in the space of the image pixels, laid over the
+            // shape with the image matrix.
+            Fill::Image(image_fill) => {
+                let image = self.render_state.images.get(&image_fill.id())?;
+                let image_rect = Rect::from_wh(image.width() as f32, image.height() as f32);
+                let matrix =
+                    image_fill.image_matrix((image_rect.width(), image_rect.height()), rect);
+
+                // Images that aren't tiled get a tile as big as the painted
+                // area, so they aren't repeated.
+                let tile = match image_fill.scale_mode() {
+                    ImageScaleMode::Tile(_) => image_rect,
+                    _ => {
+                        let mut tile = matrix
+                            .invert()
+                            .map(|inverse| inverse.map_rect(rect).0)
+                            .unwrap_or(image_rect);
+                        tile.join(image_rect);
+                        tile
+                    }
+                };
+
+                let mut data = self.render_state.images.get_png_data(&image_fill.id())?;
+                if let Some(filter) = image_fill.adjustments().to_color_filter() {
+                    data = filter_png(&data, filter)?;
+                }
+                let id = self.new_id("image");
+                self.defs.push_str(&format!(
+                    r#"<pattern id="{}" patternUnits="userSpaceOnUse"{} patternTransform="{}"><image{} preserveAspectRatio="none" href="data:image/png;base64,{}"/></pattern>"#,
+                    id,
+                    rect_attrs(&tile),
+                    matrix_value(&matrix),
+                    rect_attrs(&image_rect),
+                    general_purpose::STANDARD.encode(data)
+                ));
+                Some((format!("url(#{})", id), image_fill.opacity() as f32 / 255.))
+            }
+        }
+    }
+
+    /// Adds a pattern with `shader` rasterized over `rect` to the defs and
+    /// returns its id.
+    fn raster_pattern(&mut self, shader: skia::Shader, rect: &Rect) -> Option<String> {
+        let data = rasterize_shader(shader, rect)?;
+        let id = self.new_id("gradient");
+        self.defs.push_str(&format!(
+            r#"<pattern id="{}" patternUnits="userSpaceOnUse"{}><image{} preserveAspectRatio="none" href="data:image/png;base64,{}"/></pattern>"#,
+            id,
+            rect_attrs(rect),
+            rect_attrs(rect),
+            general_purpose::STANDARD.encode(data)
+        ));
+        Some(id)
+    }
+
+    /// Drop shadows and layer blur, applied to the whole shape along with its
+    /// children.
+    fn effects_filter(&mut self, shape: &Shape) -> Option<String> {
+        let drop_shadows: Vec<&Shadow> = shape.drop_shadows_visible().collect();
+        let blur = shape.blur.filter(|blur| {
+            !blur.hidden && blur.blur_type == BlurType::LayerBlur && blur.value > 0.0
+        });
+
+        if drop_shadows.is_empty() && blur.is_none() {
+            return None;
+        }
+
+        let id = self.new_id("filter");
+        let region = shape.extrect(self.tree, 1.);
+        let mut filter = format!(
+            r#"<filter id="{}" filterUnits="userSpaceOnUse"{} color-interpolation-filters="sRGB">"#,
+            id,
+            rect_attrs(&region)
+        );
+
+        // Each shadow is blended with the ones below it.
+        let mut shadows: Option<String> = None;
+        for (i, shadow) in drop_shadows.iter().enumerate() {
+            let result = format!("drop-shadow-{}", i);
+            filter.push_str(&drop_shadow_primitives(shadow, &result));
+            if let Some(below) = shadows {
+                filter.push_str(&blend_primitive(
+                    &result,
+                    &below,
+                    shadow.blend_mode,
+                    &result,
+                ));
+            }
+            shadows = Some(result);
+        }
+
+        let mut merge = String::new();
+        if let Some(shadows) = shadows {
+            merge.push_str(&format!(r#"<feMergeNode in="{}"/>"#, shadows));
+        }
+        merge.push_str(r#"<feMergeNode in="SourceGraphic"/>"#);
+        filter.push_str(&format!(r#"<feMerge result="shape">{}</feMerge>"#, merge));
+
+        if let Some(blur) = blur {
+            let (bounds, _) = shape_matrix(shape).map_rect(shape.selrect);
+            filter.push_str(&blur_primitives(&blur, &bounds, "shape", "", "blur"));
+        }
+
+        filter.push_str("</filter>");
+        self.defs.push_str(&filter);
+        Some(id)
+    }
+
+    /// The groups below `shape` blurred and clipped to its geometry, as the
+    /// renderer draws a background blur.
+    fn background_blur(&mut self, shape: &Shape) -> Option<String> {
+        let blur = shape.background_blur()?;
+        let below = self.backdrop.as_ref().filter(|below| !below.is_empty())?;
+        let path = shape.geometry_path()?;
+
+        let uses: String = below
+            .iter()
+            .map(|id| format!(r##"<use href="#{}"/>"##, id))
+            .collect();
+
+        let matrix = shape_matrix(shape);
+        let (bounds, _) = matrix.map_rect(shape.selrect);
+        let filter_id = self.new_id("filter");
+        let mut filter = format!(
+            r#"<filter id="{}" filterUnits="userSpaceOnUse"{} color-interpolation-filters="sRGB">"#,
+            filter_id,
+            rect_attrs(&bounds),
+        );
+        // The edges are duplicated like the renderer clamps the backdrop.
+        filter.push_str(&blur_primitives(
+            &blur,
+            &bounds,
+            "SourceGraphic",
+            r#" edgeMode="duplicate""#,
+            "blur",
+        ));
+        if blur.saturation != 0. {
+            filter.push_str(&format!(
+                r#"<feColorMatrix type="saturate" values="{}"/>"#,
+                1. + blur.saturation
+            ));
+        }
+        filter.push_str("</filter>");
+
+        let clip_id = self.new_id("clip");
+        self.defs.push_str(&filter);
+        self.defs.push_str(&format!(
+            r#"<clipPath id="{}"><path d="{}"{}/></clipPath>"#,
+            clip_id,
+            path.to_svg(),
+            transform_attr(&matrix)
+        ));
+
+        Some(format!(
+            r#"<g clip-path="url(#{})" filter="url(#{})">{}</g>"#,
+            clip_id, filter_id, uses
+        ))
+    }
+
+    /// Noise grains over the fills, in shape coordinates.
+    fn noise_filter(&mut self, shape: &Shape) -> Option<String> {
+        let noise = shape
+            .noise
+            .filter(|noise| !noise.hidden && noise.opacity > 0.)?;
+        let frequency = noise.frequency()?;
+
+        let id = self.new_id("filter");
+        let mut filter = format!(
+            r#"<filter id="{}" filterUnits="userSpaceOnUse"{} color-interpolation-filters="sRGB">"#,
+            id,
+            rect_attrs(&shape.selrect)
+        );
+        let turbulence = |seed: u32, result: &str| {
+            format!(
+                r#"<feTurbulence type="fractalNoise" baseFrequency="{}" numOctaves="{}" seed="{}" result="{}"/>"#,
+                frequency, NOISE_OCTAVES, seed, result
+            )
+        };
+
+        match noise.noise_type {
+            NoiseType::Mono => {
+                filter.push_str(&turbulence(0, "noise"));
+                filter.push_str(&colored_grains_primitives(
+                    &noise,
+                    noise.color,
+                    "noise",
+                    "grains",
+                ));
+            }
+            NoiseType::Duotone => {
+                filter.push_str(&turbulence(1, "secondary-noise"));
+                filter.push_str(&colored_grains_primitives(
+                    &noise,
+                    noise.secondary_color,
+                    "secondary-noise",
+                    "secondary-grains",
+                ));
+                filter.push_str(&turbulence(0, "noise"));
+                filter.push_str(&colored_grains_primitives(
+                    &noise,
+                    noise.color,
+                    "noise",
+                    "primary-grains",
+                ));
+                filter.push_str(r#"<feComposite in="primary-grains" in2="secondary-grains" operator="over" result="grains"/>"#);
+            }
+            NoiseType::Multicolor => {
+                filter.push_str(&turbulence(0, "noise"));
+                filter.push_str(&format!(
+                    r#"<feColorMatrix in="noise" type="matrix" values="{}" result="grains"/>"#,
+                    color_matrix_values(&noise.multicolor_matrix())
+                ));
+            }
+        }
+
+        // Like the noise paint, drawn with its opacity only over the fills.
+        filter.push_str(&format!(
+            r#"<feComponentTransfer in="grains"><feFuncA type="linear" slope="{}"/></feComponentTransfer><feComposite in2="SourceGraphic" operator="atop"/></filter>"#,
+            noise.opacity
+        ));
+        self.defs.push_str(&filter);
+        Some(id)
+    }
+
+    /// Inner shadows, applied to the fills of the shape or to its strokes when
+    /// it has no fills.
+    fn inner_shadows_filter(&mut self, shape: &Shape) -> Option<String> {
+        let inner_shadows: Vec<&Shadow> = shape.inner_shadows_visible().collect();
+        if inner_shadows.is_empty() {
+            return None;
+        }
+
+        let id = self.new_id("filter");
+        let region = shape.extrect(self.tree, 1.);
+        let mut filter = format!(
+            r#"<filter id="{}" filterUnits="userSpaceOnUse"{} color-interpolation-filters="sRGB">"#,
+            id,
+            rect_attrs(&region)
+        );
+
+        let mut below = String::from("SourceGraphic");
+        for (i, shadow) in inner_shadows.iter().enumerate() {
+            let result = format!("inner-shadow-{}", i);
+            filter.push_str(&inner_shadow_primitives(shadow, &result));
+            filter.push_str(&blend_primitive(
+                &result,
+                &below,
+                shadow.blend_mode,
+                &result,
+            ));
+            below = result;
+        }
+        filter.push_str("</filter>");
+
+        self.defs.push_str(&filter);
+        Some(id)
+    }
+}
+
+/// `dashed_path` is set when the path already has the dashes, so only their cap
+/// is needed.
+fn stroke_attrs(
+    shape: &Shape,
+    stroke: &Stroke,
+    paint: &str,
+    opacity: f32,
+    width: f32,
+    dashed_path: bool,
+) -> String {
+    let mut attrs = format!(
+        r#" fill="none" stroke="{}" stroke-opacity="{}" stroke-width="{}""#,
+        paint, opacity, width
+    );
+
+    if let Some(svg_attrs) = shape.svg_attrs.as_ref() {
+        if svg_attrs.stroke_linecap == StrokeLineCap::Round {
+            attrs.push_str(r#" stroke-linecap="round""#);
+        }
+    }
+
+    match stroke.line_join(shape.svg_attrs.as_ref()) {
+        StrokeLineJoin::Miter => {}
+        StrokeLineJoin::Round => attrs.push_str(r#" stroke-linejoin="round""#),
+        StrokeLineJoin::Bevel => attrs.push_str(r#" stroke-linejoin="bevel""#),
+    }
+    if stroke.miter_limit != DEFAULT_MITER_LIMIT {
+        attrs.push_str(&format!(r#" stroke-miterlimit="{}""#, stroke.miter_limit));
+    }
+
+    // Same patterns as `Stroke::to_paint`.
+    match stroke.style {
+        StrokeStyle::Solid => {}
+        StrokeStyle::Dotted => attrs.push_str(&format!(
+            r#" stroke-linecap="round" stroke-dasharray="0 {}""#,
+            stroke.width + 5.
+        )),
+        StrokeStyle::Dashed => attrs.push_str(&format!(
+            r#" stroke-dasharray="{} {}""#,
+            stroke.width + 10.,
+            stroke.width + 10.
+        )),
+        StrokeStyle::Mixed => attrs.push_str(&format!(
+            r#" stroke-dasharray="{} {} {} {}""#,
+            stroke.width + 5.,
+            stroke.width + 5.,
+            stroke.width + 1.,
+            stroke.width + 5.
+        )),
+        StrokeStyle::Custom => {
+            if let Some(dash) = &stroke.dash {
+                attrs.push_str(&dash_attrs(dash, dashed_path));
+            }
+        }
+    }
+
+    attrs
+}
+
+fn dash_attrs(dash: &StrokeDash, dashed_path: bool) -> String {
+    let mut attrs = match dash.cap {
+        StrokeLineCap::Butt => String::new(),
+        StrokeLineCap::Round => r#" stroke-linecap="round""#.to_string(),
+        StrokeLineCap::Square => r#" stroke-linecap="square""#.to_string(),
+    };
+
+    if !dashed_path && !dash.intervals.is_empty() {
+        let intervals: Vec<String> = dash.intervals.iter().map(f32::to_string).collect();
+        attrs.push_str(&format!(
+            r#" stroke-dasharray="{}" stroke-dashoffset="{}""#,
+            intervals.join(" "),
+            dash.offset
+        ));
+    }
+    attrs
+}
+
+fn stroke_caps(
+    path: &skia::Path,
+    stroke: &Stroke,
+    paint: &str,
+    opacity: f32,
+    width: f32,
+) -> String {
+    let Some([start, end]) = strokes::cap_anchors(path) else {
+        return String::new();
+    };
+
+    let caps = [
+        (stroke.cap_start, stroke.cap_start_scale, start),
+        (stroke.cap_end, stroke.cap_end_scale, end),
+    ];
+    let mut content = String::new();
+    for (cap, scale, (p1, p2)) in caps {
+        let Some(cap) = cap else {
+            continue;
+        };
+
+        for (cap_path, style) in strokes::cap_paths(cap, stroke.width, scale, &p1, &p2) {
+            let paint_attrs = match style {
+                skia::PaintStyle::Stroke => format!(
+                    r#"fill="none" stroke="{}" stroke-opacity="{}" stroke-width="{}""#,
+                    paint, opacity, width
+                ),
+                _ => format!(r#"fill="{}" fill-opacity="{}""#, paint, opacity),
+            };
+            content.push_str(&format!(
+                r#"<path d="{}" {}/>"#,
+                cap_path.to_svg(),
+                paint_attrs
+            ));
+        }
+    }
+    content
+}
+
+// Mirrors `Blur::filter`. A progression blends a few uniform blurs, each one
+// masked by its gradient rasterized over `rect`.
+fn blur_primitives(blur: &Blur, rect: &Rect, input: &str, edge_mode: &str, result: &str) -> String {
+    let Some(progression) = blur.progression else {
+        return format!(
+            r#"<feGaussianBlur in="{}" stdDeviation="{}"{} result="{}"/>"#,
+            input, blur.value, edge_mode, result
+        );
+    };
+
+    let mut primitives = String::new();
+    let mut total: Option<String> = None;
+    for (i, (sigma, shader)) in progression
+        .levels(rect, 1., blur.value)
+        .into_iter()
+        .enumerate()
+    {
+        let Some(mask) = rasterize_shader(shader, rect) else {
+            continue;
+        };
+        let level = format!("{}-level-{}", result, i);
+        primitives.push_str(&format!(
+            r#"<feGaussianBlur in="{0}" stdDeviation="{1}"{2} result="{3}"/><feImage{4} preserveAspectRatio="none" href="data:image/png;base64,{5}" result="{3}-mask"/><feComposite in="{3}" in2="{3}-mask" operator="in" result="{3}"/>"#,
+            input,
+            sigma,
+            edge_mode,
+            level,
+            rect_attrs(rect),
+            general_purpose::STANDARD.encode(mask)
+        ));
+        if let Some(previous) = total {
+            primitives.push_str(&format!(
+                r#"<feComposite in="{}" in2="{}" operator="arithmetic" k2="1" k3="1" result="{}"/>"#,
+                previous, level, level
+            ));
+        }
+        total = Some(level);
+    }
+
+    match total {
+        Some(total) => {
+            primitives.push_str(&format!(
+                r#"<feMerge result="{}"><feMergeNode in="{}"/></feMerge>"#,
+                result, total
+            ));
+            primitives
+        }
+        None => String::new(),
+    }
+}
+
+// Mirrors `Shadow::get_drop_shadow_filter`.
+fn drop_shadow_primitives(shadow: &Shadow, result: &str) -> String {
+    let (color, opacity) = color_attrs(shadow.color);
+    let (spread, blur) = shadow.spread_and_blur();
+    let mut primitives = format!(
+        r#"<feOffset in="SourceAlpha" dx="{}" dy="{}"/><feGaussianBlur stdDeviation="{}"/>"#,
+        shadow.offset.0, shadow.offset.1, blur
+    );
+    if spread > 0. {
+        primitives.push_str(&format!(
+            r#"<feMorphology operator="dilate" radius="{}"/>"#,
+            spread
+        ));
+    }
+    primitives.push_str(&format!(
+        r#"<feComposite result="{0}-alpha" operator="over"/><feFlood flood-color="{1}" flood-opacity="{2}"/><feComposite in2="{0}-alpha" operator="in" result="{0}"/>"#,
+        result, color, opacity
+    ));
+    // Mirrors `Shadow::get_drop_shadow_paint`.
+    if !shadow.show_behind {
+        primitives.push_str(&format!(
+            r#"<feComposite in="{0}" in2="SourceAlpha" operator="out" result="{0}"/>"#,
+            result
+        ));
+    }
+    primitives
+}
+
+// Mirrors `Shadow::get_inner_shadow_filter`.
+fn inner_shadow_primitives(shadow: &Shadow, result: &str) -> String {
+    let (color, opacity) = color_attrs(shadow.color);
+    let (spread, blur) = shadow.spread_and_blur();
+    // Center glows keep the blurred shape instead of its outside.
+    let operator = if shadow.is_center_glow() { "in" } else { "out" };
+    let mut primitives = format!(
+        r#"<feOffset in="SourceAlpha" dx="{}" dy="{}"/><feGaussianBlur stdDeviation="{}" result="{}-alpha"/><feFlood flood-color="{}" flood-opacity="{}"/><feComposite in2="{}-alpha" operator="{}"/>"#,
+        shadow.offset.0,
+        shadow.offset.1,
+        blur * 0.5,
+        result,
+        color,
+        opacity,
+        result,
+        operator
+    );
+    if spread > 0. {
+        primitives.push_str(&format!(
+            r#"<feMorphology operator="dilate" radius="{}"/>"#,
+            spread
+        ));
+    }
+    primitives.push_str(&format!(
+        r#"<feComposite in2="SourceAlpha" operator="in" result="{}"/>"#,
+        result
+    ));
+    primitives
+}
+
+// Mirrors `Noise::colored_grains`.
+fn colored_grains_primitives(
+    noise: &Noise,
+    color: skia::Color,
+    input: &str,
+    result: &str,
+) -> String {
+    let (color, opacity) = color_attrs(color);
+    format!(
+        r#"<feColorMatrix in="{0}" type="matrix" values="{1}" result="{2}-mask"/><feFlood flood-color="{3}" flood-opacity="{4}"/><feComposite in2="{2}-mask" operator="in" result="{2}"/>"#,
+        input,
+        color_matrix_values(&noise.mask_matrix()),
+        result,
+        color,
+        opacity
+    )
+}
+
+fn color_matrix_values(matrix: &[f32; 20]) -> String {
+    matrix
+        .iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Blends `input` over `below` like a paint with `blend_mode`.
+fn blend_primitive(input: &str, below: &str, blend_mode: BlendMode, result: &str) -> String {
+    format!(
+        r#"<feBlend in="{}" in2="{}" mode="{}" result="{}"/>"#,
+        input,
+        below,
+        css_blend_mode(blend_mode.0).unwrap_or("normal"),
+        result
+    )
+}
+
+fn is_svg_interpolation(gradient: &Gradient) -> bool {
+    matches!(
+        gradient.interpolation(),
+        GradientInterpolation::Srgb | GradientInterpolation::LinearSrgb
+    )
+}
+
+fn color_interpolation_attr(gradient: &Gradient) -> &'static str {
+    match gradient.interpolation() {
+        GradientInterpolation::LinearSrgb => r#" color-interpolation="linearRGB""#,
+        _ => "",
+    }
+}
+
+fn gradient_stops(stops: impl Iterator<Item = (skia::Color, f32)>) -> String {
+    stops
+        .map(|(color, offset)| {
+            let (color, opacity) = color_attrs(color);
+            format!(
+                r#"<stop offset="{}" stop-color="{}" stop-opacity="{}"/>"#,
+                offset, color, opacity
+            )
+        })
+        .collect()
+}
+
+fn color_attrs(color: skia::Color) -> (String, f32) {
+    (
+        format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b()),
+        color.a() as f32 / 255.,
+    )
+}
+
+/// Resolution of the shaders that are written as images, in pixels per unit.
+const RASTER_SCALE: f32 = 2.;
+
+/// Paints `shader` over `rect` and returns it as a PNG of the size of `rect`.
+fn rasterize_shader(shader: skia::Shader, rect: &Rect) -> Option<Vec<u8>> {
+    let width = (rect.width() * RASTER_SCALE).ceil() as i32;
+    let height = (rect.height() * RASTER_SCALE).ceil() as i32;
+    let mut surface = skia::surfaces::raster_n32_premul((width.max(1), height.max(1)))?;
+
+    let canvas = surface.canvas();
+    canvas.scale((RASTER_SCALE, RASTER_SCALE));
+    canvas.translate((-rect.left, -rect.top));
+    let mut paint = skia::Paint::default();
+    paint.set_shader(shader);
+    canvas.draw_rect(rect, &paint);
+
+    let data = surface
+        .image_snapshot()
+        .encode(None, skia::EncodedImageFormat::PNG, 100)?;
+    Some(data.as_bytes().to_vec())
+}
+
+/// Applies `filter` to an encoded image, and encodes the result as a PNG.
+fn filter_png(data: &[u8], filter: skia::ColorFilter) -> Option<Vec<u8>> {
+    let image = skia::Image::from_encoded(skia::Data::new_copy(data))?;
+    let mut surface = skia::surfaces::raster_n32_premul(image.dimensions())?;
+
+    let mut paint = skia::Paint::default();
+    paint.set_color_filter(filter);
+    surface.canvas().draw_image(&image, (0., 0.), Some(&paint));
+
+    let data = surface
+        .image_snapshot()
+        .encode(None, skia::EncodedImageFormat::PNG, 100)?;
+    Some(data.as_bytes().to_vec())
+}
+
+fn rect_attrs(rect: &Rect) -> String {
+    format!(
+        r#" x="{}" y="{}" width="{}" height="{}""#,
+        rect.left,
+        rect.top,
+        rect.width(),
+        rect.height()
+    )
+}
+
+fn matrix_value(matrix: &Matrix) -> String {
+    format!(
+        "matrix({} {} {} {} {} {})",
+        matrix.scale_x(),
+        matrix.skew_y(),
+        matrix.skew_x(),
+        matrix.scale_y(),
+        matrix.translate_x(),
+        matrix.translate_y()
+    )
+}
+
+fn transform_attr(matrix: &Matrix) -> String {
+    if matrix.is_identity() {
+        String::new()
+    } else {
+        format!(r#" transform="{}""#, matrix_value(matrix))
+    }
+}
+
+fn blend_style(blend_mode: BlendMode) -> String {
+    css_blend_mode(blend_mode.0)
+        .map(|mode| format!(r#" style="mix-blend-mode:{}""#, mode))
+        .unwrap_or_default()
+}
+
+fn css_blend_mode(mode: skia::BlendMode) -> Option<&'static str> {
+    match mode {
+        skia::BlendMode::Multiply => Some("multiply"),
+        skia::BlendMode::Screen => Some("screen"),
+        skia::BlendMode::Overlay => Some("overlay"),
+        skia::BlendMode::Darken => Some("darken"),
+        skia::BlendMode::Lighten => Some("lighten"),
+        skia::BlendMode::ColorDodge => Some("color-dodge"),
+        skia::BlendMode::ColorBurn => Some("color-burn"),
+        skia::BlendMode::HardLight => Some("hard-light"),
+        skia::BlendMode::SoftLight => Some("soft-light"),
+        skia::BlendMode::Difference => Some("difference"),
+        skia::BlendMode::Exclusion => Some("exclusion"),
+        skia::BlendMode::Hue => Some("hue"),
+        skia::BlendMode::Saturation => Some("saturation"),
+        skia::BlendMode::Color => Some("color"),
+        skia::BlendMode::Luminosity => Some("luminosity"),
+        _ => None,
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::RenderBackend;
+    use crate::shapes::{self, Group, ShadowStyle};
+    use crate::state::ShapesPool;
+
+    fn add_rect(pool: &mut ShapesPool, id: Uuid, fill: Fill) -> &mut Shape {
+        let shape = pool.add_shape(id);
+        shape.set_shape_type(Type::Rect(shapes::Rect::default()));
+        shape.set_selrect(0.0, 0.0, 100.0, 50.0);
+        shape.add_fill(fill);
+        shape
+    }
+
+    fn solid(color: skia::Color) -> Fill {
+        Fill::Solid(SolidColor(color))
+    }
+
+    fn render(pool: &ShapesPool, id: &Uuid) -> String {
+        let mut render_state = RenderState::new(1, 1, RenderBackend::raster());
+        render_shape_svg(&mut render_state, pool, id, false).unwrap()
+    }
+
+    #[test]
+    fn test_gradient_fill() {
+        let mut pool = ShapesPool::new();
+        let id = Uuid::new_v4();
+        let gradient = Gradient::new(
+            (0.0, 0.0),
+            (1.0, 0.0),
+            255,
+            1.0,
+            &[(skia::Color::RED, 0.0), (skia::Color::BLUE, 1.0)],
+        );
+        add_rect(&mut pool, id, Fill::LinearGradient(gradient));
+
+        let svg = render(&pool, &id);
+        assert!(svg.contains(
+            r##"<linearGradient id="gradient-1" gradientUnits="userSpaceOnUse" x1="0" y1="0" x2="100" y2="0"><stop offset="0" stop-color="#ff0000" stop-opacity="1"/><stop offset="1" stop-color="#0000ff" stop-opacity="1"/></linearGradient>"##
+        ));
+        assert!(svg.contains(r##"fill="url(#gradient-1)" fill-opacity="1""##));
+    }
+
+    #[test]
+    fn test_dashed_stroke() {
+        let mut pool = ShapesPool::new();
+        let id = Uuid::new_v4();
+        let shape = add_rect(&mut pool, id, solid(skia::Color::BLACK));
+        let mut stroke = Stroke::new_center_stroke(2.0, StrokeStyle::Dashed, None, None);
+        stroke.fill = solid(skia::Color::RED);
+        shape.add_stroke(stroke);
+
+        let svg = render(&pool, &id);
+        assert!(svg.contains(
+            r##" fill="none" stroke="#ff0000" stroke-opacity="1" stroke-width="2" stroke-dasharray="12 12"/>"##
+        ));
+    }
+
+    #[test]
+    fn test_mask() {
+        let mut pool = ShapesPool::new();
+        let group_id = Uuid::new_v4();
+        let mask_id = Uuid::new_v4();
+        let content_id = Uuid::new_v4();
+
+        let group = pool.add_shape(group_id);
+        group.set_shape_type(Type::Group(Group { masked: true }));
+        group.children = vec![mask_id, content_id];
+        add_rect(&mut pool, mask_id, solid(skia::Color::RED)).set_parent(group_id);
+        add_rect(&mut pool, content_id, solid(skia::Color::BLUE)).set_parent(group_id);
+
+        let svg = render(&pool, &group_id);
+        let mask_start = svg.find(r#"<mask id="mask-1" maskUnits="userSpaceOnUse""#);
+        let mask_end = svg.find("</mask>");
+        let (Some(mask_start), Some(mask_end)) = (mask_start, mask_end) else {
+            panic!("missing mask in {}", svg);
+        };
+        let mask = &svg[mask_start..mask_end];
+        assert!(mask.contains(r#"style="mask-type:alpha""#));
+        assert!(mask.contains("#ff0000"));
+        assert!(!mask.contains("#0000ff"));
+
+        let masked = &svg[svg.find(r##"<g mask="url(#mask-1)">"##).unwrap()..];
+        assert!(masked.contains("#0000ff"));
+        assert!(!masked.contains("#ff0000"));
+    }
+
+    #[test]
+    fn test_drop_shadow_filter() {
+        let mut pool = ShapesPool::new();
+        let id = Uuid::new_v4();
+        let shape = add_rect(&mut pool, id, solid(skia::Color::RED));
+        shape.add_shadow(Shadow::new(
+            skia::Color::BLACK,
+            8.0,
+            0.0,
+            (0.0, 4.0),
+            ShadowStyle::Drop,
+            false,
+        ));
+
+        let svg = render(&pool, &id);
+        assert!(svg.contains(r#"<filter id="filter-1" filterUnits="userSpaceOnUse""#));
+        assert!(svg.contains(r#"<feOffset in="SourceAlpha" dx="0" dy="4"/>"#));
+        assert!(svg.contains(r##"<feFlood flood-color="#000000" flood-opacity="1"/>"##));
+        // Shadows are knocked out of the shape unless they show behind it.
+        assert!(svg.contains(
+            r#"<feComposite in="drop-shadow-0" in2="SourceAlpha" operator="out" result="drop-shadow-0"/>"#
+        ));
+        assert!(svg.contains(r##"<g filter="url(#filter-1)">"##));
+    }
+
+    #[test]
+    fn test_escape() {
+        // Texts are laid out with the fonts of the global state, so only the
+        // escaping of their content and font family is checked here.
+        assert_eq!(
+            escape(r#"<b> & "quotes""#),
+            "&lt;b&gt; &amp; &quot;quotes&quot;"
+        );
+        assert_eq!(escape("plain text"), "plain text");
+    }
+}
@@ -3,7 +3,7 @@ use crate::{
     math::Rect,
     shapes::{
         calculate_position_data, calculate_text_layout_data, merge_fills, set_paint_fill,
-        ParagraphBuilderGroup, Stroke, StrokeKind, TextContent,
+        ParagraphBuilderGroup, Stroke, StrokeKind, StrokeStyle, TextContent,
     },
     utils::{get_fallback_fonts, get_font_collection},
 };
@@ -153,6 +153,19 @@ fn get_text_stroke_paints(
         }
     }
 
+    for paint in paints.iter_mut() {
+        if paint.style() != skia::PaintStyle::Stroke {
+            continue;
+        }
+        stroke.apply_join(paint, None);
+        // Glyphs are outlined by the paragraph, so dashes can't be adjusted to
+        // their corners and use the plain pattern.
+        if let (StrokeStyle::Custom, Some(dash)) = (stroke.style, &stroke.dash) {
+            dash.apply_cap(paint);
+            paint.set_path_effect(dash.path_effect());
+        }
+    }
+
     paints
 }
 
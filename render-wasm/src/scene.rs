@@ -0,0 +1,321 @@
+mod decode;
+mod encode;
+mod enums;
+
+use std::collections::HashMap;
+
+use decode::Reader;
+use encode::Writer;
+
+use crate::math::Matrix;
+use crate::shapes::{FontFamily, Shape, StructureEntry, StructureEntryType, Type};
+use crate::state::State;
+use crate::uuid::Uuid;
+
+const MAGIC: &[u8; 4] = b"PSCN";
+const VERSION: u32 = 1;
+
+/// A scene file: the shapes of a page along with the fonts and images they use,
+/// so it can be rendered without the frontend. See `docs/scene_files.md`.
+///
+/// State dumps are scenes too. They also keep the modifiers and structure
+/// entries of the pool, and reference the fonts and images by id only.
+pub struct Scene {
+    pub fonts: Vec<SceneFont>,
+    pub images: Vec<SceneImage>,
+    pub shapes: Vec<Shape>,
+    pub modifiers: Vec<(Uuid, Matrix)>,
+    pub structure: Vec<StructureEntry>,
+    pub scale_content: Vec<(Uuid, f32)>,
+}
+
+/// Fonts and images without data are only referenced, they have to be uploaded
+/// before loading the scene.
+pub struct SceneFont {
+    pub family: FontFamily,
+    pub is_emoji: bool,
+    pub is_fallback: bool,
+    pub data: Vec<u8>,
+}
+
+pub struct SceneImage {
+    pub id: Uuid,
+    pub is_thumbnail: bool,
+    pub data: Vec<u8>,
+}
+
+impl Scene {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader::new(bytes);
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err("Not a scene file".to_string());
+        }
+
+        let version = reader.read::<u32>()?;
+        if version != VERSION {
+            return Err(format!("Unsupported scene version: {}", version));
+        }
+
+        let mut fonts = Vec::new();
+        for _ in 0..reader.read::<u32>()? {
+            fonts.push(SceneFont {
+                family: reader.read()?,
+                is_emoji: reader.read()?,
+                is_fallback: reader.read()?,
+                data: reader.read_bytes()?.to_vec(),
+            });
+        }
+
+        let mut images = Vec::new();
+        for _ in 0..reader.read::<u32>()? {
+            images.push(SceneImage {
+                id: reader.read()?,
+                is_thumbnail: reader.read()?,
+                data: reader.read_bytes()?.to_vec(),
+            });
+        }
+
+        let shapes = reader.read()?;
+
+        let modifiers = reader.read()?;
+        let structure = reader.read()?;
+        let scale_content = reader.read()?;
+
+        Ok(Self {
+            fonts,
+            images,
+            shapes,
+            modifiers,
+            structure,
+            scale_content,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer::new();
+        writer.write(MAGIC);
+        writer.write(&VERSION);
+
+        writer.write(&(self.fonts.len() as u32));
+        for font in self.fonts.iter() {
+            writer.write(&font.family);
+            writer.write(&font.is_emoji);
+            writer.write(&font.is_fallback);
+            writer.write_bytes(&font.data);
+        }
+
+        writer.write(&(self.images.len() as u32));
+        for image in self.images.iter() {
+            writer.write(&image.id);
+            writer.write(&image.is_thumbnail);
+            writer.write_bytes(&image.data);
+        }
+
+        writer.write(&self.shapes);
+        writer.write(&self.modifiers);
+        writer.write(&self.structure);
+        writer.write(&self.scale_content);
+        writer.into_bytes()
+    }
+}
+
+impl State {
+    /// Snapshot of the shapes pool, with its modifiers and structure entries. The
+    /// uploaded fonts and images are referenced by id, without their data.
+    pub fn dump_scene(&self) -> Scene {
+        let fonts = self
+            .render_state
+            .fonts()
+            .families()
+            .map(|(family, is_emoji, is_fallback)| SceneFont {
+                family,
+                is_emoji,
+                is_fallback,
+                data: Vec::new(),
+            })
+            .collect();
+
+        let images = self
+            .render_state
+            .images
+            .ids()
+            .map(|(id, is_thumbnail)| SceneImage {
+                id,
+                is_thumbnail,
+                data: Vec::new(),
+            })
+            .collect();
+
+        Scene {
+            fonts,
+            images,
+            shapes: self.shapes.base_shapes().cloned().collect(),
+            modifiers: self
+                .shapes
+                .modifiers()
+                .map(|(id, matrix)| (id, *matrix))
+                .collect(),
+            structure: self
+                .shapes
+                .structure()
+                .flat_map(|(_, entries)| entries.iter().cloned())
+                .collect(),
+            scale_content: self.shapes.scale_content().collect(),
+        }
+    }
+
+    /// Replaces the shapes of the state with the ones of the scene and stores its
+    /// fonts and images. Text layouts are computed once all the fonts are loaded.
+    pub fn load_scene(&mut self, scene: Scene) -> Result<(), String> {
+        for font in scene.fonts {
+            if font.data.is_empty() {
+                let fonts = self.render_state.fonts();
+                if !fonts.has_family(&font.family, font.is_emoji) {
+                    eprintln!("Font {} is missing, it has to be stored first", font.family);
+                }
+                continue;
+            }
+            self.render_state.fonts_mut().add(
+                font.family,
+                &font.data,
+                font.is_emoji,
+                font.is_fallback,
+            )?;
+        }
+
+        for image in scene.images {
+            if image.data.is_empty() {
+                continue;
+            }
+            self.render_state
+                .add_image(image.id, image.is_thumbnail, &image.data)?;
+        }
+
+        self.current_id = None;
+        self.shapes.clean_all();
+        self.shapes.initialize(scene.shapes.len());
+        for shape in scene.shapes {
+            *self.shapes.add_shape(shape.id) = shape;
+        }
+
+        if !scene.modifiers.is_empty() {
+            self.shapes
+                .set_modifiers(scene.modifiers.into_iter().collect());
+        }
+
+        let mut structure: HashMap<Uuid, Vec<StructureEntry>> = HashMap::new();
+        for entry in scene.structure {
+            if entry.entry_type != StructureEntryType::ScaleContent {
+                structure.entry(entry.parent).or_default().push(entry);
+            }
+        }
+        if !structure.is_empty() {
+            self.shapes.set_structure(structure);
+        }
+
+        if !scene.scale_content.is_empty() {
+            self.shapes
+                .set_scale_content(scene.scale_content.into_iter().collect());
+        }
+
+        for shape in self.shapes.iter_mut() {
+            if let Type::Text(text_content) = &mut shape.shape_type {
+                text_content.update_layout(shape.selrect);
+            }
+        }
+
+        self.rebuild_tiles();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shapes::{
+        BlendMode, Fill, FillLayer, Gradient, GradientInterpolation, HueInterpolation, Noise,
+        NoiseType, Shadow, ShadowStyle, SolidColor, Stroke, StrokeCap, StrokeStyle, TextDecoration,
+    };
+    use skia_safe as skia;
+
+    #[test]
+    fn test_scene_roundtrip() {
+        let mut shape = Shape::new(Uuid::new_v4());
+        shape.set_shape_type(Type::Circle);
+        shape.set_selrect(10.0, 20.0, 110.0, 70.0);
+        shape.set_transform(1.0, 0.5, -0.5, 1.0, 3.0, 4.0);
+        shape.set_blend_mode(BlendMode(skia::BlendMode::Multiply));
+        shape.add_fill(Fill::Solid(SolidColor(skia::Color::new(0xfffabada))));
+        let mut gradient = Gradient::new(
+            (0.0, 0.0),
+            (1.0, 1.0),
+            128,
+            1.0,
+            &[(skia::Color::RED, 0.0), (skia::Color::BLUE, 1.0)],
+        );
+        gradient.set_interpolation(GradientInterpolation::Oklch, HueInterpolation::Longer);
+        gradient.set_dither(true);
+        shape.add_fill(FillLayer::new(
+            Fill::LinearGradient(gradient),
+            BlendMode(skia::BlendMode::Screen),
+            0.5,
+        ));
+        shape.add_stroke(Stroke::new_center_stroke(
+            2.0,
+            StrokeStyle::Dashed,
+            Some(StrokeCap::Round),
+            None,
+        ));
+        let mut shadow = Shadow::new(
+            skia::Color::BLACK,
+            8.0,
+            2.0,
+            (0.0, 4.0),
+            ShadowStyle::Drop,
+            false,
+        );
+        shadow.blend_mode = BlendMode(skia::BlendMode::Multiply);
+        shadow.show_behind = true;
+        shape.add_shadow(shadow);
+        shape.set_noise(Some(Noise::new(NoiseType::Duotone, false, 2.0, 0.4, 0.2)));
+
+        let scene = Scene {
+            fonts: vec![],
+            images: vec![],
+            shapes: vec![shape.clone()],
+            modifiers: vec![(shape.id, Matrix::translate((5.0, 5.0)))],
+            structure: vec![StructureEntry::new(
+                StructureEntryType::AddChild,
+                0,
+                Uuid::nil(),
+                shape.id,
+                0.0,
+            )],
+            scale_content: vec![],
+        };
+
+        let bytes = scene.to_bytes();
+        let decoded = Scene::from_bytes(&bytes).unwrap();
+
+        assert_eq!(decoded.shapes.len(), 1);
+        assert_eq!(decoded.shapes[0].id, shape.id);
+        assert_eq!(decoded.shapes[0].selrect, shape.selrect);
+        assert_eq!(decoded.shapes[0].transform, shape.transform);
+        assert_eq!(decoded.shapes[0].fills, shape.fills);
+        assert_eq!(decoded.shapes[0].strokes, shape.strokes);
+        assert_eq!(decoded.shapes[0].shadows, shape.shadows);
+        assert_eq!(decoded.shapes[0].noise, shape.noise);
+        assert_eq!(decoded.modifiers, scene.modifiers);
+        assert_eq!(decoded.structure, scene.structure);
+        assert_eq!(decoded.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_enum_values_match_the_wasm_api() {
+        let mut writer = Writer::new();
+        writer.write(&skia::BlendMode::Multiply);
+        writer.write(&TextDecoration::LINE_THROUGH);
+        assert_eq!(writer.into_bytes(), vec![24, 2]);
+    }
+}
@@ -0,0 +1,621 @@
+use crate::math::{Matrix, Point, Rect};
+use crate::shapes::{
+    BlendMode, Blur, BlurProgression, Bool, Color, Corners, Fill, FillLayer, FlexData, FontFamily,
+    Frame, Gradient, GridCell, GridData, GridTrack, Group, ImageAdjustments, ImageFill,
+    ImageScaleMode, ImageTransform, Layout, LayoutData, LayoutItem, MeshGradient, MeshPatch, Noise,
+    Paragraph, Path, Rect as RectShape, SVGRaw, Segment, Shadow, Shape, SolidColor, Stroke,
+    StrokeDash, StrokeSides, StrokeWidthProfile, StructureEntry, SvgAttrs, TextContent, TextSpan,
+    Type,
+};
+use crate::uuid::Uuid;
+
+/// Reads the values of a scene file. All the numbers are little endian.
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0 }
+    }
+
+    pub fn read<T: Decode>(&mut self) -> Result<T, String> {
+        T::decode(self)
+    }
+
+    /// Reads a length-prefixed blob, like the font and image data.
+    pub fn read_bytes(&mut self) -> Result<&'a [u8], String> {
+        let len = self.read::<u32>()? as usize;
+        self.take(len)
+    }
+
+    pub fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| format!("Unexpected end of scene at byte {}", self.offset))?;
+
+        let slice = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(slice)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+}
+
+pub trait Decode: Sized {
+    fn decode(reader: &mut Reader) -> Result<Self, String>;
+}
+
+impl Decode for u8 {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        Ok(reader.take(1)?[0])
+    }
+}
+
+impl Decode for bool {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        Ok(reader.read::<u8>()? != 0)
+    }
+}
+
+impl Decode for u32 {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        Ok(u32::from_le_bytes(reader.take_array()?))
+    }
+}
+
+impl Decode for i32 {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        Ok(i32::from_le_bytes(reader.take_array()?))
+    }
+}
+
+impl Decode for f32 {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        Ok(f32::from_le_bytes(reader.take_array()?))
+    }
+}
+
+impl Decode for Uuid {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        Ok(Uuid::from(reader.take_array::<16>()?))
+    }
+}
+
+impl Decode for String {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        let bytes = reader.read_bytes()?;
+        String::from_utf8(bytes.to_vec()).map_err(|e| format!("Invalid string: {}", e))
+    }
+}
+
+impl<T: Decode> Decode for Option<T> {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        if reader.read::<bool>()? {
+            Ok(Some(reader.read()?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<T: Decode> Decode for Vec<T> {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        let len = reader.read::<u32>()? as usize;
+        // The length comes from the file, we don't trust it for the allocation.
+        let mut items = Vec::with_capacity(len.min(1024));
+        for _ in 0..len {
+            items.push(reader.read()?);
+        }
+        Ok(items)
+    }
+}
+
+impl<A: Decode, B: Decode> Decode for (A, B) {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        Ok((reader.read()?, reader.read()?))
+    }
+}
+
+impl Decode for Point {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        let (x, y) = reader.read::<(f32, f32)>()?;
+        Ok(Point::new(x, y))
+    }
+}
+
+impl Decode for Rect {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        Ok(Rect::from_ltrb(
+            reader.read()?,
+            reader.read()?,
+            reader.read()?,
+            reader.read()?,
+        ))
+    }
+}
+
+// Same order as `set_shape_transform`: a b c d e f.
+impl Decode for Matrix {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        let [a, b, c, d, e, f] = reader.read::<[f32; 6]>()?;
+        Ok(Matrix::new_all(a, c, e, b, d, f, 0.0, 0.0, 1.0))
+    }
+}
+
+impl<T: Decode + Copy + Default, const N: usize> Decode for [T; N] {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        let mut array = [T::default(); N];
+        for item in array.iter_mut() {
+            *item = reader.read()?;
+        }
+        Ok(array)
+    }
+}
+
+impl Decode for Color {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        Ok(Color::new(reader.read()?))
+    }
+}
+
+impl Decode for Gradient {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        let start = reader.read()?;
+        let end = reader.read()?;
+        let opacity = reader.read()?;
+        let width = reader.read()?;
+        let stops: Vec<(Color, f32)> = reader.read()?;
+        let mut gradient = Gradient::new(start, end, opacity, width, &stops);
+        gradient.set_interpolation(reader.read()?, reader.read()?);
+        gradient.set_dither(reader.read()?);
+        Ok(gradient)
+    }
+}
+
+impl Decode for ImageFill {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        let id = reader.read()?;
+        let opacity = reader.read()?;
+        let width = reader.read()?;
+        let height = reader.read()?;
+        let scale_mode = reader.read()?;
+        let mut image_fill = ImageFill::new(id, opacity, width, height, scale_mode);
+        image_fill.set_adjustments(reader.read()?);
+        Ok(image_fill)
+    }
+}
+
+impl Decode for ImageAdjustments {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        Ok(ImageAdjustments {
+            exposure: reader.read()?,
+            contrast: reader.read()?,
+            saturation: reader.read()?,
+            temperature: reader.read()?,
+            tint: reader.read()?,
+            highlights: reader.read()?,
+            shadows: reader.read()?,
+        })
+    }
+}
+
+// Same scale modes as the wasm API.
+impl Decode for ImageScaleMode {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        match reader.read::<u8>()? {
+            0 => Ok(ImageScaleMode::Stretch),
+            1 => Ok(ImageScaleMode::Fill),
+            2 => Ok(ImageScaleMode::Fit),
+            3 => Ok(ImageScaleMode::Crop(ImageTransform {
+                offset: reader.read()?,
+                scale: reader.read()?,
+                rotation: reader.read()?,
+            })),
+            4 => Ok(ImageScaleMode::Tile(reader.read()?)),
+            value => Err(format!("Invalid image scale mode: {}", value)),
+        }
+    }
+}
+
+impl Decode for MeshPatch {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        Ok(MeshPatch {
+            cubics: reader.read()?,
+            colors: reader.read()?,
+        })
+    }
+}
+
+impl Decode for MeshGradient {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        Ok(MeshGradient::new(reader.read()?, reader.read()?))
+    }
+}
+
+// Same fill types as the wasm API, plus mesh gradients.
+impl Decode for Fill {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        match reader.read::<u8>()? {
+            0 => Ok(Fill::Solid(SolidColor(reader.read()?))),
+            1 => Ok(Fill::LinearGradient(reader.read()?)),
+            2 => Ok(Fill::RadialGradient(reader.read()?)),
+            3 => Ok(Fill::Image(reader.read()?)),
+            4 => Ok(Fill::AngularGradient(reader.read()?)),
+            5 => Ok(Fill::MeshGradient(reader.read()?)),
+            value => Err(format!("Invalid fill type: {}", value)),
+        }
+    }
+}
+
+impl Decode for FillLayer {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        Ok(FillLayer::new(
+            reader.read()?,
+            BlendMode(reader.read()?),
+            reader.read()?,
+        ))
+    }
+}
+
+impl Decode for Stroke {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        // Same layout as the fill layers.
+        let layer: FillLayer = reader.read()?;
+        Ok(Stroke {
+            fill: layer.fill,
+            blend_mode: layer.blend_mode,
+            opacity: layer.opacity,
+            width: reader.read()?,
+            style: reader.read()?,
+            dash: reader.read()?,
+            width_profile: reader.read()?,
+            sides: reader.read()?,
+            join: reader.read()?,
+            miter_limit: reader.read()?,
+            cap_end: reader.read()?,
+            cap_start: reader.read()?,
+            cap_start_scale: reader.read()?,
+            cap_end_scale: reader.read()?,
+            kind: reader.read()?,
+        })
+    }
+}
+
+impl Decode for StrokeDash {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        Ok(StrokeDash {
+            intervals: reader.read()?,
+            offset: reader.read()?,
+            adjust: reader.read()?,
+            cap: reader.read()?,
+        })
+    }
+}
+
+impl Decode for StrokeSides {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        Ok(StrokeSides {
+            top: reader.read()?,
+            right: reader.read()?,
+            bottom: reader.read()?,
+            left: reader.read()?,
+        })
+    }
+}
+
+impl Decode for StrokeWidthProfile {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        match reader.read::<u8>()? {
+            0 => Ok(StrokeWidthProfile::Points(reader.read()?)),
+            1 => Ok(StrokeWidthProfile::TaperStart),
+            2 => Ok(StrokeWidthProfile::TaperEnd),
+            3 => Ok(StrokeWidthProfile::TaperBoth),
+            value => Err(format!("Invalid stroke width profile: {}", value)),
+        }
+    }
+}
+
+impl Decode for Shadow {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        let mut shadow = Shadow::new(
+            reader.read()?,
+            reader.read()?,
+            reader.read()?,
+            reader.read()?,
+            reader.read()?,
+            reader.read()?,
+        );
+        shadow.blend_mode = BlendMode(reader.read()?);
+        shadow.technique = reader.read()?;
+        shadow.source = reader.read()?;
+        shadow.show_behind = reader.read()?;
+        Ok(shadow)
+    }
+}
+
+impl Decode for Blur {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        let blur_type = reader.read()?;
+        let hidden = reader.read()?;
+        let value = reader.read()?;
+        let mut blur = Blur::new(blur_type, hidden, value);
+        blur.saturation = reader.read()?;
+        blur.progression = reader.read()?;
+        Ok(blur)
+    }
+}
+
+impl Decode for Noise {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        let mut noise = Noise::new(
+            reader.read()?,
+            reader.read()?,
+            reader.read()?,
+            reader.read()?,
+            reader.read()?,
+        );
+        noise.color = reader.read()?;
+        noise.secondary_color = reader.read()?;
+        Ok(noise)
+    }
+}
+
+impl Decode for BlurProgression {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        Ok(BlurProgression {
+            progression_type: reader.read()?,
+            start: reader.read()?,
+            end: reader.read()?,
+            start_value: reader.read()?,
+        })
+    }
+}
+
+impl Decode for SvgAttrs {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        Ok(SvgAttrs {
+            fill_rule: reader.read()?,
+            stroke_linecap: reader.read()?,
+            stroke_linejoin: reader.read()?,
+            fill_none: reader.read()?,
+        })
+    }
+}
+
+// Same commands as the path segments of the wasm API.
+impl Decode for Segment {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        match reader.read::<u8>()? {
+            1 => Ok(Segment::MoveTo(reader.read()?)),
+            2 => Ok(Segment::LineTo(reader.read()?)),
+            3 => Ok(Segment::CurveTo((
+                reader.read()?,
+                reader.read()?,
+                reader.read()?,
+            ))),
+            4 => Ok(Segment::Close),
+            value => Err(format!("Invalid path command: {}", value)),
+        }
+    }
+}
+
+impl Decode for Path {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        Ok(Path::new(reader.read()?))
+    }
+}
+
+impl Decode for LayoutData {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        Ok(LayoutData {
+            align_items: reader.read()?,
+            align_content: reader.read()?,
+            justify_items: reader.read()?,
+            justify_content: reader.read()?,
+            padding_top: reader.read()?,
+            padding_right: reader.read()?,
+            padding_bottom: reader.read()?,
+            padding_left: reader.read()?,
+            row_gap: reader.read()?,
+            column_gap: reader.read()?,
+        })
+    }
+}
+
+impl Decode for GridTrack {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        Ok(GridTrack {
+            track_type: reader.read()?,
+            value: reader.read()?,
+        })
+    }
+}
+
+impl Decode for GridCell {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        Ok(GridCell {
+            row: reader.read()?,
+            row_span: reader.read()?,
+            column: reader.read()?,
+            column_span: reader.read()?,
+            align_self: reader.read()?,
+            justify_self: reader.read()?,
+            shape: reader.read()?,
+        })
+    }
+}
+
+impl Decode for Layout {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        match reader.read::<u8>()? {
+            0 => Ok(Layout::FlexLayout(
+                reader.read()?,
+                FlexData {
+                    direction: reader.read()?,
+                    wrap_type: reader.read()?,
+                },
+            )),
+            1 => Ok(Layout::GridLayout(
+                reader.read()?,
+                GridData {
+                    direction: reader.read()?,
+                    rows: reader.read()?,
+                    columns: reader.read()?,
+                    cells: reader.read()?,
+                },
+            )),
+            value => Err(format!("Invalid layout type: {}", value)),
+        }
+    }
+}
+
+impl Decode for LayoutItem {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        Ok(LayoutItem {
+            margin_top: reader.read()?,
+            margin_right: reader.read()?,
+            margin_bottom: reader.read()?,
+            margin_left: reader.read()?,
+            h_sizing: reader.read()?,
+            v_sizing: reader.read()?,
+            max_h: reader.read()?,
+            min_h: reader.read()?,
+            max_w: reader.read()?,
+            min_w: reader.read()?,
+            is_absolute: reader.read()?,
+            z_index: reader.read()?,
+            align_self: reader.read()?,
+        })
+    }
+}
+
+impl Decode for FontFamily {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        let id = reader.read()?;
+        let weight = reader.read()?;
+        let style = reader.read()?;
+        Ok(FontFamily::new(id, weight, style))
+    }
+}
+
+impl Decode for TextSpan {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        Ok(TextSpan {
+            text: reader.read()?,
+            font_family: reader.read()?,
+            font_size: reader.read()?,
+            line_height: reader.read()?,
+            letter_spacing: reader.read()?,
+            font_weight: reader.read()?,
+            font_variant_id: reader.read()?,
+            text_decoration: reader.read()?,
+            text_transform: reader.read()?,
+            text_direction: reader.read()?,
+            fills: reader.read()?,
+        })
+    }
+}
+
+impl Decode for Paragraph {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        Ok(Paragraph::new(
+            reader.read()?,
+            reader.read()?,
+            reader.read()?,
+            reader.read()?,
+            reader.read()?,
+            reader.read()?,
+            reader.read()?,
+        ))
+    }
+}
+
+impl Decode for TextContent {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        let bounds = reader.read()?;
+        let grow_type = reader.read()?;
+        let paragraphs: Vec<Paragraph> = reader.read()?;
+
+        let mut content = TextContent::new(bounds, grow_type);
+        for paragraph in paragraphs {
+            content.add_paragraph(paragraph);
+        }
+        Ok(content)
+    }
+}
+
+// Same shape types as the wasm API.
+impl Decode for Type {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        match reader.read::<u8>()? {
+            0 => Ok(Type::Frame(Frame {
+                corners: reader.read::<Option<Corners>>()?,
+                layout: reader.read()?,
+            })),
+            1 => Ok(Type::Group(Group {
+                masked: reader.read()?,
+            })),
+            2 => Ok(Type::Bool(Bool {
+                bool_type: reader.read()?,
+                path: reader.read()?,
+            })),
+            3 => Ok(Type::Rect(RectShape {
+                corners: reader.read()?,
+            })),
+            4 => Ok(Type::Path(reader.read()?)),
+            5 => Ok(Type::Text(reader.read()?)),
+            6 => Ok(Type::Circle),
+            7 => Ok(Type::SVGRaw(SVGRaw::from_content(reader.read()?))),
+            value => Err(format!("Invalid shape type: {}", value)),
+        }
+    }
+}
+
+impl Decode for Shape {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        let mut shape = Shape::new(reader.read()?);
+        shape.parent_id = reader.read()?;
+        shape.shape_type = reader.read()?;
+        shape.children = reader.read()?;
+        shape.selrect = reader.read()?;
+        shape.transform = reader.read()?;
+        shape.rotation = reader.read()?;
+        shape.constraint_h = reader.read()?;
+        shape.constraint_v = reader.read()?;
+        shape.clip_content = reader.read()?;
+        shape.fills = reader.read()?;
+        shape.strokes = reader.read()?;
+        shape.blend_mode = BlendMode(reader.read()?);
+        shape.vertical_align = reader.read()?;
+        shape.blur = reader.read()?;
+        shape.noise = reader.read()?;
+        shape.opacity = reader.read()?;
+        shape.hidden = reader.read()?;
+        shape.svg_attrs = reader.read()?;
+        shape.shadows = reader.read()?;
+        shape.layout_item = reader.read()?;
+        shape.svg_transform = reader.read()?;
+        shape.ignore_constraints = reader.read()?;
+        Ok(shape)
+    }
+}
+
+impl Decode for StructureEntry {
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        Ok(StructureEntry::new(
+            reader.read()?,
+            reader.read()?,
+            reader.read()?,
+            reader.read()?,
+            reader.read()?,
+        ))
+    }
+}
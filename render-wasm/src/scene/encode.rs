@@ -0,0 +1,601 @@
+use crate::math::{Matrix, Point, Rect};
+use crate::shapes::{
+    Blur, BlurProgression, Color, Fill, FillLayer, FontFamily, Gradient, GridCell, GridTrack,
+    ImageAdjustments, ImageFill, ImageScaleMode, Layout, LayoutData, LayoutItem, MeshGradient,
+    MeshPatch, Noise, Paragraph, Path, Segment, Shadow, Shape, SolidColor, Stroke, StrokeDash,
+    StrokeSides, StrokeWidthProfile, StructureEntry, SvgAttrs, TextContent, TextSpan, Type,
+};
+use crate::uuid::Uuid;
+
+/// Writes the values of a scene file, in the layout `Reader` expects.
+#[derive(Default)]
+pub struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn write<T: Encode + ?Sized>(&mut self, value: &T) {
+        value.encode(self);
+    }
+
+    /// Writes a length-prefixed blob, like the font and image data.
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.write(&(bytes.len() as u32));
+        self.put(bytes);
+    }
+
+    fn put(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+pub trait Encode {
+    fn encode(&self, writer: &mut Writer);
+}
+
+impl Encode for u8 {
+    fn encode(&self, writer: &mut Writer) {
+        writer.put(&[*self]);
+    }
+}
+
+impl Encode for bool {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&(*self as u8));
+    }
+}
+
+impl Encode for u32 {
+    fn encode(&self, writer: &mut Writer) {
+        writer.put(&self.to_le_bytes());
+    }
+}
+
+impl Encode for i32 {
+    fn encode(&self, writer: &mut Writer) {
+        writer.put(&self.to_le_bytes());
+    }
+}
+
+impl Encode for f32 {
+    fn encode(&self, writer: &mut Writer) {
+        writer.put(&self.to_le_bytes());
+    }
+}
+
+impl Encode for Uuid {
+    fn encode(&self, writer: &mut Writer) {
+        writer.put(&<[u8; 16]>::from(*self));
+    }
+}
+
+impl Encode for str {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write_bytes(self.as_bytes());
+    }
+}
+
+impl Encode for String {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(self.as_str());
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.is_some());
+        if let Some(value) = self {
+            writer.write(value);
+        }
+    }
+}
+
+impl<T: Encode> Encode for [T] {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&(self.len() as u32));
+        for item in self {
+            writer.write(item);
+        }
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(self.as_slice());
+    }
+}
+
+impl<A: Encode, B: Encode> Encode for (A, B) {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.0);
+        writer.write(&self.1);
+    }
+}
+
+impl Encode for Point {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&(self.x, self.y));
+    }
+}
+
+impl Encode for Rect {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&[self.left, self.top, self.right, self.bottom]);
+    }
+}
+
+// Same order as `set_shape_transform`: a b c d e f.
+impl Encode for Matrix {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&[
+            self.scale_x(),
+            self.skew_y(),
+            self.skew_x(),
+            self.scale_y(),
+            self.translate_x(),
+            self.translate_y(),
+        ]);
+    }
+}
+
+impl<T: Encode, const N: usize> Encode for [T; N] {
+    fn encode(&self, writer: &mut Writer) {
+        for item in self {
+            writer.write(item);
+        }
+    }
+}
+
+impl Encode for Color {
+    fn encode(&self, writer: &mut Writer) {
+        let argb = u32::from_be_bytes([self.a(), self.r(), self.g(), self.b()]);
+        writer.write(&argb);
+    }
+}
+
+impl Encode for Gradient {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.start());
+        writer.write(&self.end());
+        writer.write(&self.opacity());
+        writer.write(&self.width());
+        writer.write(&self.stops().collect::<Vec<_>>());
+        writer.write(&self.interpolation());
+        writer.write(&self.hue_interpolation());
+        writer.write(&self.dither());
+    }
+}
+
+impl Encode for ImageFill {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.id());
+        writer.write(&self.opacity());
+        writer.write(&self.width());
+        writer.write(&self.height());
+        writer.write(&self.scale_mode());
+        writer.write(self.adjustments());
+    }
+}
+
+impl Encode for ImageAdjustments {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.exposure);
+        writer.write(&self.contrast);
+        writer.write(&self.saturation);
+        writer.write(&self.temperature);
+        writer.write(&self.tint);
+        writer.write(&self.highlights);
+        writer.write(&self.shadows);
+    }
+}
+
+// Same scale modes as the wasm API.
+impl Encode for ImageScaleMode {
+    fn encode(&self, writer: &mut Writer) {
+        match self {
+            ImageScaleMode::Stretch => writer.write(&0u8),
+            ImageScaleMode::Fill => writer.write(&1u8),
+            ImageScaleMode::Fit => writer.write(&2u8),
+            ImageScaleMode::Crop(transform) => {
+                writer.write(&3u8);
+                writer.write(&transform.offset);
+                writer.write(&transform.scale);
+                writer.write(&transform.rotation);
+            }
+            ImageScaleMode::Tile(scale) => {
+                writer.write(&4u8);
+                writer.write(scale);
+            }
+        }
+    }
+}
+
+impl Encode for MeshPatch {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.cubics);
+        writer.write(&self.colors);
+    }
+}
+
+impl Encode for MeshGradient {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.opacity());
+        writer.write(self.patches());
+    }
+}
+
+impl Encode for Fill {
+    fn encode(&self, writer: &mut Writer) {
+        match self {
+            Fill::Solid(SolidColor(color)) => {
+                writer.write(&0u8);
+                writer.write(color);
+            }
+            Fill::LinearGradient(gradient) => {
+                writer.write(&1u8);
+                writer.write(gradient);
+            }
+            Fill::RadialGradient(gradient) => {
+                writer.write(&2u8);
+                writer.write(gradient);
+            }
+            Fill::Image(image_fill) => {
+                writer.write(&3u8);
+                writer.write(image_fill);
+            }
+            Fill::AngularGradient(gradient) => {
+                writer.write(&4u8);
+                writer.write(gradient);
+            }
+            Fill::MeshGradient(mesh) => {
+                writer.write(&5u8);
+                writer.write(mesh);
+            }
+        }
+    }
+}
+
+impl Encode for FillLayer {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.fill);
+        writer.write(&self.blend_mode.0);
+        writer.write(&self.opacity);
+    }
+}
+
+impl Encode for Stroke {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.fill);
+        writer.write(&self.blend_mode.0);
+        writer.write(&self.opacity);
+        writer.write(&self.width);
+        writer.write(&self.style);
+        writer.write(&self.dash);
+        writer.write(&self.width_profile);
+        writer.write(&self.sides);
+        writer.write(&self.join);
+        writer.write(&self.miter_limit);
+        writer.write(&self.cap_end);
+        writer.write(&self.cap_start);
+        writer.write(&self.cap_start_scale);
+        writer.write(&self.cap_end_scale);
+        writer.write(&self.kind);
+    }
+}
+
+impl Encode for StrokeDash {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.intervals);
+        writer.write(&self.offset);
+        writer.write(&self.adjust);
+        writer.write(&self.cap);
+    }
+}
+
+impl Encode for StrokeSides {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.top);
+        writer.write(&self.right);
+        writer.write(&self.bottom);
+        writer.write(&self.left);
+    }
+}
+
+// Same presets as the wasm API, with the points only for custom profiles.
+impl Encode for StrokeWidthProfile {
+    fn encode(&self, writer: &mut Writer) {
+        match self {
+            StrokeWidthProfile::Points(points) => {
+                writer.write(&0u8);
+                writer.write(points);
+            }
+            StrokeWidthProfile::TaperStart => writer.write(&1u8),
+            StrokeWidthProfile::TaperEnd => writer.write(&2u8),
+            StrokeWidthProfile::TaperBoth => writer.write(&3u8),
+        }
+    }
+}
+
+impl Encode for Shadow {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.color);
+        writer.write(&self.blur);
+        writer.write(&self.spread);
+        writer.write(&self.offset);
+        writer.write(&self.style());
+        writer.write(&self.hidden());
+        writer.write(&self.blend_mode.0);
+        writer.write(&self.technique);
+        writer.write(&self.source);
+        writer.write(&self.show_behind);
+    }
+}
+
+impl Encode for Blur {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.blur_type);
+        writer.write(&self.hidden);
+        writer.write(&self.value);
+        writer.write(&self.saturation);
+        writer.write(&self.progression);
+    }
+}
+
+impl Encode for Noise {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.noise_type);
+        writer.write(&self.hidden);
+        writer.write(&self.size);
+        writer.write(&self.density);
+        writer.write(&self.opacity);
+        writer.write(&self.color);
+        writer.write(&self.secondary_color);
+    }
+}
+
+impl Encode for BlurProgression {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.progression_type);
+        writer.write(&self.start);
+        writer.write(&self.end);
+        writer.write(&self.start_value);
+    }
+}
+
+impl Encode for SvgAttrs {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.fill_rule);
+        writer.write(&self.stroke_linecap);
+        writer.write(&self.stroke_linejoin);
+        writer.write(&self.fill_none);
+    }
+}
+
+impl Encode for Segment {
+    fn encode(&self, writer: &mut Writer) {
+        match self {
+            Segment::MoveTo(point) => {
+                writer.write(&1u8);
+                writer.write(point);
+            }
+            Segment::LineTo(point) => {
+                writer.write(&2u8);
+                writer.write(point);
+            }
+            Segment::CurveTo((c1, c2, point)) => {
+                writer.write(&3u8);
+                writer.write(c1);
+                writer.write(c2);
+                writer.write(point);
+            }
+            Segment::Close => writer.write(&4u8),
+        }
+    }
+}
+
+impl Encode for Path {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(self.segments());
+    }
+}
+
+impl Encode for LayoutData {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.align_items);
+        writer.write(&self.align_content);
+        writer.write(&self.justify_items);
+        writer.write(&self.justify_content);
+        writer.write(&self.padding_top);
+        writer.write(&self.padding_right);
+        writer.write(&self.padding_bottom);
+        writer.write(&self.padding_left);
+        writer.write(&self.row_gap);
+        writer.write(&self.column_gap);
+    }
+}
+
+impl Encode for GridTrack {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.track_type);
+        writer.write(&self.value);
+    }
+}
+
+impl Encode for GridCell {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.row);
+        writer.write(&self.row_span);
+        writer.write(&self.column);
+        writer.write(&self.column_span);
+        writer.write(&self.align_self);
+        writer.write(&self.justify_self);
+        writer.write(&self.shape);
+    }
+}
+
+impl Encode for Layout {
+    fn encode(&self, writer: &mut Writer) {
+        match self {
+            Layout::FlexLayout(layout_data, flex_data) => {
+                writer.write(&0u8);
+                writer.write(layout_data);
+                writer.write(&flex_data.direction);
+                writer.write(&flex_data.wrap_type);
+            }
+            Layout::GridLayout(layout_data, grid_data) => {
+                writer.write(&1u8);
+                writer.write(layout_data);
+                writer.write(&grid_data.direction);
+                writer.write(&grid_data.rows);
+                writer.write(&grid_data.columns);
+                writer.write(&grid_data.cells);
+            }
+        }
+    }
+}
+
+impl Encode for LayoutItem {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.margin_top);
+        writer.write(&self.margin_right);
+        writer.write(&self.margin_bottom);
+        writer.write(&self.margin_left);
+        writer.write(&self.h_sizing);
+        writer.write(&self.v_sizing);
+        writer.write(&self.max_h);
+        writer.write(&self.min_h);
+        writer.write(&self.max_w);
+        writer.write(&self.min_w);
+        writer.write(&self.is_absolute);
+        writer.write(&self.z_index);
+        writer.write(&self.align_self);
+    }
+}
+
+impl Encode for FontFamily {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.id());
+        writer.write(&self.weight());
+        writer.write(&self.style());
+    }
+}
+
+impl Encode for TextSpan {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.text);
+        writer.write(&self.font_family);
+        writer.write(&self.font_size);
+        writer.write(&self.line_height);
+        writer.write(&self.letter_spacing);
+        writer.write(&self.font_weight);
+        writer.write(&self.font_variant_id);
+        writer.write(&self.text_decoration);
+        writer.write(&self.text_transform);
+        writer.write(&self.text_direction);
+        writer.write(&self.fills);
+    }
+}
+
+impl Encode for Paragraph {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.text_align());
+        writer.write(&self.text_direction());
+        writer.write(&self.text_decoration());
+        writer.write(&self.text_transform());
+        writer.write(&self.line_height());
+        writer.write(&self.letter_spacing());
+        writer.write(self.children());
+    }
+}
+
+impl Encode for TextContent {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.bounds());
+        writer.write(&self.grow_type());
+        writer.write(self.paragraphs());
+    }
+}
+
+impl Encode for Type {
+    fn encode(&self, writer: &mut Writer) {
+        match self {
+            Type::Frame(frame) => {
+                writer.write(&0u8);
+                writer.write(&frame.corners);
+                writer.write(&frame.layout);
+            }
+            Type::Group(group) => {
+                writer.write(&1u8);
+                writer.write(&group.masked);
+            }
+            Type::Bool(bool_shape) => {
+                writer.write(&2u8);
+                writer.write(&bool_shape.bool_type);
+                writer.write(&bool_shape.path);
+            }
+            Type::Rect(rect) => {
+                writer.write(&3u8);
+                writer.write(&rect.corners);
+            }
+            Type::Path(path) => {
+                writer.write(&4u8);
+                writer.write(path);
+            }
+            Type::Text(text_content) => {
+                writer.write(&5u8);
+                writer.write(text_content);
+            }
+            Type::Circle => writer.write(&6u8),
+            Type::SVGRaw(svg_raw) => {
+                writer.write(&7u8);
+                writer.write(&svg_raw.content);
+            }
+        }
+    }
+}
+
+impl Encode for Shape {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.id);
+        writer.write(&self.parent_id);
+        writer.write(&self.shape_type);
+        writer.write(&self.children);
+        writer.write(&self.selrect);
+        writer.write(&self.transform);
+        writer.write(&self.rotation);
+        writer.write(&self.constraint_h);
+        writer.write(&self.constraint_v);
+        writer.write(&self.clip_content);
+        writer.write(&self.fills);
+        writer.write(&self.strokes);
+        writer.write(&self.blend_mode.0);
+        writer.write(&self.vertical_align);
+        writer.write(&self.blur);
+        writer.write(&self.noise);
+        writer.write(&self.opacity);
+        writer.write(&self.hidden);
+        writer.write(&self.svg_attrs);
+        writer.write(&self.shadows);
+        writer.write(&self.layout_item);
+        writer.write(&self.svg_transform);
+        writer.write(&self.ignore_constraints);
+    }
+}
+
+impl Encode for StructureEntry {
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.entry_type);
+        writer.write(&self.index);
+        writer.write(&self.parent);
+        writer.write(&self.id);
+        writer.write(&self.value);
+    }
+}
@@ -0,0 +1,305 @@
+use skia_safe as skia;
+
+use super::decode::{Decode, Reader};
+use super::encode::{Encode, Writer};
+use crate::shapes::{
+    AlignContent, AlignItems, AlignSelf, BlurProgressionType, BlurType, BoolType, ConstraintH,
+    ConstraintV, FillRule, FlexDirection, FontStyle, GlowSource, GlowTechnique,
+    GradientInterpolation, GridDirection, GridTrackType, GrowType, HueInterpolation,
+    JustifyContent, JustifyItems, JustifySelf, NoiseType, ShadowStyle, Sizing, StrokeCap,
+    StrokeKind, StrokeLineCap, StrokeLineJoin, StrokeStyle, StructureEntryType, TextAlign,
+    TextDecoration, TextDirection, TextTransform, VerticalAlign, WrapType,
+};
+
+/// Enums are stored as a `u8`. When the value is also sent through the wasm API,
+/// it's the same one (see `docs/serialization.md`).
+///
+/// The values are compared instead of matched because some of the types, like
+/// `TextDecoration`, are bitflags. Values the wasm API can't set (for example
+/// most of the skia blend modes) are written as the first one of the table.
+macro_rules! codec_enum {
+    ($type:ty { $first:literal => $first_variant:path $(, $value:literal => $variant:path)* $(,)? }) => {
+        impl Decode for $type {
+            fn decode(reader: &mut Reader) -> Result<Self, String> {
+                match reader.read::<u8>()? {
+                    $first => Ok($first_variant),
+                    $($value => Ok($variant),)*
+                    value => Err(format!("Invalid {} value: {}", stringify!($type), value)),
+                }
+            }
+        }
+
+        impl Encode for $type {
+            fn encode(&self, writer: &mut Writer) {
+                let value: u8 = match self {
+                    $(variant if *variant == $variant => $value,)*
+                    _ => $first,
+                };
+                writer.write(&value);
+            }
+        }
+    };
+}
+
+codec_enum!(ConstraintH {
+    0 => ConstraintH::Left,
+    1 => ConstraintH::Right,
+    2 => ConstraintH::LeftRight,
+    3 => ConstraintH::Center,
+    4 => ConstraintH::Scale,
+});
+
+codec_enum!(ConstraintV {
+    0 => ConstraintV::Top,
+    1 => ConstraintV::Bottom,
+    2 => ConstraintV::TopBottom,
+    3 => ConstraintV::Center,
+    4 => ConstraintV::Scale,
+});
+
+codec_enum!(VerticalAlign {
+    0 => VerticalAlign::Top,
+    1 => VerticalAlign::Center,
+    2 => VerticalAlign::Bottom,
+});
+
+codec_enum!(skia::BlendMode {
+    3 => skia::BlendMode::SrcOver,
+    14 => skia::BlendMode::Screen,
+    15 => skia::BlendMode::Overlay,
+    16 => skia::BlendMode::Darken,
+    17 => skia::BlendMode::Lighten,
+    18 => skia::BlendMode::ColorDodge,
+    19 => skia::BlendMode::ColorBurn,
+    20 => skia::BlendMode::HardLight,
+    21 => skia::BlendMode::SoftLight,
+    22 => skia::BlendMode::Difference,
+    23 => skia::BlendMode::Exclusion,
+    24 => skia::BlendMode::Multiply,
+    25 => skia::BlendMode::Hue,
+    26 => skia::BlendMode::Saturation,
+    27 => skia::BlendMode::Color,
+    28 => skia::BlendMode::Luminosity,
+});
+
+codec_enum!(BoolType {
+    0 => BoolType::Union,
+    1 => BoolType::Difference,
+    2 => BoolType::Intersection,
+    3 => BoolType::Exclusion,
+});
+
+codec_enum!(BlurType {
+    1 => BlurType::LayerBlur,
+    2 => BlurType::BackgroundBlur,
+});
+
+codec_enum!(BlurProgressionType {
+    0 => BlurProgressionType::Linear,
+    1 => BlurProgressionType::Radial,
+});
+
+codec_enum!(NoiseType {
+    0 => NoiseType::Mono,
+    1 => NoiseType::Duotone,
+    2 => NoiseType::Multicolor,
+});
+
+codec_enum!(ShadowStyle {
+    0 => ShadowStyle::Drop,
+    1 => ShadowStyle::Inner,
+    2 => ShadowStyle::OuterGlow,
+    3 => ShadowStyle::InnerGlow,
+});
+
+codec_enum!(GlowTechnique {
+    0 => GlowTechnique::Soft,
+    1 => GlowTechnique::Precise,
+});
+
+codec_enum!(GlowSource {
+    0 => GlowSource::Edge,
+    1 => GlowSource::Center,
+});
+
+codec_enum!(StrokeStyle {
+    0 => StrokeStyle::Solid,
+    1 => StrokeStyle::Dotted,
+    2 => StrokeStyle::Dashed,
+    3 => StrokeStyle::Mixed,
+    4 => StrokeStyle::Custom,
+});
+
+codec_enum!(StrokeCap {
+    1 => StrokeCap::LineArrow,
+    2 => StrokeCap::TriangleArrow,
+    3 => StrokeCap::SquareMarker,
+    4 => StrokeCap::CircleMarker,
+    5 => StrokeCap::DiamondMarker,
+    6 => StrokeCap::Round,
+    7 => StrokeCap::Square,
+    8 => StrokeCap::Bar,
+    9 => StrokeCap::ReversedArrow,
+    10 => StrokeCap::HalfArrow,
+    11 => StrokeCap::CircleOutline,
+    12 => StrokeCap::DiamondOutline,
+    13 => StrokeCap::TriangleOutline,
+});
+
+codec_enum!(StrokeKind {
+    0 => StrokeKind::Inner,
+    1 => StrokeKind::Outer,
+    2 => StrokeKind::Center,
+});
+
+codec_enum!(FillRule {
+    0 => FillRule::Nonzero,
+    1 => FillRule::Evenodd,
+});
+
+codec_enum!(StrokeLineCap {
+    0 => StrokeLineCap::Butt,
+    1 => StrokeLineCap::Round,
+    2 => StrokeLineCap::Square,
+});
+
+codec_enum!(StrokeLineJoin {
+    0 => StrokeLineJoin::Miter,
+    1 => StrokeLineJoin::Round,
+    2 => StrokeLineJoin::Bevel,
+});
+
+codec_enum!(FlexDirection {
+    0 => FlexDirection::Row,
+    1 => FlexDirection::RowReverse,
+    2 => FlexDirection::Column,
+    3 => FlexDirection::ColumnReverse,
+});
+
+codec_enum!(GridDirection {
+    0 => GridDirection::Row,
+    1 => GridDirection::Column,
+});
+
+codec_enum!(AlignItems {
+    0 => AlignItems::Start,
+    1 => AlignItems::End,
+    2 => AlignItems::Center,
+    3 => AlignItems::Stretch,
+});
+
+codec_enum!(AlignContent {
+    0 => AlignContent::Start,
+    1 => AlignContent::End,
+    2 => AlignContent::Center,
+    3 => AlignContent::SpaceBetween,
+    4 => AlignContent::SpaceAround,
+    5 => AlignContent::SpaceEvenly,
+    6 => AlignContent::Stretch,
+});
+
+codec_enum!(JustifyItems {
+    0 => JustifyItems::Start,
+    1 => JustifyItems::End,
+    2 => JustifyItems::Center,
+    3 => JustifyItems::Stretch,
+});
+
+codec_enum!(JustifyContent {
+    0 => JustifyContent::Start,
+    1 => JustifyContent::End,
+    2 => JustifyContent::Center,
+    3 => JustifyContent::SpaceBetween,
+    4 => JustifyContent::SpaceAround,
+    5 => JustifyContent::SpaceEvenly,
+    6 => JustifyContent::Stretch,
+});
+
+codec_enum!(AlignSelf {
+    1 => AlignSelf::Auto,
+    2 => AlignSelf::Start,
+    3 => AlignSelf::End,
+    4 => AlignSelf::Center,
+    5 => AlignSelf::Stretch,
+});
+
+codec_enum!(JustifySelf {
+    1 => JustifySelf::Auto,
+    2 => JustifySelf::Start,
+    3 => JustifySelf::End,
+    4 => JustifySelf::Center,
+    5 => JustifySelf::Stretch,
+});
+
+codec_enum!(WrapType {
+    0 => WrapType::Wrap,
+    1 => WrapType::NoWrap,
+});
+
+codec_enum!(GridTrackType {
+    0 => GridTrackType::Percent,
+    1 => GridTrackType::Flex,
+    2 => GridTrackType::Auto,
+    3 => GridTrackType::Fixed,
+});
+
+codec_enum!(Sizing {
+    0 => Sizing::Fill,
+    1 => Sizing::Fix,
+    2 => Sizing::Auto,
+});
+
+codec_enum!(FontStyle {
+    0 => FontStyle::Normal,
+    1 => FontStyle::Italic,
+});
+
+codec_enum!(GrowType {
+    0 => GrowType::Fixed,
+    1 => GrowType::AutoWidth,
+    2 => GrowType::AutoHeight,
+});
+
+codec_enum!(TextAlign {
+    0 => TextAlign::Left,
+    1 => TextAlign::Center,
+    2 => TextAlign::Right,
+    3 => TextAlign::Justify,
+});
+
+codec_enum!(TextDirection {
+    0 => TextDirection::LTR,
+    1 => TextDirection::RTL,
+});
+
+codec_enum!(TextDecoration {
+    1 => TextDecoration::UNDERLINE,
+    2 => TextDecoration::LINE_THROUGH,
+    3 => TextDecoration::OVERLINE,
+});
+
+codec_enum!(TextTransform {
+    1 => TextTransform::Uppercase,
+    2 => TextTransform::Lowercase,
+    3 => TextTransform::Capitalize,
+});
+
+codec_enum!(StructureEntryType {
+    1 => StructureEntryType::RemoveChild,
+    2 => StructureEntryType::AddChild,
+    3 => StructureEntryType::ScaleContent,
+});
+
+codec_enum!(GradientInterpolation {
+    0 => GradientInterpolation::Srgb,
+    1 => GradientInterpolation::LinearSrgb,
+    2 => GradientInterpolation::Oklab,
+    3 => GradientInterpolation::Oklch,
+});
+
+codec_enum!(HueInterpolation {
+    0 => HueInterpolation::Shorter,
+    1 => HueInterpolation::Longer,
+    2 => HueInterpolation::Increasing,
+    3 => HueInterpolation::Decreasing,
+});
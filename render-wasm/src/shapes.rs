@@ -16,8 +16,10 @@ mod fills;
 mod fonts;
 mod frames;
 mod groups;
+mod image_adjustments;
 mod layouts;
 pub mod modifiers;
+mod noise;
 mod paths;
 mod rects;
 mod shadows;
@@ -37,8 +39,10 @@ pub use fills::*;
 pub use fonts::*;
 pub use frames::*;
 pub use groups::*;
+pub use image_adjustments::*;
 pub use layouts::*;
 pub use modifiers::*;
+pub use noise::*;
 pub use paths::*;
 pub use rects::*;
 pub use shadows::*;
@@ -181,11 +185,12 @@ pub struct Shape {
     pub constraint_h: Option<ConstraintH>,
     pub constraint_v: Option<ConstraintV>,
     pub clip_content: bool,
-    pub fills: Vec<Fill>,
+    pub fills: Vec<FillLayer>,
     pub strokes: Vec<Stroke>,
     pub blend_mode: BlendMode,
     pub vertical_align: VerticalAlign,
     pub blur: Option<Blur>,
+    pub noise: Option<Noise>,
     pub opacity: f32,
     pub hidden: bool,
     pub svg: Option<skia::svg::Dom>,
@@ -276,6 +281,7 @@ impl Shape {
             opacity: 1.,
             hidden: false,
             blur: None,
+            noise: None,
             svg: None,
             svg_attrs: None,
             shadows: Vec::with_capacity(1),
@@ -298,6 +304,10 @@ impl Shape {
             blur.scale_content(value);
         }
 
+        if let Some(noise) = self.noise.as_mut() {
+            noise.scale_content(value);
+        }
+
         self.layout_item
             .iter_mut()
             .for_each(|i| i.scale_content(value));
@@ -607,6 +617,37 @@ impl Shape {
         self.blur = blur;
     }
 
+    pub fn set_blur_saturation(&mut self, saturation: f32) -> Result<(), String> {
+        let blur = self.blur.as_mut().ok_or("Shape has no blur")?;
+        blur.saturation = saturation;
+        Ok(())
+    }
+
+    pub fn set_blur_progression(&mut self, progression: BlurProgression) -> Result<(), String> {
+        self.invalidate_extrect();
+        let blur = self.blur.as_mut().ok_or("Shape has no blur")?;
+        blur.progression = Some(progression);
+        Ok(())
+    }
+
+    pub fn set_noise(&mut self, noise: Option<Noise>) {
+        self.noise = noise;
+    }
+
+    pub fn set_noise_colors(&mut self, color: Color, secondary_color: Color) -> Result<(), String> {
+        let noise = self.noise.as_mut().ok_or("Shape has no noise")?;
+        noise.color = color;
+        noise.secondary_color = secondary_color;
+        Ok(())
+    }
+
+    /// Paint of the noise drawn over the fills, if the shape has a visible one.
+    pub fn noise_paint(&self) -> Option<skia::Paint> {
+        self.noise
+            .filter(|noise| !noise.hidden && noise.opacity > 0.)
+            .and_then(|noise| noise.paint())
+    }
+
     pub fn add_child(&mut self, id: Uuid) {
         self.children.push(id);
     }
@@ -620,16 +661,16 @@ impl Shape {
         (added, removed)
     }
 
-    pub fn fills(&self) -> std::slice::Iter<'_, Fill> {
+    pub fn fills(&self) -> std::slice::Iter<'_, FillLayer> {
         self.fills.iter()
     }
 
-    pub fn set_fills(&mut self, fills: Vec<Fill>) {
+    pub fn set_fills(&mut self, fills: Vec<FillLayer>) {
         self.fills = fills;
     }
 
-    pub fn add_fill(&mut self, f: Fill) {
-        self.fills.push(f);
+    pub fn add_fill(&mut self, f: impl Into<FillLayer>) {
+        self.fills.push(f.into());
     }
 
     pub fn clear_fills(&mut self) {
@@ -653,9 +694,37 @@ impl Shape {
         self.strokes.push(s)
     }
 
-    pub fn set_stroke_fill(&mut self, f: Fill) -> Result<(), String> {
+    pub fn set_stroke_fill(&mut self, f: impl Into<FillLayer>) -> Result<(), String> {
         let stroke = self.strokes.last_mut().ok_or("Shape has no strokes")?;
-        stroke.fill = f;
+        let layer = f.into();
+        stroke.fill = layer.fill;
+        stroke.blend_mode = layer.blend_mode;
+        stroke.opacity = layer.opacity;
+        Ok(())
+    }
+
+    pub fn set_stroke_width_profile(
+        &mut self,
+        width_profile: StrokeWidthProfile,
+    ) -> Result<(), String> {
+        self.invalidate_extrect();
+        let stroke = self.strokes.last_mut().ok_or("Shape has no strokes")?;
+        stroke.width_profile = Some(width_profile);
+        Ok(())
+    }
+
+    pub fn set_stroke_cap_scales(&mut self, start: f32, end: f32) -> Result<(), String> {
+        self.invalidate_extrect();
+        let stroke = self.strokes.last_mut().ok_or("Shape has no strokes")?;
+        stroke.cap_start_scale = start;
+        stroke.cap_end_scale = end;
+        Ok(())
+    }
+
+    pub fn set_stroke_sides(&mut self, sides: StrokeSides) -> Result<(), String> {
+        self.invalidate_extrect();
+        let stroke = self.strokes.last_mut().ok_or("Shape has no strokes")?;
+        stroke.sides = Some(sides);
         Ok(())
     }
 
@@ -844,12 +913,16 @@ impl Shape {
         rect
     }
 
-    fn apply_stroke_bounds(&self, bounds: Bounds, stroke_width: f32) -> Bounds {
+    fn apply_stroke_bounds(&self, bounds: Bounds, stroke_sides: StrokeSides) -> Bounds {
         let mut result = bounds.to_rect();
-        if stroke_width > 0.0 {
-            let mut expanded_rect = bounds.to_rect();
-            expanded_rect.inset((-stroke_width, -stroke_width));
-            result.join(expanded_rect);
+        if stroke_sides.max() > 0.0 {
+            let rect = bounds.to_rect();
+            result.join(math::Rect::new(
+                rect.left - stroke_sides.left,
+                rect.top - stroke_sides.top,
+                rect.right + stroke_sides.right,
+                rect.bottom + stroke_sides.bottom,
+            ));
         }
 
         let cap_margin = self.cap_bounds_margin();
@@ -885,10 +958,14 @@ impl Shape {
 
     fn apply_blur_bounds(&self, bounds: Bounds) -> Bounds {
         let mut rect = bounds.to_rect();
-        let image_filter = self.image_filter(1.);
-        if let Some(image_filter) = image_filter {
-            let blur_bounds = image_filter.compute_fast_bounds(rect);
-            rect.join(blur_bounds);
+        // Progressive blurs can't tell their bounds through their filter, so the
+        // bounds grow by the reach of their largest radius.
+        if let Some(blur) = self
+            .blur
+            .filter(|blur| !blur.hidden && blur.blur_type == BlurType::LayerBlur)
+        {
+            let reach = blur.reach();
+            rect.outset((reach, reach));
         }
         Bounds::from_rect(&rect)
     }
@@ -1028,7 +1105,8 @@ impl Shape {
             _ => shape.calculate_bounds(false),
         };
 
-        bounds = self.apply_stroke_bounds(bounds, max_stroke);
+        let stroke_sides = Stroke::max_bounds_sides(shape.strokes.iter(), shape.is_open());
+        bounds = self.apply_stroke_bounds(bounds, stroke_sides);
         bounds = self.apply_shadow_bounds(bounds);
         bounds = self.apply_blur_bounds(bounds);
         bounds = self.apply_children_bounds(bounds, shapes_pool, scale);
@@ -1188,12 +1266,8 @@ impl Shape {
         self.blur
             .filter(|blur| !blur.hidden)
             .and_then(|blur| match blur.blur_type {
-                BlurType::LayerBlur => skia::image_filters::blur(
-                    (blur.value * scale, blur.value * scale),
-                    None,
-                    None,
-                    None,
-                ),
+                BlurType::LayerBlur => blur.layer_filter(&self.selrect, scale),
+                BlurType::BackgroundBlur => None,
             })
     }
 
@@ -1205,6 +1279,7 @@ impl Shape {
                 BlurType::LayerBlur => {
                     skia::MaskFilter::blur(skia::BlurStyle::Normal, blur.value * scale, Some(true))
                 }
+                BlurType::BackgroundBlur => None,
             })
     }
 
@@ -1229,34 +1304,52 @@ impl Shape {
         self.shadows.clear();
     }
 
+    pub fn set_shadow_glow(
+        &mut self,
+        technique: GlowTechnique,
+        source: GlowSource,
+    ) -> Result<(), String> {
+        self.invalidate_extrect();
+        let shadow = self.shadows.last_mut().ok_or("Shape has no shadows")?;
+        shadow.technique = technique;
+        shadow.source = source;
+        Ok(())
+    }
+
+    pub fn set_shadow_blend_mode(&mut self, blend_mode: BlendMode) -> Result<(), String> {
+        let shadow = self.shadows.last_mut().ok_or("Shape has no shadows")?;
+        shadow.blend_mode = blend_mode;
+        Ok(())
+    }
+
+    pub fn set_shadow_show_behind(&mut self, show_behind: bool) -> Result<(), String> {
+        let shadow = self.shadows.last_mut().ok_or("Shape has no shadows")?;
+        shadow.show_behind = show_behind;
+        Ok(())
+    }
+
     #[allow(dead_code)]
     pub fn drop_shadows(&self) -> impl DoubleEndedIterator<Item = &Shadow> {
-        self.shadows
-            .iter()
-            .rev()
-            .filter(|shadow| shadow.style() == ShadowStyle::Drop)
+        self.shadows.iter().rev().filter(|shadow| shadow.is_drop())
     }
 
     pub fn drop_shadows_visible(&self) -> impl DoubleEndedIterator<Item = &Shadow> {
         self.shadows
             .iter()
             .rev()
-            .filter(|shadow| shadow.style() == ShadowStyle::Drop && !shadow.hidden())
+            .filter(|shadow| shadow.is_drop() && !shadow.hidden())
     }
 
     #[allow(dead_code)]
     pub fn inner_shadows(&self) -> impl DoubleEndedIterator<Item = &Shadow> {
-        self.shadows
-            .iter()
-            .rev()
-            .filter(|shadow| shadow.style() == ShadowStyle::Inner)
+        self.shadows.iter().rev().filter(|shadow| shadow.is_inner())
     }
 
     pub fn inner_shadows_visible(&self) -> impl DoubleEndedIterator<Item = &Shadow> {
         self.shadows
             .iter()
             .rev()
-            .filter(|shadow| shadow.style() == ShadowStyle::Inner && !shadow.hidden())
+            .filter(|shadow| shadow.is_inner() && !shadow.hidden())
     }
 
     pub fn shadows_visible(&self) -> impl DoubleEndedIterator<Item = &Shadow> {
@@ -1295,6 +1388,27 @@ impl Shape {
         }
     }
 
+    /// Path of the shape in its own coordinates, the ones its transform applies to.
+    pub fn geometry_path(&self) -> Option<skia::Path> {
+        let mut path = skia::Path::new();
+        match &self.shape_type {
+            Type::Rect(_) | Type::Frame(_) => match self.shape_type.corners() {
+                Some(corners) => {
+                    path.add_rrect(skia::RRect::new_rect_radii(self.selrect, &corners), None);
+                }
+                None => {
+                    path.add_rect(self.selrect, None);
+                }
+            },
+            Type::Circle => {
+                path.add_oval(self.selrect, None);
+            }
+            Type::Path(_) | Type::Bool(_) => return self.get_skia_path(),
+            _ => return None,
+        }
+        Some(path)
+    }
+
     pub fn get_skia_path(&self) -> Option<skia::Path> {
         if let Some(path) = self.shape_type.path() {
             let mut skia_path = path.to_skia_path();
@@ -1533,6 +1647,13 @@ impl Shape {
         }
     }
 
+    /// Returns the visible background blur of this shape, if any.
+    pub fn background_blur(&self) -> Option<Blur> {
+        self.blur.filter(|blur| {
+            !blur.hidden && blur.blur_type == BlurType::BackgroundBlur && blur.value > 0.0
+        })
+    }
+
     /// Checks if this shape has visual effects that might extend its bounds beyond selrect
     /// Shapes with these effects require expensive extrect calculation for accurate visibility checks
     pub fn has_effects_that_extend_bounds(&self) -> bool {
@@ -1556,12 +1677,7 @@ impl Shape {
 
         drop_shadows
             .into_iter()
-            .map(|shadow| {
-                let mut paint = skia_safe::Paint::default();
-                let filter = shadow.get_drop_shadow_filter();
-                paint.set_image_filter(filter);
-                paint
-            })
+            .map(|shadow| shadow.get_drop_shadow_paint())
             .collect()
     }
 
@@ -1574,6 +1690,7 @@ impl Shape {
                 let mut paint = skia_safe::Paint::default();
                 let filter = shadow.get_inner_shadow_filter();
                 paint.set_image_filter(filter);
+                paint.set_blend_mode(shadow.blend_mode.into());
                 paint
             })
             .collect()
@@ -1597,7 +1714,9 @@ mod tests {
         shape.add_fill(Fill::Solid(SolidColor(Color::TRANSPARENT)));
         assert_eq!(
             shape.fills.first(),
-            Some(&Fill::Solid(SolidColor(Color::TRANSPARENT)))
+            Some(&FillLayer::from(Fill::Solid(SolidColor(
+                Color::TRANSPARENT
+            ))))
         )
     }
 
@@ -1640,6 +1759,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_glows_are_drawn_as_drop_and_inner_shadows() {
+        let mut shape = any_shape();
+        for style in [ShadowStyle::OuterGlow, ShadowStyle::InnerGlow] {
+            shape.add_shadow(Shadow::new(Color::BLACK, 4., 0., (0., 0.), style, false));
+        }
+        assert!(shape
+            .set_shadow_glow(GlowTechnique::Precise, GlowSource::Center)
+            .is_ok());
+
+        assert_eq!(shape.drop_shadows_visible().count(), 1);
+        assert_eq!(shape.inner_shadows_visible().count(), 1);
+        assert_eq!(shape.shadows[1].technique, GlowTechnique::Precise);
+        assert!(any_shape()
+            .set_shadow_glow(GlowTechnique::Soft, GlowSource::Edge)
+            .is_err());
+    }
+
     #[test]
     fn test_apply_transform() {
         let mut shape = Shape::new(Uuid::new_v4());
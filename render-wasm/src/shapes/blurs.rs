@@ -1,6 +1,97 @@
+use skia_safe::{self as skia, color_filters, ImageFilter, Rect};
+
+/// Number of blur radii blended by a progressive blur.
+const PROGRESSIVE_BLUR_STEPS: usize = 6;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BlurType {
     LayerBlur,
+    BackgroundBlur,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlurProgressionType {
+    Linear,
+    Radial,
+}
+
+/// Makes the blur radius go from `start_value` at the start point to the blur
+/// value at the end point. The points are relative to the shape selrect, like
+/// the gradient ones. Radial progressions are centered on the start point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlurProgression {
+    pub progression_type: BlurProgressionType,
+    pub start: (f32, f32),
+    pub end: (f32, f32),
+    pub start_value: f32,
+}
+
+impl BlurProgression {
+    /// Uniform blurs approximating the progression up to `end_value`, with the
+    /// mask laying each of them over `rect`. Each mask is a gradient peaking at
+    /// its radius and fading out at the neighbouring ones, so the masks add up
+    /// to one everywhere.
+    pub fn levels(&self, rect: &Rect, scale: f32, end_value: f32) -> Vec<(f32, skia::Shader)> {
+        let positions: Vec<f32> = (0..PROGRESSIVE_BLUR_STEPS)
+            .map(|step| step as f32 / (PROGRESSIVE_BLUR_STEPS - 1) as f32)
+            .collect();
+
+        positions
+            .iter()
+            .enumerate()
+            .filter_map(|(step, t)| {
+                let colors: Vec<skia::Color> = (0..PROGRESSIVE_BLUR_STEPS)
+                    .map(|other| {
+                        if other == step {
+                            skia::Color::BLACK
+                        } else {
+                            skia::Color::TRANSPARENT
+                        }
+                    })
+                    .collect();
+                let shader = self.shader(rect, scale, &colors, &positions)?;
+                let sigma = (self.start_value + (end_value - self.start_value) * t) * scale;
+                Some((sigma, shader))
+            })
+            .collect()
+    }
+
+    fn shader(
+        &self,
+        rect: &Rect,
+        scale: f32,
+        colors: &[skia::Color],
+        positions: &[f32],
+    ) -> Option<skia::Shader> {
+        let point = |(x, y): (f32, f32)| {
+            skia::Point::new(
+                (rect.left + x * rect.width()) * scale,
+                (rect.top + y * rect.height()) * scale,
+            )
+        };
+        let start = point(self.start);
+        let end = point(self.end);
+
+        match self.progression_type {
+            BlurProgressionType::Linear => skia::Shader::linear_gradient(
+                (start, end),
+                colors,
+                positions,
+                skia::TileMode::Clamp,
+                None,
+                None,
+            ),
+            BlurProgressionType::Radial => skia::Shader::radial_gradient(
+                start,
+                skia::Point::distance(start, end),
+                colors,
+                positions,
+                skia::TileMode::Clamp,
+                None,
+                None,
+            ),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -8,6 +99,10 @@ pub struct Blur {
     pub hidden: bool,
     pub blur_type: BlurType,
     pub value: f32,
+    /// Saturation change of a background blur, from `-1` to `1`. `0` leaves
+    /// the blurred backdrop as it is.
+    pub saturation: f32,
+    pub progression: Option<BlurProgression>,
 }
 
 impl Blur {
@@ -16,10 +111,98 @@ impl Blur {
             blur_type,
             hidden,
             value,
+            saturation: 0.,
+            progression: None,
         }
     }
 
     pub fn scale_content(&mut self, value: f32) {
         self.value *= value;
+        if let Some(progression) = self.progression.as_mut() {
+            progression.start_value *= value;
+        }
+    }
+
+    /// Largest radius of the blur.
+    pub fn max_value(&self) -> f32 {
+        match self.progression {
+            Some(progression) => self.value.max(progression.start_value),
+            None => self.value,
+        }
+    }
+
+    /// How far the blur spreads the content, three times its largest radius.
+    pub fn reach(&self) -> f32 {
+        self.max_value() * 3.
+    }
+
+    /// Filter of a layer blur, with the radius scaled by `scale`. `rect` is the
+    /// selrect the progression is laid over.
+    pub fn layer_filter(&self, rect: &Rect, scale: f32) -> Option<ImageFilter> {
+        self.filter(rect, scale, None)
+    }
+
+    /// Filter applied to what's drawn below a shape with a background blur.
+    /// It blurs the backdrop and then changes its saturation.
+    pub fn backdrop_filter(&self, rect: &Rect) -> Option<ImageFilter> {
+        let blur = self.filter(rect, 1., Some(skia::TileMode::Clamp))?;
+        if self.saturation == 0. {
+            return Some(blur);
+        }
+
+        let mut matrix = skia::ColorMatrix::default();
+        matrix.set_saturation(1. + self.saturation);
+        skia::image_filters::color_filter(color_filters::matrix(&matrix, None), blur, None)
+    }
+
+    fn filter(
+        &self,
+        rect: &Rect,
+        scale: f32,
+        tile_mode: Option<skia::TileMode>,
+    ) -> Option<ImageFilter> {
+        let Some(progression) = self.progression else {
+            let sigma = self.value * scale;
+            return skia::image_filters::blur((sigma, sigma), tile_mode, None, None);
+        };
+
+        progression
+            .levels(rect, scale, self.value)
+            .into_iter()
+            .filter_map(|(sigma, shader)| {
+                let mask = skia::image_filters::shader(shader, None)?;
+                // A missing blur, as with a zero radius, takes the content as is.
+                let level = skia::image_filters::blur((sigma, sigma), tile_mode, None, None);
+                skia::image_filters::blend(skia::BlendMode::DstIn, level, mask, None)
+            })
+            .reduce(|total, level| {
+                skia::image_filters::blend(skia::BlendMode::Plus, total.clone(), level, None)
+                    .unwrap_or(total)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progressive_blur_reach() {
+        let mut blur = Blur::new(BlurType::LayerBlur, false, 4.);
+        blur.progression = Some(BlurProgression {
+            progression_type: BlurProgressionType::Linear,
+            start: (0.5, 0.),
+            end: (0.5, 1.),
+            start_value: 10.,
+        });
+        assert_eq!(blur.max_value(), 10.);
+        assert_eq!(blur.reach(), 30.);
+
+        blur.scale_content(0.5);
+        assert_eq!(blur.value, 2.);
+        assert_eq!(blur.max_value(), 5.);
+
+        let rect = Rect::from_xywh(0., 0., 100., 100.);
+        assert!(blur.layer_filter(&rect, 1.).is_some());
     }
 }
@@ -1,9 +1,31 @@
-use skia_safe::{self as skia, Paint, Rect};
+use skia_safe::{self as skia, gradient_shader, Paint, Rect};
 
 pub use super::Color;
+use super::{BlendMode, ImageAdjustments};
 use crate::utils::get_image;
 use crate::uuid::Uuid;
 
+/// Color space in which the colors of a gradient are interpolated, like the CSS
+/// `<color-interpolation-method>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GradientInterpolation {
+    #[default]
+    Srgb,
+    LinearSrgb,
+    Oklab,
+    Oklch,
+}
+
+/// Way around the hue wheel for the polar color spaces (OKLCH).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HueInterpolation {
+    #[default]
+    Shorter,
+    Longer,
+    Increasing,
+    Decreasing,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Gradient {
     start: (f32, f32),
@@ -12,6 +34,9 @@ pub struct Gradient {
     width: f32,
     colors: Vec<Color>,
     offsets: Vec<f32>,
+    interpolation: GradientInterpolation,
+    hue_interpolation: HueInterpolation,
+    dither: bool,
 }
 
 impl Gradient {
@@ -29,6 +54,9 @@ impl Gradient {
             colors: vec![],
             offsets: vec![],
             width,
+            interpolation: GradientInterpolation::default(),
+            hue_interpolation: HueInterpolation::default(),
+            dither: false,
         };
 
         gradient.add_stops(stops);
@@ -42,27 +70,94 @@ impl Gradient {
         self.offsets.extend(offsets);
     }
 
-    pub fn to_linear_shader(&self, rect: &Rect) -> Option<skia::Shader> {
-        let start = (
-            rect.left + self.start.0 * rect.width(),
-            rect.top + self.start.1 * rect.height(),
-        );
-        let end = (
-            rect.left + self.end.0 * rect.width(),
-            rect.top + self.end.1 * rect.height(),
-        );
-        skia::shader::Shader::linear_gradient(
-            (start, end),
-            self.colors.as_slice(),
-            self.offsets.as_slice(),
-            skia::TileMode::Clamp,
-            None,
-            None,
-        )
+    pub fn start(&self) -> (f32, f32) {
+        self.start
     }
 
-    pub fn to_radial_shader(&self, rect: &Rect) -> Option<skia::Shader> {
-        let center = skia::Point::new(
+    pub fn end(&self) -> (f32, f32) {
+        self.end
+    }
+
+    pub fn opacity(&self) -> u8 {
+        self.opacity
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    pub fn stops(&self) -> impl Iterator<Item = (Color, f32)> + '_ {
+        self.colors
+            .iter()
+            .copied()
+            .zip(self.offsets.iter().copied())
+    }
+
+    pub fn set_interpolation(
+        &mut self,
+        interpolation: GradientInterpolation,
+        hue_interpolation: HueInterpolation,
+    ) {
+        self.interpolation = interpolation;
+        self.hue_interpolation = hue_interpolation;
+    }
+
+    pub fn interpolation(&self) -> GradientInterpolation {
+        self.interpolation
+    }
+
+    pub fn hue_interpolation(&self) -> HueInterpolation {
+        self.hue_interpolation
+    }
+
+    pub fn set_dither(&mut self, dither: bool) {
+        self.dither = dither;
+    }
+
+    /// Whether the gradient is dithered when it's painted, to hide the banding
+    /// of smooth gradients over large areas.
+    pub fn dither(&self) -> bool {
+        self.dither
+    }
+
+    fn colors4f(&self) -> Vec<skia::Color4f> {
+        self.colors.iter().map(|color| (*color).into()).collect()
+    }
+
+    fn skia_interpolation(&self) -> gradient_shader::Interpolation {
+        use gradient_shader::interpolation::{ColorSpace, HueMethod, InPremul};
+
+        let color_space = match self.interpolation {
+            // The colors are blended as they are, like the rest of the renderer.
+            GradientInterpolation::Srgb => ColorSpace::Destination,
+            GradientInterpolation::LinearSrgb => ColorSpace::SRGBLinear,
+            GradientInterpolation::Oklab => ColorSpace::OKLab,
+            GradientInterpolation::Oklch => ColorSpace::OKLCH,
+        };
+        let hue_method = match self.hue_interpolation {
+            HueInterpolation::Shorter => HueMethod::Shorter,
+            HueInterpolation::Longer => HueMethod::Longer,
+            HueInterpolation::Increasing => HueMethod::Increasing,
+            HueInterpolation::Decreasing => HueMethod::Decreasing,
+        };
+        // CSS interpolates premultiplied colors, so transparent stops don't
+        // tint their neighbours.
+        let in_premul = if self.interpolation == GradientInterpolation::Srgb {
+            InPremul::No
+        } else {
+            InPremul::Yes
+        };
+
+        gradient_shader::Interpolation {
+            in_premul,
+            color_space,
+            hue_method,
+        }
+    }
+
+    /// Start and end points of a linear gradient laid over `rect`.
+    pub fn linear_points(&self, rect: &Rect) -> (skia::Point, skia::Point) {
+        let start = skia::Point::new(
             rect.left + self.start.0 * rect.width(),
             rect.top + self.start.1 * rect.height(),
         );
@@ -70,6 +165,12 @@ impl Gradient {
             rect.left + self.end.0 * rect.width(),
             rect.top + self.end.1 * rect.height(),
         );
+        (start, end)
+    }
+
+    /// Center, radius and local transform of a radial gradient laid over `rect`.
+    pub fn radial_geometry(&self, rect: &Rect) -> (skia::Point, f32, skia::Matrix) {
+        let (center, end) = self.linear_points(rect);
 
         let direction = end - center;
         let distance = (direction.x.powi(2) + direction.y.powi(2)).sqrt();
@@ -83,35 +184,171 @@ impl Gradient {
         transform.pre_scale((self.width * rect.width() / rect.height(), 1.), None);
         transform.pre_translate((-center.x, -center.y));
 
-        skia::shader::Shader::radial_gradient(
+        (center, distance, transform)
+    }
+
+    /// Center and start angle (in degrees) of an angular gradient laid over `rect`.
+    /// The gradient goes clockwise around the start point, beginning in the
+    /// direction of the end point.
+    pub fn angular_geometry(&self, rect: &Rect) -> (skia::Point, f32) {
+        let (center, end) = self.linear_points(rect);
+        let direction = end - center;
+        (center, direction.y.atan2(direction.x).to_degrees())
+    }
+
+    pub fn to_linear_shader(&self, rect: &Rect) -> Option<skia::Shader> {
+        skia::shader::Shader::linear_gradient_with_interpolation(
+            self.linear_points(rect),
+            (self.colors4f().as_slice(), None),
+            self.offsets.as_slice(),
+            skia::TileMode::Clamp,
+            self.skia_interpolation(),
+            None,
+        )
+    }
+
+    pub fn to_radial_shader(&self, rect: &Rect) -> Option<skia::Shader> {
+        let (center, distance, transform) = self.radial_geometry(rect);
+
+        skia::shader::Shader::radial_gradient_with_interpolation(
+            (center, distance),
+            (self.colors4f().as_slice(), None),
+            self.offsets.as_slice(),
+            skia::TileMode::Clamp,
+            self.skia_interpolation(),
+            Some(&transform),
+        )
+    }
+
+    pub fn to_angular_shader(&self, rect: &Rect) -> Option<skia::Shader> {
+        let (center, angle) = self.angular_geometry(rect);
+
+        // Sweep gradients start at the positive x axis, so they're rotated to
+        // start towards the end point.
+        let mut transform = skia::Matrix::new_identity();
+        transform.pre_rotate(angle, center);
+
+        skia::shader::Shader::sweep_gradient_with_interpolation(
             center,
-            distance,
-            self.colors.as_slice(),
+            (self.colors4f().as_slice(), None),
             self.offsets.as_slice(),
             skia::TileMode::Clamp,
             None,
+            self.skia_interpolation(),
             Some(&transform),
         )
     }
 }
 
+/// A patch of a mesh gradient: a Coons patch with bezier edges and a color at
+/// each corner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeshPatch {
+    /// The four cubic edges, clockwise from the top-left corner, relative to
+    /// the shape bounds. Each corner is shared by two consecutive edges.
+    pub cubics: [(f32, f32); 12],
+    /// Top-left, top-right, bottom-right and bottom-left colors.
+    pub colors: [Color; 4],
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeshGradient {
+    opacity: u8,
+    patches: Vec<MeshPatch>,
+}
+
+impl MeshGradient {
+    pub fn new(opacity: u8, patches: Vec<MeshPatch>) -> Self {
+        Self { opacity, patches }
+    }
+
+    pub fn opacity(&self) -> u8 {
+        self.opacity
+    }
+
+    pub fn patches(&self) -> &[MeshPatch] {
+        &self.patches
+    }
+
+    /// Draws the patches over `rect` into a picture and returns it as a
+    /// shader. The area outside of the patches is transparent.
+    pub fn to_shader(&self, rect: &Rect) -> Option<skia::Shader> {
+        let mut recorder = skia::PictureRecorder::new();
+        let canvas = recorder.begin_recording(*rect, None);
+
+        let mut paint = skia::Paint::default();
+        paint.set_anti_alias(true);
+
+        for patch in &self.patches {
+            let cubics = patch.cubics.map(|(x, y)| {
+                skia::Point::new(rect.left + x * rect.width(), rect.top + y * rect.height())
+            });
+            // The colors are modulated with the paint color, which is opaque
+            // white, so they're drawn as they are.
+            canvas.draw_patch(
+                &cubics,
+                Some(&patch.colors),
+                None,
+                skia::BlendMode::Modulate,
+                &paint,
+            );
+        }
+
+        let picture = recorder.finish_recording_as_picture(None)?;
+        Some(picture.to_shader(
+            (skia::TileMode::Decal, skia::TileMode::Decal),
+            skia::FilterMode::Linear,
+            None,
+            Some(rect),
+        ))
+    }
+}
+
+/// Offset, scale and rotation (in degrees) applied by the user to a cropped
+/// image. The offset is relative to the shape size, and the image is scaled and
+/// rotated around its center.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageTransform {
+    pub offset: (f32, f32),
+    pub scale: f32,
+    pub rotation: f32,
+}
+
+/// How an image fill is laid over the shape.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageScaleMode {
+    /// Scaled to the size of the shape.
+    Stretch,
+    /// Scaled to cover the shape keeping its aspect ratio, and centered.
+    Fill,
+    /// Scaled to fit in the shape keeping its aspect ratio, and centered.
+    Fit,
+    /// Laid like `Fill`, and then transformed.
+    Crop(ImageTransform),
+    /// Repeated from the top-left corner of the shape, at its size times the
+    /// given scale.
+    Tile(f32),
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ImageFill {
     id: Uuid,
     opacity: u8,
     width: i32,
     height: i32,
-    keep_aspect_ratio: bool,
+    scale_mode: ImageScaleMode,
+    adjustments: ImageAdjustments,
 }
 
 impl ImageFill {
-    pub fn new(id: Uuid, opacity: u8, width: i32, height: i32, keep_aspect_ratio: bool) -> Self {
+    pub fn new(id: Uuid, opacity: u8, width: i32, height: i32, scale_mode: ImageScaleMode) -> Self {
         Self {
             id,
             opacity,
             width,
             height,
-            keep_aspect_ratio,
+            scale_mode,
+            adjustments: ImageAdjustments::default(),
         }
     }
 
@@ -123,8 +360,100 @@ impl ImageFill {
         self.opacity
     }
 
-    pub fn keep_aspect_ratio(&self) -> bool {
-        self.keep_aspect_ratio
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    pub fn scale_mode(&self) -> ImageScaleMode {
+        self.scale_mode
+    }
+
+    pub fn adjustments(&self) -> &ImageAdjustments {
+        &self.adjustments
+    }
+
+    pub fn set_adjustments(&mut self, adjustments: ImageAdjustments) {
+        self.adjustments = adjustments;
+    }
+
+    /// Transform from the pixels of an image of `image_size` to the shape
+    /// space, laying it over `container` following the scale mode.
+    ///
+    /// The layout uses the size of the fill, so thumbnails end up like the
+    /// full image.
+    pub fn image_matrix(&self, image_size: (f32, f32), container: &Rect) -> skia::Matrix {
+        let (width, height) = if self.width > 0 && self.height > 0 {
+            (self.width as f32, self.height as f32)
+        } else {
+            image_size
+        };
+        let scale_x = container.width() / width;
+        let scale_y = container.height() / height;
+
+        let mut matrix = skia::Matrix::new_identity();
+        match self.scale_mode {
+            ImageScaleMode::Stretch => {
+                matrix.pre_translate((container.left, container.top));
+                matrix.pre_scale((scale_x, scale_y), None);
+            }
+            ImageScaleMode::Fill | ImageScaleMode::Fit | ImageScaleMode::Crop(_) => {
+                let scale = if self.scale_mode == ImageScaleMode::Fit {
+                    scale_x.min(scale_y)
+                } else {
+                    scale_x.max(scale_y)
+                };
+
+                matrix.pre_translate(container.center());
+                if let ImageScaleMode::Crop(transform) = self.scale_mode {
+                    matrix.pre_translate((
+                        transform.offset.0 * container.width(),
+                        transform.offset.1 * container.height(),
+                    ));
+                    matrix.pre_rotate(transform.rotation, None);
+                    matrix.pre_scale((transform.scale, transform.scale), None);
+                }
+                matrix.pre_scale((scale, scale), None);
+                matrix.pre_translate((-width / 2., -height / 2.));
+            }
+            ImageScaleMode::Tile(scale) => {
+                matrix.pre_translate((container.left, container.top));
+                matrix.pre_scale((scale, scale), None);
+            }
+        }
+
+        matrix.pre_scale((width / image_size.0, height / image_size.1), None);
+        matrix
+    }
+
+    /// Shader painting `image` over `container` with its adjustments, without
+    /// the fill opacity. Outside of the image it's transparent, unless the image
+    /// is tiled.
+    pub fn to_shader(
+        &self,
+        image: &skia::Image,
+        container: &Rect,
+        sampling_options: skia::SamplingOptions,
+    ) -> Option<skia::Shader> {
+        let image_size = (image.width() as f32, image.height() as f32);
+        let tile_mode = match self.scale_mode {
+            ImageScaleMode::Tile(_) => skia::TileMode::Repeat,
+            _ => skia::TileMode::Decal,
+        };
+
+        let shader = image.to_shader(
+            (tile_mode, tile_mode),
+            sampling_options,
+            &self.image_matrix(image_size, container),
+        )?;
+
+        match self.adjustments.to_color_filter() {
+            Some(filter) => Some(shader.with_color_filter(filter)),
+            None => Some(shader),
+        }
     }
 }
 
@@ -136,10 +465,22 @@ pub enum Fill {
     Solid(SolidColor),
     LinearGradient(Gradient),
     RadialGradient(Gradient),
+    AngularGradient(Gradient),
+    MeshGradient(MeshGradient),
     Image(ImageFill),
 }
 
 impl Fill {
+    /// Whether the paint has to be dithered, see `Gradient::dither`.
+    pub fn is_dithered(&self) -> bool {
+        match self {
+            Self::LinearGradient(gradient)
+            | Self::RadialGradient(gradient)
+            | Self::AngularGradient(gradient) => gradient.dither(),
+            _ => false,
+        }
+    }
+
     pub fn to_paint(&self, rect: &Rect, anti_alias: bool) -> skia::Paint {
         match self {
             Self::Solid(SolidColor(color)) => {
@@ -154,6 +495,7 @@ impl Fill {
                 let mut p = skia::Paint::default();
                 p.set_shader(gradient.to_linear_shader(rect));
                 p.set_alpha(gradient.opacity);
+                p.set_dither(gradient.dither);
                 p.set_style(skia::PaintStyle::Fill);
                 p.set_anti_alias(anti_alias);
                 p.set_blend_mode(skia::BlendMode::SrcOver);
@@ -163,6 +505,26 @@ impl Fill {
                 let mut p = skia::Paint::default();
                 p.set_shader(gradient.to_radial_shader(rect));
                 p.set_alpha(gradient.opacity);
+                p.set_dither(gradient.dither);
+                p.set_style(skia::PaintStyle::Fill);
+                p.set_anti_alias(anti_alias);
+                p.set_blend_mode(skia::BlendMode::SrcOver);
+                p
+            }
+            Self::AngularGradient(gradient) => {
+                let mut p = skia::Paint::default();
+                p.set_shader(gradient.to_angular_shader(rect));
+                p.set_alpha(gradient.opacity);
+                p.set_dither(gradient.dither);
+                p.set_style(skia::PaintStyle::Fill);
+                p.set_anti_alias(anti_alias);
+                p.set_blend_mode(skia::BlendMode::SrcOver);
+                p
+            }
+            Self::MeshGradient(mesh) => {
+                let mut p = skia::Paint::default();
+                p.set_shader(mesh.to_shader(rect));
+                p.set_alpha(mesh.opacity);
                 p.set_style(skia::PaintStyle::Fill);
                 p.set_anti_alias(anti_alias);
                 p.set_blend_mode(skia::BlendMode::SrcOver);
@@ -180,11 +542,51 @@ impl Fill {
     }
 }
 
+/// One of the fills stacked in a shape, composited over the ones below it with
+/// its own blend mode and opacity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FillLayer {
+    pub fill: Fill,
+    pub blend_mode: BlendMode,
+    pub opacity: f32,
+}
+
+impl FillLayer {
+    pub fn new(fill: Fill, blend_mode: BlendMode, opacity: f32) -> Self {
+        Self {
+            fill,
+            blend_mode,
+            opacity,
+        }
+    }
+
+    pub fn to_paint(&self, rect: &Rect, anti_alias: bool) -> skia::Paint {
+        let mut p = self.fill.to_paint(rect, anti_alias);
+        set_paint_layer(&mut p, self.blend_mode, self.opacity);
+        p
+    }
+}
+
+impl From<Fill> for FillLayer {
+    fn from(fill: Fill) -> Self {
+        Self::new(fill, BlendMode::default(), 1.)
+    }
+}
+
+/// Sets the blend mode of a fill layer in `paint` and applies its opacity on
+/// top of the one of the fill.
+pub fn set_paint_layer(paint: &mut Paint, blend_mode: BlendMode, opacity: f32) {
+    paint.set_blend_mode(blend_mode.into());
+    paint.set_alpha_f(paint.alpha_f() * opacity.clamp(0., 1.));
+}
+
 pub fn get_fill_shader(fill: &Fill, bounding_box: &Rect) -> Option<skia::Shader> {
     match fill {
         Fill::Solid(SolidColor(color)) => Some(skia::shaders::color(*color)),
         Fill::LinearGradient(gradient) => gradient.to_linear_shader(bounding_box),
         Fill::RadialGradient(gradient) => gradient.to_radial_shader(bounding_box),
+        Fill::AngularGradient(gradient) => gradient.to_angular_shader(bounding_box),
+        Fill::MeshGradient(mesh) => mesh.to_shader(bounding_box),
         Fill::Image(image_fill) => {
             let mut image_shader = None;
             let image = get_image(&image_fill.id);
@@ -192,27 +594,11 @@ pub fn get_fill_shader(fill: &Fill, bounding_box: &Rect) -> Option<skia::Shader>
                 let sampling_options =
                     skia::SamplingOptions::new(skia::FilterMode::Linear, skia::MipmapMode::Nearest);
 
-                // FIXME no image ratio applied, centered to the current rect
-                let tile_modes = (skia::TileMode::Clamp, skia::TileMode::Clamp);
-                let image_width = image_fill.width as f32;
-                let image_height = image_fill.height as f32;
-                let scale_x = bounding_box.width() / image_width;
-                let scale_y = bounding_box.height() / image_height;
-                let scale = scale_x.max(scale_y);
-                let scaled_width = image_width * scale;
-                let scaled_height = image_height * scale;
-                let pos_x = bounding_box.left() - (scaled_width - bounding_box.width()) / 2.0;
-                let pos_y = bounding_box.top() - (scaled_height - bounding_box.height()) / 2.0;
-
-                let mut matrix = skia::Matrix::new_identity();
-                matrix.pre_translate((pos_x, pos_y));
-                matrix.pre_scale((scale, scale), None);
-
                 let opacity = image_fill.opacity();
                 let alpha_color = skia::Color4f::new(1.0, 1.0, 1.0, opacity as f32 / 255.0);
                 let alpha_shader = skia::shaders::color(alpha_color.to_color());
 
-                image_shader = image.to_shader(tile_modes, sampling_options, &matrix);
+                image_shader = image_fill.to_shader(image, bounding_box, sampling_options);
                 if let Some(shader) = image_shader {
                     image_shader = Some(skia::shaders::blend(
                         skia::Blender::mode(skia::BlendMode::DstIn),
@@ -252,6 +638,7 @@ pub fn merge_fills(fills: &[Fill], bounding_box: Rect) -> skia::Paint {
     }
 
     fills_paint.set_shader(combined_shader.clone());
+    fills_paint.set_dither(fills.iter().any(Fill::is_dithered));
     fills_paint
 }
 
@@ -265,4 +652,50 @@ pub fn set_paint_fill(paint: &mut Paint, fill: &Fill, bounding_box: &Rect, remov
     if let Some(shader) = shader {
         paint.set_shader(shader);
     }
+    paint.set_dither(fill.is_dithered());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image_fill(scale_mode: ImageScaleMode) -> ImageFill {
+        ImageFill::new(Uuid::nil(), 255, 200, 100, scale_mode)
+    }
+
+    #[test]
+    fn test_image_matrix_scale_modes() {
+        let container = Rect::from_xywh(10.0, 10.0, 100.0, 100.0);
+        // A thumbnail at half the size of the image.
+        let thumbnail = (100.0, 50.0);
+        let image_rect = Rect::from_wh(100.0, 50.0);
+        let mapped = |mode| {
+            image_fill(mode)
+                .image_matrix(thumbnail, &container)
+                .map_rect(image_rect)
+                .0
+        };
+
+        assert_eq!(mapped(ImageScaleMode::Stretch), container);
+        assert_eq!(
+            mapped(ImageScaleMode::Fill),
+            Rect::from_xywh(-40.0, 10.0, 200.0, 100.0)
+        );
+        assert_eq!(
+            mapped(ImageScaleMode::Fit),
+            Rect::from_xywh(10.0, 35.0, 100.0, 50.0)
+        );
+        assert_eq!(
+            mapped(ImageScaleMode::Crop(ImageTransform {
+                offset: (0.1, 0.0),
+                scale: 0.5,
+                rotation: 0.0,
+            })),
+            Rect::from_xywh(20.0, 35.0, 100.0, 50.0)
+        );
+        assert_eq!(
+            mapped(ImageScaleMode::Tile(0.25)),
+            Rect::from_xywh(10.0, 10.0, 50.0, 25.0)
+        );
+    }
 }
@@ -0,0 +1,150 @@
+use skia_safe::{self as skia, color_filters};
+
+// Rec. 709 luma coefficients.
+const LUMA: [f32; 3] = [0.2126, 0.7152, 0.0722];
+
+/// How much temperature and tint change the channels at their extremes.
+const WHITE_BALANCE_RANGE: f32 = 0.2;
+
+/// How much highlights and shadows move the tones at their extremes.
+const TONE_RANGE: f32 = 1.5;
+
+/// Color adjustments of an image fill. They're applied when the image is
+/// painted, so the stored image is never modified.
+///
+/// Exposure is in stops. The rest go from `-1` to `1`, and `0` leaves the
+/// image as it is.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ImageAdjustments {
+    pub exposure: f32,
+    pub contrast: f32,
+    pub saturation: f32,
+    pub temperature: f32,
+    pub tint: f32,
+    pub highlights: f32,
+    pub shadows: f32,
+}
+
+impl ImageAdjustments {
+    /// Color filter applying the adjustments in order: exposure and white
+    /// balance, highlights and shadows, contrast and saturation.
+    pub fn to_color_filter(&self) -> Option<skia::ColorFilter> {
+        let filters = [
+            self.white_balance_filter(),
+            self.tone_filter(),
+            self.contrast_filter(),
+            self.saturation_filter(),
+        ];
+
+        filters
+            .into_iter()
+            .flatten()
+            .reduce(|inner, outer| outer.composed(inner.clone()).unwrap_or(inner))
+    }
+
+    fn white_balance_filter(&self) -> Option<skia::ColorFilter> {
+        if self.exposure == 0. && self.temperature == 0. && self.tint == 0. {
+            return None;
+        }
+
+        let gain = 2f32.powf(self.exposure);
+        let red = gain * (1. + self.temperature * WHITE_BALANCE_RANGE);
+        // Positive tints go towards magenta, negative ones towards green.
+        let green = gain * (1. - self.tint * WHITE_BALANCE_RANGE);
+        let blue = gain * (1. - self.temperature * WHITE_BALANCE_RANGE);
+
+        #[rustfmt::skip]
+        let matrix = [
+            red, 0., 0., 0., 0.,
+            0., green, 0., 0., 0.,
+            0., 0., blue, 0., 0.,
+            0., 0., 0., 1., 0.,
+        ];
+        Some(color_filters::matrix_row_major(&matrix, None))
+    }
+
+    /// Shadows lift or sink the dark tones and highlights the bright ones,
+    /// leaving black and white as they are.
+    fn tone_filter(&self) -> Option<skia::ColorFilter> {
+        if self.highlights == 0. && self.shadows == 0. {
+            return None;
+        }
+
+        let mut table = [0u8; 256];
+        for (i, value) in table.iter_mut().enumerate() {
+            let x = i as f32 / 255.;
+            let shadows = x * (1. - x).powi(2) * TONE_RANGE;
+            let highlights = x.powi(2) * (1. - x) * TONE_RANGE;
+            let y = x + self.shadows * shadows + self.highlights * highlights;
+            *value = (y.clamp(0., 1.) * 255.).round() as u8;
+        }
+
+        color_filters::table_argb(None, &table, &table, &table)
+    }
+
+    fn contrast_filter(&self) -> Option<skia::ColorFilter> {
+        if self.contrast == 0. {
+            return None;
+        }
+
+        // Scales the channels around the middle gray.
+        let scale = 1. + self.contrast;
+        let offset = 0.5 * (1. - scale);
+
+        #[rustfmt::skip]
+        let matrix = [
+            scale, 0., 0., 0., offset,
+            0., scale, 0., 0., offset,
+            0., 0., scale, 0., offset,
+            0., 0., 0., 1., 0.,
+        ];
+        Some(color_filters::matrix_row_major(&matrix, None))
+    }
+
+    fn saturation_filter(&self) -> Option<skia::ColorFilter> {
+        if self.saturation == 0. {
+            return None;
+        }
+
+        // Interpolates between the luma of the color and the color itself.
+        let saturation = 1. + self.saturation;
+        let mut matrix = [0.; 20];
+        for row in 0..3 {
+            for (column, luma) in LUMA.iter().enumerate() {
+                let identity = if row == column { 1. } else { 0. };
+                matrix[row * 5 + column] = luma * (1. - saturation) + identity * saturation;
+            }
+        }
+        matrix[18] = 1.;
+        Some(color_filters::matrix_row_major(&matrix, None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_adjustments_filter() {
+        assert!(ImageAdjustments::default().to_color_filter().is_none());
+
+        let exposure = ImageAdjustments {
+            exposure: 1.,
+            ..Default::default()
+        };
+        let filter = exposure.to_color_filter().unwrap();
+        let color = filter.filter_color(skia::Color::from_rgb(64, 32, 16));
+        assert!(color.r().abs_diff(128) <= 1);
+        assert!(color.g().abs_diff(64) <= 1);
+        assert!(color.b().abs_diff(32) <= 1);
+
+        let desaturated = ImageAdjustments {
+            saturation: -1.,
+            ..Default::default()
+        };
+        let filter = desaturated.to_color_filter().unwrap();
+        let gray = filter.filter_color(skia::Color::from_rgb(200, 100, 50));
+        assert_eq!(gray.r(), gray.g());
+        assert_eq!(gray.g(), gray.b());
+    }
+}
@@ -0,0 +1,148 @@
+use skia_safe::{self as skia, color_filters, Paint, Shader};
+
+use super::Color;
+
+/// How sharp the grains are. The noise values are pushed away from the density
+/// threshold by this factor.
+const GRAIN_CONTRAST: f32 = 4.;
+
+/// Octaves of the fractal noise the grains are made of.
+pub const NOISE_OCTAVES: usize = 2;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum NoiseType {
+    #[default]
+    Mono,
+    Duotone,
+    Multicolor,
+}
+
+/// Grain drawn over the fills of a shape. `size` is the size of the grains in
+/// shape units and `density` how much of the shape they cover, from `0` to `1`.
+/// Mono noise uses `color`, duotone noise mixes `color` and `secondary_color`
+/// and multicolor noise keeps the colors of the noise itself.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Noise {
+    pub hidden: bool,
+    pub noise_type: NoiseType,
+    pub size: f32,
+    pub density: f32,
+    pub opacity: f32,
+    pub color: Color,
+    pub secondary_color: Color,
+}
+
+impl Noise {
+    pub fn new(noise_type: NoiseType, hidden: bool, size: f32, density: f32, opacity: f32) -> Self {
+        Noise {
+            hidden,
+            noise_type,
+            size,
+            density,
+            opacity,
+            color: skia::Color::BLACK,
+            secondary_color: skia::Color::WHITE,
+        }
+    }
+
+    pub fn scale_content(&mut self, value: f32) {
+        self.size *= value;
+    }
+
+    /// Paint drawing the noise over what's already on the canvas, so it's
+    /// masked to the fills below it.
+    pub fn paint(&self) -> Option<Paint> {
+        let shader = match self.noise_type {
+            NoiseType::Mono => self.colored_grains(self.color, 0.)?,
+            NoiseType::Duotone => skia::shaders::blend(
+                skia::BlendMode::SrcOver,
+                self.colored_grains(self.secondary_color, 1.)?,
+                self.colored_grains(self.color, 0.)?,
+            ),
+            NoiseType::Multicolor => {
+                self.grains(0.)?
+                    .with_color_filter(color_filters::matrix_row_major(
+                        &self.multicolor_matrix(),
+                        None,
+                    ))
+            }
+        };
+
+        let mut paint = Paint::default();
+        paint.set_shader(shader);
+        paint.set_alpha_f(self.opacity);
+        paint.set_blend_mode(skia::BlendMode::SrcATop);
+        Some(paint)
+    }
+
+    /// Base frequency of the fractal noise, none when the grains have no size.
+    pub fn frequency(&self) -> Option<f32> {
+        (self.size > 0.).then(|| 1. / self.size)
+    }
+
+    /// Color matrix of the multicolor noise. It spreads the colors around the
+    /// middle gray the noise is centered on, so they don't look washed out.
+    pub fn multicolor_matrix(&self) -> [f32; 20] {
+        let offset = 0.5 * (1. - GRAIN_CONTRAST);
+
+        #[rustfmt::skip]
+        let matrix = [
+            GRAIN_CONTRAST, 0., 0., 0., offset,
+            0., GRAIN_CONTRAST, 0., 0., offset,
+            0., 0., GRAIN_CONTRAST, 0., offset,
+            0., 0., 0., GRAIN_CONTRAST, self.grain_offset(),
+        ];
+        matrix
+    }
+
+    /// Color matrix keeping the noise alpha over the density threshold, used
+    /// as the mask of the single color grains.
+    pub fn mask_matrix(&self) -> [f32; 20] {
+        #[rustfmt::skip]
+        let matrix = [
+            0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0.,
+            0., 0., 0., GRAIN_CONTRAST, self.grain_offset(),
+        ];
+        matrix
+    }
+
+    fn grains(&self, seed: f32) -> Option<Shader> {
+        let frequency = self.frequency()?;
+        Shader::fractal_perlin_noise((frequency, frequency), NOISE_OCTAVES, seed, None)
+    }
+
+    /// Grains of a single color, shown where the noise alpha is over the
+    /// density threshold.
+    fn colored_grains(&self, color: Color, seed: f32) -> Option<Shader> {
+        let mask = self
+            .grains(seed)?
+            .with_color_filter(color_filters::matrix_row_major(&self.mask_matrix(), None));
+        Some(mask.with_color_filter(color_filters::blend(color, skia::BlendMode::SrcIn)?))
+    }
+
+    /// Alpha offset putting the edge of the grains at `1 - density`, so denser
+    /// noise shows more of them.
+    fn grain_offset(&self) -> f32 {
+        0.5 - GRAIN_CONTRAST * (1. - self.density.clamp(0., 1.))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_noise_paint() {
+        let mut noise = Noise::new(NoiseType::Duotone, false, 2., 0.5, 0.3);
+        noise.scale_content(2.);
+        assert_eq!(noise.size, 4.);
+
+        let paint = noise.paint().unwrap();
+        assert_eq!(paint.as_blend_mode(), Some(skia::BlendMode::SrcATop));
+
+        noise.size = 0.;
+        assert!(noise.paint().is_none());
+    }
+}
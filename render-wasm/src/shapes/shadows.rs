@@ -1,13 +1,35 @@
 use skia_safe::{self as skia, image_filters, ImageFilter, Paint};
 
-use super::Color;
+use super::{BlendMode, Color};
 use crate::render::filters::compose_filters;
 
+/// Glows are shadows without offset, so they go through the same filters and
+/// surfaces: outer glows along with the drop shadows and inner glows along with
+/// the inner ones.
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub enum ShadowStyle {
     #[default]
     Drop,
     Inner,
+    OuterGlow,
+    InnerGlow,
+}
+
+/// How a glow is blurred. Precise glows grow the shape before blurring it, so
+/// they keep its corners instead of rounding them.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum GlowTechnique {
+    #[default]
+    Soft,
+    Precise,
+}
+
+/// Where an inner glow starts from.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum GlowSource {
+    #[default]
+    Edge,
+    Center,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -18,6 +40,12 @@ pub struct Shadow {
     pub offset: (f32, f32),
     style: ShadowStyle,
     hidden: bool,
+    pub blend_mode: BlendMode,
+    pub technique: GlowTechnique,
+    pub source: GlowSource,
+    /// Whether a drop shadow shows through the semi-transparent parts of the
+    /// shape. When it doesn't, the default, it's removed below the shape.
+    pub show_behind: bool,
 }
 
 impl Shadow {
@@ -36,6 +64,10 @@ impl Shadow {
             offset,
             style,
             hidden,
+            blend_mode: BlendMode::default(),
+            technique: GlowTechnique::default(),
+            source: GlowSource::default(),
+            show_behind: false,
         }
     }
 
@@ -43,27 +75,67 @@ impl Shadow {
         self.style
     }
 
+    /// Drop shadows and outer glows, drawn below the shape.
+    pub fn is_drop(&self) -> bool {
+        matches!(self.style, ShadowStyle::Drop | ShadowStyle::OuterGlow)
+    }
+
+    /// Inner shadows and inner glows, drawn over the shape.
+    pub fn is_inner(&self) -> bool {
+        matches!(self.style, ShadowStyle::Inner | ShadowStyle::InnerGlow)
+    }
+
+    /// Spread and blur used by the filters.
+    pub fn spread_and_blur(&self) -> (f32, f32) {
+        match (self.style, self.technique) {
+            (ShadowStyle::OuterGlow | ShadowStyle::InnerGlow, GlowTechnique::Precise) => {
+                (self.spread + self.blur * 0.5, self.blur * 0.25)
+            }
+            _ => (self.spread, self.blur),
+        }
+    }
+
+    /// Glows from the center are the blurred shape itself, fading towards the
+    /// edges, instead of the blurred outside of the shape.
+    pub fn is_center_glow(&self) -> bool {
+        self.style == ShadowStyle::InnerGlow && self.source == GlowSource::Center
+    }
+
     pub fn hidden(&self) -> bool {
         self.hidden
     }
 
     pub fn get_drop_shadow_filter(&self) -> Option<ImageFilter> {
+        let (spread, blur) = self.spread_and_blur();
         let mut filter = image_filters::drop_shadow_only(
             (self.offset.0, self.offset.1),
-            (self.blur, self.blur),
+            (blur, blur),
             self.color,
             None,
             None,
             None,
         );
 
-        if self.spread > 0. {
-            filter = image_filters::dilate((self.spread, self.spread), filter, None);
+        if spread > 0. {
+            filter = image_filters::dilate((spread, spread), filter, None);
         }
 
         filter
     }
 
+    /// Paint of a layer drawing the shape and leaving only its drop shadow.
+    pub fn get_drop_shadow_paint(&self) -> Paint {
+        let mut filter = self.get_drop_shadow_filter();
+        if !self.show_behind {
+            filter = image_filters::blend(skia::BlendMode::DstOut, filter, None, None);
+        }
+
+        let mut paint = Paint::default();
+        paint.set_image_filter(filter);
+        paint.set_blend_mode(self.blend_mode.into());
+        paint
+    }
+
     pub fn get_inner_shadow_paint(
         &self,
         antialias: bool,
@@ -74,28 +146,38 @@ impl Shadow {
         let filter = compose_filters(blur_filter, shadow_filter.as_ref());
         paint.set_image_filter(filter);
         paint.set_anti_alias(antialias);
+        paint.set_blend_mode(self.blend_mode.into());
         paint
     }
 
     pub fn get_inner_shadow_filter(&self) -> Option<ImageFilter> {
-        let sigma = self.blur * 0.5;
+        let (spread, blur) = self.spread_and_blur();
+        let sigma = blur * 0.5;
+
+        let from_center = self.is_center_glow();
         let mut filter = skia::image_filters::drop_shadow_only(
             (self.offset.0, self.offset.1), // DPR?
             (sigma, sigma),
-            skia::Color::WHITE,
+            if from_center {
+                self.color
+            } else {
+                skia::Color::WHITE
+            },
             None,
             None,
             None,
         );
 
-        filter = skia::image_filters::color_filter(
-            skia::color_filters::blend(self.color, skia::BlendMode::SrcOut).unwrap(),
-            filter,
-            None,
-        );
+        if !from_center {
+            filter = skia::image_filters::color_filter(
+                skia::color_filters::blend(self.color, skia::BlendMode::SrcOut).unwrap(),
+                filter,
+                None,
+            );
+        }
 
-        if self.spread > 0. {
-            filter = skia::image_filters::dilate((self.spread, self.spread), filter, None);
+        if spread > 0. {
+            filter = skia::image_filters::dilate((spread, spread), filter, None);
         }
 
         filter = skia::image_filters::blend(skia::BlendMode::SrcIn, None, filter, None);
@@ -110,3 +192,60 @@ impl Shadow {
         self.offset.1 *= value;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Draws an opaque square through the drop shadow paint and reads back the
+    /// alpha of one pixel.
+    fn drop_shadow_alpha(shadow: &Shadow, x: i32, y: i32) -> u8 {
+        let mut surface = skia::surfaces::raster_n32_premul((40, 40)).unwrap();
+        let canvas = surface.canvas();
+        let paint = shadow.get_drop_shadow_paint();
+        canvas.save_layer(&skia::canvas::SaveLayerRec::default().paint(&paint));
+        let mut fill = Paint::default();
+        fill.set_color(skia::Color::RED);
+        canvas.draw_rect(skia::Rect::from_ltrb(10., 10., 30., 30.), &fill);
+        canvas.restore();
+
+        let image = surface.image_snapshot();
+        let info = skia::ImageInfo::new(
+            (1, 1),
+            skia::ColorType::RGBA8888,
+            skia::AlphaType::Unpremul,
+            None,
+        );
+        let mut pixel = [0; 4];
+        assert!(image.read_pixels(
+            &info,
+            &mut pixel,
+            4,
+            (x, y),
+            skia::image::CachingHint::Allow
+        ));
+        pixel[3]
+    }
+
+    #[test]
+    fn test_drop_shadow_paint_show_behind() {
+        let mut shadow = Shadow::new(
+            skia::Color::BLACK,
+            0.,
+            0.,
+            (5., 5.),
+            ShadowStyle::Drop,
+            false,
+        );
+
+        // Below the shape the shadow is removed unless it shows behind.
+        assert_eq!(drop_shadow_alpha(&shadow, 20, 20), 0);
+        assert_eq!(drop_shadow_alpha(&shadow, 32, 32), 255);
+        assert_eq!(drop_shadow_alpha(&shadow, 5, 5), 0);
+
+        shadow.show_behind = true;
+        assert_eq!(drop_shadow_alpha(&shadow, 20, 20), 255);
+        assert_eq!(drop_shadow_alpha(&shadow, 32, 32), 255);
+        assert_eq!(drop_shadow_alpha(&shadow, 5, 5), 0);
+    }
+}
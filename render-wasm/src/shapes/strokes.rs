@@ -1,6 +1,8 @@
-use crate::shapes::fills::{Fill, SolidColor};
+use crate::shapes::fills::{set_paint_layer, Fill, SolidColor};
 use skia_safe::{self as skia, Rect};
+use std::f32::consts::FRAC_PI_8;
 
+use super::BlendMode;
 use super::Corners;
 use super::StrokeLineCap;
 use super::StrokeLineJoin;
@@ -12,6 +14,8 @@ pub enum StrokeStyle {
     Dotted,
     Dashed,
     Mixed,
+    /// Dashes with the pattern of `Stroke::dash`.
+    Custom,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -23,6 +27,334 @@ pub enum StrokeCap {
     DiamondMarker,
     Round,
     Square,
+    Bar,
+    ReversedArrow,
+    HalfArrow,
+    CircleOutline,
+    DiamondOutline,
+    TriangleOutline,
+}
+
+/// Custom dash pattern of a stroke, like the SVG `stroke-dasharray`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StrokeDash {
+    /// Lengths of the dashes and the gaps between them, starting with a dash.
+    /// Odd lists are repeated to get an even one, like in SVG.
+    pub intervals: Vec<f32>,
+    /// Distance into the pattern where the stroke starts.
+    pub offset: f32,
+    /// Scales the pattern between every pair of corners so the corners and the
+    /// path ends land in the middle of a dash. The offset is ignored then.
+    pub adjust: bool,
+    /// Cap of every dash.
+    pub cap: StrokeLineCap,
+}
+
+// Same default as SVG and skia.
+pub const DEFAULT_MITER_LIMIT: f32 = 4.;
+
+// Turns smaller than this (in radians) aren't corners.
+const CORNER_MIN_ANGLE: f32 = 0.02;
+
+impl StrokeDash {
+    fn pattern(&self) -> Option<Vec<f32>> {
+        if self
+            .intervals
+            .iter()
+            .any(|interval| !interval.is_finite() || *interval < 0.)
+        {
+            return None;
+        }
+
+        let mut pattern = self.intervals.clone();
+        if pattern.len() % 2 == 1 {
+            pattern.extend_from_within(..);
+        }
+        (pattern.iter().sum::<f32>() > 0.).then_some(pattern)
+    }
+
+    /// Sets the cap of the dashes on a stroke paint.
+    pub fn apply_cap(&self, paint: &mut skia::Paint) {
+        match self.cap {
+            StrokeLineCap::Butt => {}
+            StrokeLineCap::Round => {
+                paint.set_stroke_cap(skia::paint::Cap::Round);
+            }
+            StrokeLineCap::Square => {
+                paint.set_stroke_cap(skia::paint::Cap::Square);
+            }
+        }
+    }
+
+    pub fn path_effect(&self) -> Option<skia::PathEffect> {
+        skia::PathEffect::dash(&self.pattern()?, self.offset)
+    }
+
+    /// Dashes of `path` for the adjusted patterns, which depend on the length
+    /// between the corners and can't be a path effect.
+    pub fn adjusted_path(&self, path: &skia::Path) -> Option<skia::Path> {
+        let pattern = self.pattern()?;
+        let pattern_length: f32 = pattern.iter().sum();
+
+        let mut dashed = skia::Path::new();
+        for contour in skia::ContourMeasureIter::new(path, false, None) {
+            let length = contour.length();
+            let corners = corner_distances(&contour);
+
+            // Ranges of the dashes, with the pattern scaled to fit between every
+            // pair of corners, starting and ending in the middle of a dash.
+            let mut dashes: Vec<(f32, f32)> = vec![];
+            for span in corners.windows(2) {
+                let (start, end) = (span[0], span[1]);
+                let count = ((end - start) / pattern_length).round().max(1.);
+                let scale = (end - start) / (count * pattern_length);
+
+                let mut distance = start - pattern[0] * scale / 2.;
+                let mut index = 0;
+                while distance < end {
+                    let next = distance + pattern[index % pattern.len()] * scale;
+                    let (from, to) = (distance.max(start), next.min(end));
+                    if index % 2 == 0 && to > from {
+                        match dashes.last_mut() {
+                            // The dashes around a corner are one, so it's joined.
+                            Some(last) if from <= last.1 => last.1 = to,
+                            _ => dashes.push((from, to)),
+                        }
+                    }
+                    distance = next;
+                    index += 1;
+                }
+            }
+
+            // The dashes around the start of a closed contour are one too.
+            let wraps = contour.is_closed()
+                && dashes.len() > 1
+                && dashes[0].0 <= 0.
+                && dashes[dashes.len() - 1].1 >= length;
+            if wraps {
+                let (start, _) = dashes.pop().unwrap();
+                let (_, end) = dashes.remove(0);
+                if let Some(segment) = contour.segment(start, length, true) {
+                    dashed.add_path(&segment, (0., 0.), None);
+                }
+                if let Some(segment) = contour.segment(0., end, true) {
+                    dashed.add_path(&segment, (0., 0.), skia::path::AddPathMode::Extend);
+                }
+            }
+
+            for (from, to) in dashes {
+                if let Some(segment) = contour.segment(from, to, true) {
+                    dashed.add_path(&segment, (0., 0.), None);
+                }
+            }
+        }
+        Some(dashed)
+    }
+}
+
+/// Distances along the contour of its ends and its corners.
+fn corner_distances(contour: &skia::ContourMeasure) -> Vec<f32> {
+    let length = contour.length();
+    let delta = (length * 1e-4).max(1e-3);
+    let tangent = |distance: f32| contour.pos_tan(distance).map(|(_, tangent)| tangent);
+
+    let mut distances = vec![0.];
+    for verb in contour.verbs() {
+        let distance = verb.distance();
+        if distance <= delta || distance >= length - delta {
+            continue;
+        }
+        if let (Some(before), Some(after)) = (tangent(distance - delta), tangent(distance + delta))
+        {
+            if before.cross(after).atan2(before.dot(after)).abs() > CORNER_MIN_ANGLE {
+                distances.push(distance);
+            }
+        }
+    }
+    distances.push(length);
+    distances
+}
+
+// Maximum distance between the samples of a variable-width outline, and the
+// maximum number of them for every contour.
+const WIDTH_PROFILE_STEP: f32 = 1.;
+const WIDTH_PROFILE_MAX_SAMPLES: f32 = 2048.;
+
+/// Width of a stroke along its path, as a factor of `Stroke::width`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StrokeWidthProfile {
+    /// Positions along the path, from `0` to `1`, and the width factor there.
+    /// The width is interpolated linearly between them.
+    Points(Vec<(f32, f32)>),
+    TaperStart,
+    TaperEnd,
+    TaperBoth,
+}
+
+impl StrokeWidthProfile {
+    fn points(&self) -> Vec<(f32, f32)> {
+        match self {
+            Self::Points(points) => {
+                let mut points = points.clone();
+                points.sort_by(|a, b| a.0.total_cmp(&b.0));
+                points
+            }
+            Self::TaperStart => vec![(0., 0.), (1., 1.)],
+            Self::TaperEnd => vec![(0., 1.), (1., 0.)],
+            Self::TaperBoth => vec![(0., 0.), (0.5, 1.), (1., 0.)],
+        }
+    }
+
+    /// Largest width factor of the profile. Profiles without points have the
+    /// width of the stroke.
+    pub fn max_factor(&self) -> f32 {
+        let points = self.points();
+        if points.is_empty() {
+            return 1.;
+        }
+        points
+            .iter()
+            .map(|(_, factor)| factor.max(0.))
+            .fold(0., f32::max)
+    }
+
+    fn factor_at(points: &[(f32, f32)], position: f32) -> f32 {
+        let factor = match points.iter().position(|(at, _)| *at >= position) {
+            None => points.last().map_or(1., |(_, factor)| *factor),
+            Some(0) => points[0].1,
+            Some(index) => {
+                let (start, from) = points[index - 1];
+                let (end, to) = points[index];
+                from + (to - from) * (position - start) / (end - start)
+            }
+        };
+        factor.max(0.)
+    }
+
+    /// Fill outline of a stroke of `width` along `path`. Corners are beveled
+    /// and the ends are butt.
+    pub fn outline(&self, path: &skia::Path, width: f32) -> skia::Path {
+        let points = self.points();
+        let mut outline = skia::Path::new();
+
+        for contour in skia::ContourMeasureIter::new(path, false, None) {
+            let length = contour.length();
+            if length <= 0. {
+                continue;
+            }
+
+            // Uniform samples plus both sides of every corner.
+            let count = (length / WIDTH_PROFILE_STEP)
+                .ceil()
+                .clamp(1., WIDTH_PROFILE_MAX_SAMPLES);
+            let delta = (length * 1e-4).max(1e-3);
+            let mut distances: Vec<f32> = (0..=count as usize)
+                .map(|i| length * i as f32 / count)
+                .collect();
+            for corner in corner_distances(&contour) {
+                distances.extend([corner - delta, corner + delta]);
+            }
+            distances.retain(|distance| (0. ..=length).contains(distance));
+            distances.sort_by(f32::total_cmp);
+
+            let mut left = vec![];
+            let mut right = vec![];
+            for distance in distances {
+                let Some((point, tangent)) = contour.pos_tan(distance) else {
+                    continue;
+                };
+                let normal = skia::Point::new(-tangent.y, tangent.x);
+                let half = width * Self::factor_at(&points, distance / length) / 2.;
+                left.push(point + normal * half);
+                right.push(point - normal * half);
+            }
+            right.reverse();
+
+            if contour.is_closed() {
+                // The rings go in opposite directions, so the inside of the
+                // contour isn't filled.
+                outline.add_poly(&left, true);
+                outline.add_poly(&right, true);
+            } else {
+                left.extend(right);
+                outline.add_poly(&left, true);
+            }
+        }
+
+        outline.simplify().unwrap_or(outline)
+    }
+}
+
+/// Widths of every side of a rect stroke, like the CSS `border-*-width`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct StrokeSides {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+
+impl StrokeSides {
+    pub fn uniform(width: f32) -> Self {
+        Self {
+            top: width,
+            right: width,
+            bottom: width,
+            left: width,
+        }
+    }
+
+    pub fn max(&self) -> f32 {
+        self.top.max(self.right).max(self.bottom).max(self.left)
+    }
+
+    fn scale(&self, factor: f32) -> Self {
+        Self {
+            top: self.top * factor,
+            right: self.right * factor,
+            bottom: self.bottom * factor,
+            left: self.left * factor,
+        }
+    }
+
+    fn union(&self, other: &Self) -> Self {
+        Self {
+            top: self.top.max(other.top),
+            right: self.right.max(other.right),
+            bottom: self.bottom.max(other.bottom),
+            left: self.left.max(other.left),
+        }
+    }
+
+    /// `rect` moved outwards by every side (inwards when negative), with the
+    /// corners following it like the CSS borders.
+    fn offset_rrect(&self, rect: &Rect, corners: Option<&Corners>) -> skia::RRect {
+        let left = rect.left - self.left;
+        let top = rect.top - self.top;
+        let offset_rect = Rect::new(
+            left,
+            top,
+            (rect.right + self.right).max(left),
+            (rect.bottom + self.bottom).max(top),
+        );
+
+        let Some(corners) = corners else {
+            return skia::RRect::new_rect(offset_rect);
+        };
+        let offsets = [
+            (self.left, self.top),
+            (self.right, self.top),
+            (self.right, self.bottom),
+            (self.left, self.bottom),
+        ];
+        let mut radii = *corners;
+        for (radius, (x, y)) in radii.iter_mut().zip(offsets) {
+            if radius.x > 0. && radius.y > 0. {
+                *radius = skia::Point::new((radius.x + x).max(0.), (radius.y + y).max(0.));
+            }
+        }
+        skia::RRect::new_rect_radii(offset_rect, &radii)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -35,10 +367,24 @@ pub enum StrokeKind {
 #[derive(Debug, Clone, PartialEq)]
 pub struct Stroke {
     pub fill: Fill,
+    pub blend_mode: BlendMode,
+    pub opacity: f32,
     pub width: f32,
     pub style: StrokeStyle,
+    pub dash: Option<StrokeDash>,
+    /// Variable width, drawn as an outline. Dashes are ignored then.
+    pub width_profile: Option<StrokeWidthProfile>,
+    /// Widths of the sides of rect and frame strokes, drawn instead of `width`.
+    pub sides: Option<StrokeSides>,
+    /// Strokes without a join take the one of imported SVGs, or miter.
+    pub join: Option<StrokeLineJoin>,
+    /// Miter joins longer than this ratio to the width are beveled.
+    pub miter_limit: f32,
     pub cap_end: Option<StrokeCap>,
     pub cap_start: Option<StrokeCap>,
+    /// Size of the caps relative to the one they have by default.
+    pub cap_start_scale: f32,
+    pub cap_end_scale: f32,
     pub kind: StrokeKind,
 }
 
@@ -52,20 +398,57 @@ impl Stroke {
         }
     }
 
-    pub fn bounds_width(&self, is_open: bool) -> f32 {
+    /// How much the stroke extends out of every side of the shape.
+    pub fn bounds_sides(&self, is_open: bool) -> StrokeSides {
+        let sides = self.sides.unwrap_or(StrokeSides::uniform(self.width));
+        let factor = self
+            .width_profile
+            .as_ref()
+            .map_or(1., StrokeWidthProfile::max_factor);
         match self.render_kind(is_open) {
-            StrokeKind::Inner => 0.,
-            StrokeKind::Center => self.width / 2.,
-            StrokeKind::Outer => self.width,
+            StrokeKind::Inner => StrokeSides::default(),
+            StrokeKind::Center => sides.scale(factor / 2.),
+            StrokeKind::Outer => sides.scale(factor),
         }
     }
 
+    pub fn bounds_width(&self, is_open: bool) -> f32 {
+        self.bounds_sides(is_open).max()
+    }
+
     pub fn max_bounds_width<'a>(strokes: impl Iterator<Item = &'a Stroke>, is_open: bool) -> f32 {
         strokes
             .map(|stroke| stroke.bounds_width(is_open))
             .fold(0.0, f32::max)
     }
 
+    pub fn max_bounds_sides<'a>(
+        strokes: impl Iterator<Item = &'a Stroke>,
+        is_open: bool,
+    ) -> StrokeSides {
+        strokes
+            .map(|stroke| stroke.bounds_sides(is_open))
+            .fold(StrokeSides::default(), |max, sides| max.union(&sides))
+    }
+
+    /// Outer and inner edges of a stroke with different side widths on `rect`.
+    pub fn side_rrects(
+        &self,
+        rect: &Rect,
+        corners: Option<&Corners>,
+    ) -> Option<(skia::RRect, skia::RRect)> {
+        let sides = self.sides?;
+        let (outer, inner) = match self.kind {
+            StrokeKind::Inner => (0., 1.),
+            StrokeKind::Center => (0.5, 0.5),
+            StrokeKind::Outer => (1., 0.),
+        };
+        Some((
+            sides.scale(outer).offset_rrect(rect, corners),
+            sides.scale(-inner).offset_rrect(rect, corners),
+        ))
+    }
+
     pub fn new_center_stroke(
         width: f32,
         style: StrokeStyle,
@@ -74,10 +457,19 @@ impl Stroke {
     ) -> Self {
         Stroke {
             fill: Fill::Solid(SolidColor(skia::Color::TRANSPARENT)),
+            blend_mode: BlendMode::default(),
+            opacity: 1.,
             width,
             style,
+            dash: None,
+            width_profile: None,
+            sides: None,
+            join: None,
+            miter_limit: DEFAULT_MITER_LIMIT,
             cap_end,
             cap_start,
+            cap_start_scale: 1.,
+            cap_end_scale: 1.,
             kind: StrokeKind::Center,
         }
     }
@@ -90,10 +482,19 @@ impl Stroke {
     ) -> Self {
         Stroke {
             fill: Fill::Solid(SolidColor(skia::Color::TRANSPARENT)),
+            blend_mode: BlendMode::default(),
+            opacity: 1.,
             width,
             style,
+            dash: None,
+            width_profile: None,
+            sides: None,
+            join: None,
+            miter_limit: DEFAULT_MITER_LIMIT,
             cap_end,
             cap_start,
+            cap_start_scale: 1.,
+            cap_end_scale: 1.,
             kind: StrokeKind::Inner,
         }
     }
@@ -106,10 +507,19 @@ impl Stroke {
     ) -> Self {
         Stroke {
             fill: Fill::Solid(SolidColor(skia::Color::TRANSPARENT)),
+            blend_mode: BlendMode::default(),
+            opacity: 1.,
             width,
             style,
+            dash: None,
+            width_profile: None,
+            sides: None,
+            join: None,
+            miter_limit: DEFAULT_MITER_LIMIT,
             cap_end,
             cap_start,
+            cap_start_scale: 1.,
+            cap_end_scale: 1.,
             kind: StrokeKind::Outer,
         }
     }
@@ -158,6 +568,23 @@ impl Stroke {
         outer
     }
 
+    /// Join of the stroke, or the one of the imported SVG when it has none.
+    pub fn line_join(&self, svg_attrs: Option<&SvgAttrs>) -> StrokeLineJoin {
+        self.join
+            .or(svg_attrs.map(|svg_attrs| svg_attrs.stroke_linejoin))
+            .unwrap_or_default()
+    }
+
+    /// Sets the join and the miter limit of the stroke on a paint.
+    pub fn apply_join(&self, paint: &mut skia::Paint, svg_attrs: Option<&SvgAttrs>) {
+        paint.set_stroke_join(match self.line_join(svg_attrs) {
+            StrokeLineJoin::Miter => skia::paint::Join::Miter,
+            StrokeLineJoin::Round => skia::paint::Join::Round,
+            StrokeLineJoin::Bevel => skia::paint::Join::Bevel,
+        });
+        paint.set_stroke_miter(self.miter_limit);
+    }
+
     pub fn to_paint(
         &self,
         rect: &Rect,
@@ -181,11 +608,8 @@ impl Stroke {
             if svg_attrs.stroke_linecap == StrokeLineCap::Round {
                 paint.set_stroke_cap(skia::paint::Cap::Round);
             }
-
-            if svg_attrs.stroke_linejoin == StrokeLineJoin::Round {
-                paint.set_stroke_join(skia::paint::Join::Round);
-            }
         }
+        self.apply_join(&mut paint, svg_attrs);
 
         if self.style != StrokeStyle::Solid {
             let path_effect = match self.style {
@@ -217,6 +641,10 @@ impl Stroke {
                     ],
                     0.,
                 ),
+                StrokeStyle::Custom => self.dash.as_ref().and_then(|dash| {
+                    dash.apply_cap(&mut paint);
+                    dash.path_effect()
+                }),
                 _ => None,
             };
             paint.set_path_effect(path_effect);
@@ -271,6 +699,26 @@ impl Stroke {
         paint
     }
 
+    /// Paint of the layer compositing the stroke over the rest of the shape, if
+    /// it doesn't use the default blend mode and opacity.
+    pub fn layer_paint(&self) -> Option<skia::Paint> {
+        if self.blend_mode == BlendMode::default() && self.opacity >= 1. {
+            return None;
+        }
+        let mut paint = skia::Paint::default();
+        set_paint_layer(&mut paint, self.blend_mode, self.opacity);
+        Some(paint)
+    }
+
+    /// Dash pattern adjusted to the corners, which is drawn with
+    /// `StrokeDash::adjusted_path` instead of the path effect of the paint.
+    pub fn adjusted_dash(&self) -> Option<&StrokeDash> {
+        match (self.style, &self.dash) {
+            (StrokeStyle::Custom, Some(dash)) if dash.adjust => Some(dash),
+            _ => None,
+        }
+    }
+
     pub fn is_transparent(&self) -> bool {
         match &self.fill {
             Fill::Solid(SolidColor(color)) => color.a() == 0,
@@ -279,20 +727,101 @@ impl Stroke {
     }
 
     pub fn cap_bounds_margin(&self) -> f32 {
-        cap_margin_for_cap(self.cap_start, self.width)
-            .max(cap_margin_for_cap(self.cap_end, self.width))
+        cap_margin_for_cap(self.cap_start, self.width, self.cap_start_scale).max(
+            cap_margin_for_cap(self.cap_end, self.width, self.cap_end_scale),
+        )
     }
 }
 
-fn cap_margin_for_cap(cap: Option<StrokeCap>, width: f32) -> f32 {
+fn cap_margin_for_cap(cap: Option<StrokeCap>, width: f32, scale: f32) -> f32 {
+    let size = width * scale;
     match cap {
-        Some(StrokeCap::LineArrow)
-        | Some(StrokeCap::TriangleArrow)
+        Some(StrokeCap::TriangleArrow)
         | Some(StrokeCap::SquareMarker)
-        | Some(StrokeCap::DiamondMarker) => width * 4.0,
-        Some(StrokeCap::CircleMarker) => width * 2.0,
-        Some(StrokeCap::Square) => width,
-        Some(StrokeCap::Round) => width * 0.5,
-        _ => 0.0,
+        | Some(StrokeCap::DiamondMarker) => size * 4.0,
+        // Arrows and outlined markers are stroked, so half of the width is outside.
+        Some(StrokeCap::LineArrow)
+        | Some(StrokeCap::ReversedArrow)
+        | Some(StrokeCap::HalfArrow)
+        | Some(StrokeCap::DiamondOutline) => size * 4.0 + width * 0.5,
+        // The miters of the 45° corners of the triangle go further.
+        Some(StrokeCap::TriangleOutline) => size * 4.0 + width * 0.5 / FRAC_PI_8.tan(),
+        Some(StrokeCap::CircleOutline) | Some(StrokeCap::Bar) => size * 2.0 + width * 0.5,
+        Some(StrokeCap::CircleMarker) => size * 2.0,
+        Some(StrokeCap::Square) => size,
+        Some(StrokeCap::Round) => size * 0.5,
+        None => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adjusted_dash_path() {
+        let dash = StrokeDash {
+            intervals: vec![10., 10.],
+            adjust: true,
+            ..Default::default()
+        };
+        let path = skia::Path::rect(skia::Rect::from_xywh(0., 0., 100., 100.), None);
+        let dashed = dash.adjusted_path(&path).unwrap();
+
+        // Four dashes along every side, plus one around every corner.
+        let lengths: Vec<f32> = skia::ContourMeasureIter::new(&dashed, false, None)
+            .map(|contour| contour.length())
+            .collect();
+        assert_eq!(lengths.len(), 20);
+        assert!(lengths.iter().all(|length| (length - 10.).abs() < 0.01));
+    }
+
+    #[test]
+    fn test_line_join() {
+        let svg_attrs = SvgAttrs {
+            stroke_linejoin: StrokeLineJoin::Round,
+            ..Default::default()
+        };
+        let mut stroke = Stroke::new_center_stroke(1., StrokeStyle::Solid, None, None);
+        assert_eq!(stroke.line_join(None), StrokeLineJoin::Miter);
+        assert_eq!(stroke.line_join(Some(&svg_attrs)), StrokeLineJoin::Round);
+
+        stroke.join = Some(StrokeLineJoin::Miter);
+        assert_eq!(stroke.line_join(Some(&svg_attrs)), StrokeLineJoin::Miter);
+    }
+
+    #[test]
+    fn test_stroke_side_rrects() {
+        let mut stroke = Stroke::new_outer_stroke(1., StrokeStyle::Solid, None, None);
+        stroke.sides = Some(StrokeSides {
+            top: 1.,
+            right: 2.,
+            bottom: 3.,
+            left: 4.,
+        });
+
+        let rect = Rect::from_xywh(0., 0., 100., 100.);
+        let (outer, inner) = stroke.side_rrects(&rect, None).unwrap();
+        assert_eq!(*outer.rect(), Rect::new(-4., -1., 102., 103.));
+        assert_eq!(*inner.rect(), rect);
+
+        let sides = stroke.bounds_sides(false);
+        assert_eq!(sides.left, 4.);
+        assert_eq!(sides.bottom, 3.);
+    }
+
+    #[test]
+    fn test_width_profile_outline() {
+        let profile = StrokeWidthProfile::Points(vec![(1., 2.), (0., 0.)]);
+        assert_eq!(profile.max_factor(), 2.);
+
+        let mut path = skia::Path::new();
+        path.move_to((0., 0.));
+        path.line_to((100., 0.));
+        let bounds = profile.outline(&path, 10.).bounds().to_owned();
+
+        // Tapers from nothing at the start to twice the width at the end.
+        assert!((bounds.height() - 20.).abs() < 0.01);
+        assert!((bounds.width() - 100.).abs() < 0.01);
     }
 }
@@ -1,4 +1,5 @@
 use crate::shapes::text::TextContent;
+use crate::shapes::Fill;
 use skia_safe::{
     self as skia, textlayout::Paragraph as SkiaParagraph, FontMetrics, Point, Rect, TextBlob,
 };
@@ -8,8 +9,17 @@ use crate::{with_state_mut, STATE};
 
 pub struct TextPaths(TextContent);
 
-// Note: This class is not being currently used.
-// It's an example of how to convert texts to paths
+/// A span of text with its font and fills, positioned at its baseline.
+pub struct TextRun<'a> {
+    pub text: String,
+    pub font: skia::Font,
+    pub fills: &'a [Fill],
+    pub decoration: skia::textlayout::TextDecoration,
+    pub position: Point,
+}
+
+// Note: This class is only used to convert texts to paths and by the SVG
+// export, the renderer draws the paragraphs directly.
 #[allow(dead_code)]
 impl TextPaths {
     pub fn new(text_content: TextContent) -> Self {
@@ -18,10 +28,97 @@ impl TextPaths {
 
     pub fn get_paths(&self, antialias: bool) -> Vec<(skia::Path, skia::Paint)> {
         let mut paths = Vec::new();
+
+        self.layout_spans(|span_text, font, offset, style_metric, _| {
+            // 4. Get the path for each text span
+            if let Some((text_path, paint)) = self.generate_text_path(
+                span_text,
+                font,
+                offset.x,
+                offset.y,
+                style_metric,
+                antialias,
+            ) {
+                paths.push((text_path, paint));
+                true
+            } else {
+                false
+            }
+        });
+
+        paths
+    }
+
+    /// Like `get_paths`, but with the fills of the span each path comes from
+    /// instead of their merged paint.
+    pub fn get_fill_paths(&self, antialias: bool) -> Vec<(skia::Path, &[Fill])> {
+        let mut paths = Vec::new();
+
+        self.layout_spans(|span_text, font, offset, style_metric, fills| {
+            match self.generate_text_path(
+                span_text,
+                font,
+                offset.x,
+                offset.y,
+                style_metric,
+                antialias,
+            ) {
+                Some((text_path, _)) => {
+                    paths.push((text_path, fills));
+                    true
+                }
+                None => false,
+            }
+        });
+
+        paths
+    }
+
+    /// Returns the text spans laid out in the same positions as `get_paths`, so
+    /// they can be written as text instead of outlines.
+    pub fn get_runs(&self) -> Vec<TextRun<'_>> {
+        let mut runs = Vec::new();
+
+        self.layout_spans(|span_text, font, offset, style_metric, fills| {
+            if span_text.is_empty() {
+                return false;
+            }
+
+            runs.push(TextRun {
+                text: span_text.to_string(),
+                font: font.clone(),
+                fills,
+                decoration: style_metric.text_style.decoration().ty,
+                position: offset,
+            });
+            true
+        });
+
+        runs
+    }
+
+    /// Walks the spans of every laid out line. `f` receives the text of the span,
+    /// its font, its offset, its style and the fills of the text span it comes
+    /// from, and returns whether the span was drawn so the next one is placed
+    /// after it.
+    fn layout_spans<'a>(
+        &'a self,
+        mut f: impl FnMut(&str, &skia::Font, Point, &skia::textlayout::StyleMetrics, &'a [Fill]) -> bool,
+    ) {
         let mut offset_y = self.bounds.y();
         let mut paragraph_builders = self.0.paragraph_builder_group_from_text(None);
 
-        for paragraphs in paragraph_builders.iter_mut() {
+        for (paragraphs, paragraph) in paragraph_builders.iter_mut().zip(self.0.paragraphs()) {
+            // Char index where each text span of the paragraph ends
+            let span_ends: Vec<usize> = paragraph
+                .children()
+                .iter()
+                .scan(0, |end, span| {
+                    *end += span.apply_text_transform().chars().count();
+                    Some(*end)
+                })
+                .collect();
+
             for paragraph_builder in paragraphs.iter_mut() {
                 // 1. Get paragraph and set the width layout
                 let mut skia_paragraph = paragraph_builder.build();
@@ -60,21 +157,19 @@ impl TextPaths {
 
                         let font = skia_paragraph.get_font_at(*start_index);
 
-                        let blob_offset_x = self.bounds.x() + line_metrics.left as f32 + offset_x;
-                        let blob_offset_y = line_offset_y;
-
-                        // 4. Get the path for each text span
-                        if let Some((text_path, paint)) = self.generate_text_path(
-                            span_text,
-                            &font,
-                            blob_offset_x,
-                            blob_offset_y,
-                            style_metric,
-                            antialias,
-                        ) {
+                        let blob_offset = Point::new(
+                            self.bounds.x() + line_metrics.left as f32 + offset_x,
+                            line_offset_y,
+                        );
+
+                        let fills = span_ends
+                            .iter()
+                            .position(|span_end| *start_index < *span_end)
+                            .map_or(&[][..], |index| paragraph.children()[index].fills());
+
+                        if f(span_text, &font, blob_offset, style_metric, fills) {
                             let text_width = font.measure_text(span_text, None).0;
                             offset_x += text_width;
-                            paths.push((text_path, paint));
                         }
                     }
                     line_offset_y = offset_y + line_baseline;
@@ -82,7 +177,6 @@ impl TextPaths {
                 offset_y += skia_paragraph.height();
             }
         }
-        paths
     }
 
     fn generate_text_path(
@@ -6,7 +6,7 @@ mod text_editor;
 pub use shapes_pool::{ShapesPool, ShapesPoolMutRef, ShapesPoolRef};
 pub use text_editor::*;
 
-use crate::render::RenderState;
+use crate::render::{self, RenderBackend, RenderState};
 use crate::shapes::Shape;
 use crate::tiles;
 use crate::uuid::Uuid;
@@ -27,10 +27,82 @@ pub(crate) struct State {
     pub saved_shapes: Option<ShapesPool>,
 }
 
+/// The state shared by the exported functions, see `with_state_mut!` and friends.
+pub(crate) static mut STATE: Option<Box<State>> = None;
+
+#[macro_export]
+macro_rules! with_state_mut {
+    ($state:ident, $block:block) => {{
+        let $state = unsafe {
+            #[allow(static_mut_refs)]
+            STATE.as_mut()
+        }
+        .expect("Got an invalid state pointer");
+        $block
+    }};
+}
+
+#[macro_export]
+macro_rules! with_state {
+    ($state:ident, $block:block) => {{
+        let $state = unsafe {
+            #[allow(static_mut_refs)]
+            STATE.as_ref()
+        }
+        .expect("Got an invalid state pointer");
+        $block
+    }};
+}
+
+#[macro_export]
+macro_rules! with_current_shape_mut {
+    ($state:ident, |$shape:ident: &mut Shape| $block:block) => {
+        let $state = unsafe {
+            #[allow(static_mut_refs)]
+            STATE.as_mut()
+        }
+        .expect("Got an invalid state pointer");
+
+        $state.touch_current();
+
+        if let Some($shape) = $state.current_shape_mut() {
+            $block
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! with_current_shape {
+    ($state:ident, |$shape:ident: &Shape| $block:block) => {
+        let $state = unsafe {
+            #[allow(static_mut_refs)]
+            STATE.as_ref()
+        }
+        .expect("Got an invalid state pointer");
+        if let Some($shape) = $state.current_shape() {
+            $block
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! with_state_mut_current_shape {
+    ($state:ident, |$shape:ident: &Shape| $block:block) => {
+        let $state = unsafe {
+            #[allow(static_mut_refs)]
+            STATE.as_mut()
+        }
+        .expect("Got an invalid state pointer");
+        if let Some($shape) = $state.current_shape() {
+            $block
+        }
+    };
+}
+
 impl State {
-    pub fn new(width: i32, height: i32) -> Self {
+    pub fn new(width: i32, height: i32, backend: RenderBackend) -> Self {
         State {
-            render_state: RenderState::new(width, height),
+            render_state: RenderState::new(width, height, backend),
             text_editor_state: TextEditorState::new(),
             current_id: None,
             current_browser: 0,
@@ -99,6 +171,36 @@ impl State {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_shape_image(
+        &mut self,
+        id: &Uuid,
+        scale: f32,
+        format: skia::EncodedImageFormat,
+        quality: u32,
+        background: Option<skia::Color>,
+        timestamp: i32,
+    ) -> Result<Vec<u8>, String> {
+        render::export::render_shape_image(
+            &mut self.render_state,
+            &self.shapes,
+            id,
+            scale,
+            format,
+            quality,
+            background,
+            timestamp,
+        )
+    }
+
+    pub fn render_frames_pdf(&mut self, ids: &[Uuid]) -> Result<Vec<u8>, String> {
+        render::pdf::render_frames(&mut self.render_state, &self.shapes, ids)
+    }
+
+    pub fn render_shape_svg(&mut self, id: &Uuid, text_as_paths: bool) -> Result<String, String> {
+        render::svg::render_shape_svg(&mut self.render_state, &self.shapes, id, text_as_paths)
+    }
+
     pub fn start_render_loop(&mut self, timestamp: i32) -> Result<(), String> {
         // If zoom changed, we MUST rebuild the tile index before using it.
         // Otherwise, the index will have tiles from the old zoom level, causing visible
@@ -187,6 +187,30 @@ impl ShapesPoolImpl {
         self.shapes.iter_mut()
     }
 
+    /// The shapes of the pool as they were set, without modifiers nor structure
+    /// changes applied.
+    pub fn base_shapes(&self) -> impl Iterator<Item = &Shape> {
+        self.shapes[..self.counter].iter()
+    }
+
+    pub fn modifiers(&self) -> impl Iterator<Item = (Uuid, &skia::Matrix)> {
+        self.modifiers
+            .iter()
+            .map(|(idx, matrix)| (self.shapes[*idx].id, matrix))
+    }
+
+    pub fn structure(&self) -> impl Iterator<Item = (Uuid, &Vec<StructureEntry>)> {
+        self.structure
+            .iter()
+            .map(|(idx, entries)| (self.shapes[*idx].id, entries))
+    }
+
+    pub fn scale_content(&self) -> impl Iterator<Item = (Uuid, f32)> + '_ {
+        self.scale_content
+            .iter()
+            .map(|(idx, value)| (self.shapes[*idx].id, *value))
+    }
+
     fn clean_shape_cache(&mut self) {
         self.modified_shape_cache.clear()
     }
@@ -0,0 +1,265 @@
+// Visual regression tests: the fixture scenes are rendered with the raster
+// backend and compared with the golden images in `visual_tests/golden`. See
+// docs/visual_regression_tests.md.
+mod fixtures;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use skia_safe::{self as skia, images, AlphaType, ColorType, Data, EncodedImageFormat, ImageInfo};
+
+use crate::performance;
+use crate::render::{export, RenderBackend};
+use crate::state::State;
+use crate::{with_state_mut, STATE};
+
+/// Per-pixel color distance, from 0 to 1, below which two pixels are
+/// considered equal. It hides antialiasing and rounding differences between
+/// platforms.
+const PIXEL_THRESHOLD: f32 = 0.1;
+
+/// Share of pixels that can differ before the test fails.
+const MAX_DIFF_RATIO: f32 = 0.001;
+
+/// Maximum `color_delta` of two YIQ colors (black against white).
+const MAX_YIQ_DELTA: f32 = 35215.0;
+
+struct Bitmap {
+    width: i32,
+    height: i32,
+    // RGBA, unpremultiplied.
+    pixels: Vec<u8>,
+}
+
+impl Bitmap {
+    fn info(width: i32, height: i32) -> ImageInfo {
+        ImageInfo::new(
+            (width, height),
+            ColorType::RGBA8888,
+            AlphaType::Unpremul,
+            None,
+        )
+    }
+
+    fn from_image(image: &skia::Image) -> Result<Self, String> {
+        let (width, height) = (image.width(), image.height());
+        let mut pixels = vec![0; (width * height * 4) as usize];
+        if !image.read_pixels(
+            &Self::info(width, height),
+            &mut pixels,
+            (width * 4) as usize,
+            (0, 0),
+            skia::image::CachingHint::Allow,
+        ) {
+            return Err("Error reading the image pixels".to_string());
+        }
+
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    fn read(path: &Path) -> Result<Self, String> {
+        let bytes =
+            fs::read(path).map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+        let image = skia::Image::from_encoded(Data::new_copy(&bytes))
+            .ok_or_else(|| format!("Error decoding {}", path.display()))?;
+        Self::from_image(&image)
+    }
+
+    fn write(&self, path: &Path) -> Result<(), String> {
+        let image = images::raster_from_data(
+            &Self::info(self.width, self.height),
+            Data::new_copy(&self.pixels),
+            (self.width * 4) as usize,
+        )
+        .ok_or("Error creating the image")?;
+        let data = image
+            .encode(None, EncodedImageFormat::PNG, 100)
+            .ok_or("Error encoding the image")?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Error creating {}: {}", dir.display(), e))?;
+        }
+        fs::write(path, data.as_bytes())
+            .map_err(|e| format!("Error writing {}: {}", path.display(), e))
+    }
+
+    fn pixel(&self, index: usize) -> [u8; 4] {
+        let offset = index * 4;
+        let mut pixel = [0; 4];
+        pixel.copy_from_slice(&self.pixels[offset..offset + 4]);
+        pixel
+    }
+}
+
+/// Result of comparing two bitmaps of the same size.
+struct Comparison {
+    different: usize,
+    total: usize,
+    diff: Bitmap,
+}
+
+/// Compares the images like a person would: colors are blended over white and
+/// compared by their perceived difference (in YIQ space), so the threshold
+/// hides what isn't noticeable. The diff image shows the expected one faded,
+/// with the different pixels in red.
+fn compare(expected: &Bitmap, actual: &Bitmap) -> Comparison {
+    let total = (expected.width * expected.height) as usize;
+    let max_delta = MAX_YIQ_DELTA * PIXEL_THRESHOLD * PIXEL_THRESHOLD;
+    let mut different = 0;
+    let mut pixels = Vec::with_capacity(total * 4);
+
+    for index in 0..total {
+        let expected_pixel = expected.pixel(index);
+        if color_delta(expected_pixel, actual.pixel(index)) > max_delta {
+            different += 1;
+            pixels.extend_from_slice(&[255, 0, 0, 255]);
+        } else {
+            let y = yiq(blend_white(expected_pixel)).0;
+            let faded = (255.0 - (255.0 - y) * 0.1) as u8;
+            pixels.extend_from_slice(&[faded, faded, faded, 255]);
+        }
+    }
+
+    Comparison {
+        different,
+        total,
+        diff: Bitmap {
+            width: expected.width,
+            height: expected.height,
+            pixels,
+        },
+    }
+}
+
+fn blend_white([r, g, b, a]: [u8; 4]) -> [f32; 3] {
+    let alpha = a as f32 / 255.0;
+    [r, g, b].map(|c| 255.0 + (c as f32 - 255.0) * alpha)
+}
+
+fn yiq([r, g, b]: [f32; 3]) -> (f32, f32, f32) {
+    (
+        r * 0.29889531 + g * 0.58662247 + b * 0.11448223,
+        r * 0.59597799 - g * 0.2741761 - b * 0.32180189,
+        r * 0.21147017 - g * 0.52261711 + b * 0.31114694,
+    )
+}
+
+fn color_delta(a: [u8; 4], b: [u8; 4]) -> f32 {
+    if a == b {
+        return 0.0;
+    }
+
+    let (y1, i1, q1) = yiq(blend_white(a));
+    let (y2, i2, q2) = yiq(blend_white(b));
+    let (y, i, q) = (y1 - y2, i1 - i2, q1 - q2);
+    0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q
+}
+
+fn manifest_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Renders the fixture and compares it with its golden image. When
+/// `UPDATE_GOLDENS` is set, the render is stored as the new golden image
+/// instead. A missing golden image is a failure.
+fn check(state: &mut State, fixture: fixtures::Fixture) -> Result<(), String> {
+    state.load_scene(fixture.scene)?;
+
+    let mut surface = export::render_shape_surface(
+        &mut state.render_state,
+        &state.shapes,
+        &fixture.frame,
+        1.0,
+        None,
+        performance::get_time(),
+    )?;
+    let actual = Bitmap::from_image(&surface.image_snapshot())?;
+
+    let golden = manifest_dir()
+        .join("visual_tests/golden")
+        .join(format!("{}.png", fixture.name));
+    if env::var_os("UPDATE_GOLDENS").is_some() {
+        return actual.write(&golden);
+    }
+    if !golden.exists() {
+        return Err(format!(
+            "missing golden image {}, write it with UPDATE_GOLDENS=1",
+            golden.display()
+        ));
+    }
+
+    let expected = Bitmap::read(&golden)?;
+    let output = manifest_dir().join("target/visual_tests");
+    let actual_path = output.join(format!("{}.actual.png", fixture.name));
+    let diff_path = output.join(format!("{}.diff.png", fixture.name));
+
+    if (expected.width, expected.height) != (actual.width, actual.height) {
+        actual.write(&actual_path)?;
+        return Err(format!(
+            "size is {}x{}, expected {}x{} (see {})",
+            actual.width,
+            actual.height,
+            expected.width,
+            expected.height,
+            actual_path.display()
+        ));
+    }
+
+    let comparison = compare(&expected, &actual);
+    if comparison.different as f32 > comparison.total as f32 * MAX_DIFF_RATIO {
+        actual.write(&actual_path)?;
+        comparison.diff.write(&diff_path)?;
+        return Err(format!(
+            "{} of {} pixels are different (see {})",
+            comparison.different,
+            comparison.total,
+            diff_path.display()
+        ));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_visual_regression() {
+    // The renderer reads the fonts and images from the global state, so the
+    // fixtures are rendered one after the other in a single test.
+    let state_box = Box::new(State::new(1, 1, RenderBackend::raster()));
+    unsafe {
+        STATE = Some(state_box);
+    }
+
+    let failures: Vec<String> = fixtures::all()
+        .into_iter()
+        .filter_map(|fixture| {
+            let name = fixture.name;
+            with_state_mut!(state, {
+                check(state, fixture)
+                    .err()
+                    .map(|error| format!("{}: {}", name, error))
+            })
+        })
+        .collect();
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn test_compare_ignores_small_differences() {
+    let bitmap = |pixel: [u8; 4]| Bitmap {
+        width: 1,
+        height: 1,
+        pixels: pixel.to_vec(),
+    };
+
+    let white = bitmap([255, 255, 255, 255]);
+    assert_eq!(compare(&white, &bitmap([252, 253, 255, 255])).different, 0);
+    assert_eq!(compare(&white, &bitmap([0, 0, 0, 0])).different, 0);
+    assert_eq!(compare(&white, &bitmap([255, 0, 0, 255])).different, 1);
+}
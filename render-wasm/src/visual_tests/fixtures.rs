@@ -0,0 +1,560 @@
+use skia_safe as skia;
+
+use crate::math::{Point, Rect};
+use crate::scene::Scene;
+use crate::shapes::{
+    BlendMode, Blur, BlurType, Fill, FillLayer, FontFamily, FontStyle, Frame, Gradient,
+    GradientInterpolation, Group, GrowType, HueInterpolation, MeshGradient, MeshPatch, Paragraph,
+    Path, Rect as RectShape, Segment, Shadow, ShadowStyle, Shape, SolidColor, Stroke, StrokeCap,
+    StrokeStyle, TextAlign, TextContent, TextDecoration, TextDirection, TextSpan, Type,
+};
+use crate::tiles::TILE_SIZE;
+use crate::uuid::Uuid;
+
+/// A scene with a single frame that is rendered and compared with
+/// `visual_tests/golden/<name>.png`.
+pub struct Fixture {
+    pub name: &'static str,
+    pub scene: Scene,
+    pub frame: Uuid,
+}
+
+const FRAME_ID: u64 = 1;
+const FRAME_SIZE: f32 = 200.0;
+
+pub fn all() -> Vec<Fixture> {
+    vec![
+        fills(),
+        angular_gradients(),
+        mesh_gradients(),
+        gradient_interpolation(),
+        fill_layers(),
+        strokes(),
+        shadows(),
+        background_blur_tile_edge(),
+        text(),
+        masks(),
+    ]
+}
+
+fn fills() -> Fixture {
+    let mut solid_rect = shape(10, rect(), (20.0, 20.0, 90.0, 90.0));
+    solid_rect.set_corners((12.0, 12.0, 12.0, 12.0));
+    solid_rect.add_fill(solid(0xff31efb8));
+
+    let mut linear = shape(11, Type::Circle, (110.0, 20.0, 180.0, 90.0));
+    linear.add_fill(Fill::LinearGradient(Gradient::new(
+        (0.0, 0.0),
+        (1.0, 1.0),
+        255,
+        1.0,
+        &[
+            (skia::Color::new(0xffff3277), 0.0),
+            (skia::Color::new(0xff3177ff), 1.0),
+        ],
+    )));
+
+    let mut radial = shape(12, rect(), (20.0, 110.0, 90.0, 180.0));
+    radial.add_fill(Fill::RadialGradient(Gradient::new(
+        (0.5, 0.5),
+        (0.5, 1.0),
+        255,
+        1.0,
+        &[
+            (skia::Color::new(0xffffffff), 0.0),
+            (skia::Color::new(0xff7a3fe0), 1.0),
+        ],
+    )));
+
+    // Translucent fills stacked over an opaque one.
+    let mut stacked = shape(13, rect(), (110.0, 110.0, 180.0, 180.0));
+    stacked.add_fill(solid(0x80ff0000));
+    stacked.add_fill(solid(0xff0000ff));
+    stacked.set_opacity(0.75);
+
+    fixture("fills", vec![solid_rect, linear, radial, stacked], vec![])
+}
+
+fn angular_gradients() -> Fixture {
+    let wheel_stops: Vec<(skia::Color, f32)> = [
+        0xffff0000, 0xffffff00, 0xff00ff00, 0xff00ffff, 0xff0000ff, 0xffff00ff, 0xffff0000,
+    ]
+    .iter()
+    .enumerate()
+    .map(|(i, argb)| (skia::Color::new(*argb), i as f32 / 6.0))
+    .collect();
+
+    let mut wheel = shape(10, Type::Circle, (20.0, 20.0, 180.0, 180.0));
+    wheel.add_fill(Fill::AngularGradient(Gradient::new(
+        (0.5, 0.5),
+        (0.5, 0.0),
+        255,
+        1.0,
+        &wheel_stops,
+    )));
+
+    // Angular gradients in strokes too, centered on the start point.
+    let mut ring = Stroke::new_outer_stroke(6.0, StrokeStyle::Solid, None, None);
+    ring.fill = Fill::AngularGradient(Gradient::new(
+        (0.5, 0.5),
+        (1.0, 0.5),
+        255,
+        1.0,
+        &[
+            (skia::Color::new(0xff000000), 0.0),
+            (skia::Color::new(0xffffffff), 1.0),
+        ],
+    ));
+    wheel.add_stroke(ring);
+
+    // Hard stops make a pie chart, with the center off the middle of the shape.
+    let mut pie = shape(11, rect(), (60.0, 60.0, 140.0, 140.0));
+    pie.add_fill(Fill::AngularGradient(Gradient::new(
+        (0.25, 0.25),
+        (1.0, 0.25),
+        200,
+        1.0,
+        &[
+            (skia::Color::new(0xffffffff), 0.0),
+            (skia::Color::new(0xffffffff), 0.3),
+            (skia::Color::new(0xff000000), 0.3),
+            (skia::Color::new(0xff000000), 1.0),
+        ],
+    )));
+
+    fixture("angular_gradients", vec![pie, wheel], vec![])
+}
+
+fn mesh_gradients() -> Fixture {
+    // Two patches sharing a curved edge, with the colors matching on it.
+    let colors = |argb: [u32; 4]| argb.map(skia::Color::new);
+    let mesh = MeshGradient::new(
+        255,
+        vec![
+            MeshPatch {
+                cubics: [
+                    (0.0, 0.0),
+                    (0.2, 0.0),
+                    (0.3, 0.0),
+                    (0.5, 0.0),
+                    (0.8, 0.33),
+                    (0.2, 0.66),
+                    (0.5, 1.0),
+                    (0.3, 1.0),
+                    (0.2, 1.0),
+                    (0.0, 1.0),
+                    (0.0, 0.66),
+                    (0.0, 0.33),
+                ],
+                colors: colors([0xffff3277, 0xff31efb8, 0xff3177ff, 0xffffffff]),
+            },
+            MeshPatch {
+                cubics: [
+                    (0.5, 0.0),
+                    (0.7, 0.0),
+                    (0.8, 0.0),
+                    (1.0, 0.0),
+                    (1.0, 0.33),
+                    (1.0, 0.66),
+                    (1.0, 1.0),
+                    (0.8, 1.0),
+                    (0.7, 1.0),
+                    (0.5, 1.0),
+                    (0.2, 0.66),
+                    (0.8, 0.33),
+                ],
+                colors: colors([0xff31efb8, 0xff7a3fe0, 0xff000000, 0xff3177ff]),
+            },
+        ],
+    );
+
+    let mut card = shape(10, rect(), (20.0, 20.0, 180.0, 110.0));
+    card.set_corners((16.0, 16.0, 16.0, 16.0));
+    card.add_fill(Fill::MeshGradient(mesh.clone()));
+
+    let family = FontFamily::new(Uuid::nil(), 400, FontStyle::Normal);
+    let bounds = Rect::from_ltrb(20.0, 120.0, 180.0, 180.0);
+    let mut content = TextContent::new(bounds, GrowType::Fixed);
+    content.add_paragraph(Paragraph::new(
+        TextAlign::Center,
+        TextDirection::LTR,
+        None,
+        None,
+        1.2,
+        0.0,
+        vec![TextSpan::new(
+            "Mesh".to_string(),
+            family,
+            40.0,
+            1.2,
+            0.0,
+            None,
+            None,
+            TextDirection::LTR,
+            400,
+            Uuid::nil(),
+            vec![Fill::MeshGradient(mesh)],
+        )],
+    ));
+    let text = shape(11, Type::Text(content), (20.0, 120.0, 180.0, 180.0));
+
+    fixture("mesh_gradients", vec![card, text], vec![])
+}
+
+/// The same blue to yellow gradient interpolated in every color space, top to
+/// bottom.
+fn gradient_interpolation() -> Fixture {
+    let spaces = [
+        (GradientInterpolation::Srgb, HueInterpolation::Shorter),
+        (GradientInterpolation::LinearSrgb, HueInterpolation::Shorter),
+        (GradientInterpolation::Oklab, HueInterpolation::Shorter),
+        (GradientInterpolation::Oklch, HueInterpolation::Shorter),
+        (GradientInterpolation::Oklch, HueInterpolation::Longer),
+    ];
+
+    let bands = spaces
+        .iter()
+        .enumerate()
+        .map(|(i, (interpolation, hue_interpolation))| {
+            let top = 10.0 + i as f32 * 37.0;
+            let mut band = shape(10 + i as u64, rect(), (10.0, top, 190.0, top + 32.0));
+            let mut gradient = Gradient::new(
+                (0.0, 0.5),
+                (1.0, 0.5),
+                255,
+                1.0,
+                &[
+                    (skia::Color::new(0xff0000ff), 0.0),
+                    (skia::Color::new(0xffffff00), 1.0),
+                ],
+            );
+            gradient.set_interpolation(*interpolation, *hue_interpolation);
+            gradient.set_dither(true);
+            band.add_fill(Fill::LinearGradient(gradient));
+            band
+        })
+        .collect();
+
+    fixture("gradient_interpolation", bands, vec![])
+}
+
+/// Fills and strokes with their own blend modes and opacities, composited over
+/// the layers below them.
+fn fill_layers() -> Fixture {
+    let mut multiply = shape(10, rect(), (20.0, 20.0, 180.0, 90.0));
+    multiply.add_fill(FillLayer::new(
+        Fill::LinearGradient(Gradient::new(
+            (0.0, 0.5),
+            (1.0, 0.5),
+            255,
+            1.0,
+            &[
+                (skia::Color::new(0xffffffff), 0.0),
+                (skia::Color::new(0xff3177ff), 1.0),
+            ],
+        )),
+        BlendMode(skia::BlendMode::Multiply),
+        1.0,
+    ));
+    multiply.add_fill(solid(0xffff3277));
+
+    let mut translucent = shape(11, Type::Circle, (20.0, 110.0, 90.0, 180.0));
+    translucent.add_fill(FillLayer::new(
+        solid(0xff31efb8),
+        BlendMode(skia::BlendMode::Screen),
+        0.5,
+    ));
+    translucent.add_fill(solid(0xff7a3fe0));
+
+    let mut outlined = shape(12, rect(), (110.0, 110.0, 180.0, 180.0));
+    outlined.add_fill(solid(0xffffe600));
+    let mut outline = stroke(
+        Stroke::new_center_stroke(12.0, StrokeStyle::Solid, None, None),
+        0xff3177ff,
+    );
+    outline.blend_mode = BlendMode(skia::BlendMode::Difference);
+    outline.opacity = 0.75;
+    outlined.add_stroke(outline);
+
+    fixture("fill_layers", vec![multiply, translucent, outlined], vec![])
+}
+
+fn strokes() -> Fixture {
+    let mut inner = shape(10, rect(), (20.0, 20.0, 90.0, 90.0));
+    inner.add_fill(solid(0xffe0e0e0));
+    inner.add_stroke(stroke(
+        Stroke::new_inner_stroke(8.0, StrokeStyle::Solid, None, None),
+        0xff000000,
+    ));
+
+    let mut outer = shape(11, Type::Circle, (110.0, 20.0, 180.0, 90.0));
+    outer.add_stroke(stroke(
+        Stroke::new_outer_stroke(6.0, StrokeStyle::Dashed, None, None),
+        0xffff3277,
+    ));
+
+    let mut dotted = shape(12, rect(), (20.0, 110.0, 90.0, 180.0));
+    dotted.add_stroke(stroke(
+        Stroke::new_center_stroke(4.0, StrokeStyle::Dotted, None, None),
+        0xff3177ff,
+    ));
+
+    let mut arrow = shape(
+        13,
+        Type::Path(Path::new(vec![
+            Segment::MoveTo(Point::new(110.0, 170.0)),
+            Segment::LineTo(Point::new(180.0, 120.0)),
+        ])),
+        (110.0, 120.0, 180.0, 170.0),
+    );
+    arrow.add_stroke(stroke(
+        Stroke::new_center_stroke(
+            3.0,
+            StrokeStyle::Solid,
+            Some(StrokeCap::CircleMarker),
+            Some(StrokeCap::TriangleArrow),
+        ),
+        0xff000000,
+    ));
+
+    fixture("strokes", vec![inner, outer, dotted, arrow], vec![])
+}
+
+fn shadows() -> Fixture {
+    let mut drop = shape(10, rect(), (30.0, 30.0, 90.0, 90.0));
+    drop.add_fill(solid(0xffffffff));
+    drop.add_shadow(Shadow::new(
+        skia::Color::new(0x80000000),
+        8.0,
+        2.0,
+        (6.0, 6.0),
+        ShadowStyle::Drop,
+        false,
+    ));
+
+    let mut inner = shape(11, Type::Circle, (110.0, 30.0, 170.0, 90.0));
+    inner.add_fill(solid(0xff31efb8));
+    inner.add_shadow(Shadow::new(
+        skia::Color::new(0xcc000000),
+        6.0,
+        0.0,
+        (4.0, 4.0),
+        ShadowStyle::Inner,
+        false,
+    ));
+
+    let mut blurred = shape(12, rect(), (30.0, 110.0, 170.0, 170.0));
+    blurred.add_fill(solid(0xff3177ff));
+    blurred.set_blur(Some(Blur::new(BlurType::LayerBlur, false, 6.0)));
+
+    fixture("shadows", vec![drop, inner, blurred], vec![])
+}
+
+/// A background blur across the edge between the first two tiles, over
+/// stripes on both sides of it.
+fn background_blur_tile_edge() -> Fixture {
+    let left = TILE_SIZE - FRAME_SIZE / 2.0;
+    let colors = [0xffff3b30, 0xff3177ff, 0xff31efb8, 0xffffcc00];
+
+    let mut children: Vec<Shape> = colors
+        .iter()
+        .cycle()
+        .take(10)
+        .enumerate()
+        .map(|(i, argb)| {
+            let x = left + i as f32 * 20.0;
+            let mut stripe = shape(10 + i as u64, rect(), (x, 0.0, x + 10.0, FRAME_SIZE));
+            stripe.add_fill(solid(*argb));
+            stripe
+        })
+        .collect();
+
+    let mut glass = shape(
+        20,
+        rect(),
+        (left + 30.0, 50.0, left + FRAME_SIZE - 30.0, 150.0),
+    );
+    glass.add_fill(solid(0x40ffffff));
+    glass.set_blur(Some(Blur::new(BlurType::BackgroundBlur, false, 12.0)));
+    children.push(glass);
+
+    fixture_at(
+        "background_blur_tile_edge",
+        (left, 0.0, left + FRAME_SIZE, FRAME_SIZE),
+        children,
+        vec![],
+    )
+}
+
+/// Texts use the default font, which is built into the renderer.
+fn text() -> Fixture {
+    let family = FontFamily::new(Uuid::nil(), 400, FontStyle::Normal);
+    let span = |text: &str, size: f32, color: u32| {
+        TextSpan::new(
+            text.to_string(),
+            family,
+            size,
+            1.2,
+            0.0,
+            None,
+            None,
+            TextDirection::LTR,
+            400,
+            Uuid::nil(),
+            vec![solid(color)],
+        )
+    };
+
+    let bounds = Rect::from_ltrb(20.0, 20.0, 180.0, 180.0);
+    let mut content = TextContent::new(bounds, GrowType::Fixed);
+    content.add_paragraph(Paragraph::new(
+        TextAlign::Left,
+        TextDirection::LTR,
+        None,
+        None,
+        1.2,
+        0.0,
+        vec![
+            span("Penpot ", 28.0, 0xff000000),
+            span("render", 28.0, 0xffff3277),
+        ],
+    ));
+    content.add_paragraph(Paragraph::new(
+        TextAlign::Center,
+        TextDirection::LTR,
+        Some(TextDecoration::UNDERLINE),
+        None,
+        1.2,
+        0.0,
+        vec![span("Centered and underlined", 14.0, 0xff3177ff)],
+    ));
+    content.add_paragraph(Paragraph::new(
+        TextAlign::Right,
+        TextDirection::LTR,
+        None,
+        None,
+        1.5,
+        2.0,
+        vec![span("Right, spaced", 14.0, 0xff000000)],
+    ));
+
+    let text = shape(10, Type::Text(content), (20.0, 20.0, 180.0, 180.0));
+    fixture("text", vec![text], vec![])
+}
+
+/// A masked group: the first child is the mask and the rest is clipped by it.
+fn masks() -> Fixture {
+    let mut group = shape(
+        10,
+        Type::Group(Group { masked: true }),
+        (20.0, 20.0, 180.0, 180.0),
+    );
+
+    let mut mask = shape(11, Type::Circle, (20.0, 20.0, 180.0, 180.0));
+    mask.add_fill(Fill::RadialGradient(Gradient::new(
+        (0.5, 0.5),
+        (0.5, 1.0),
+        255,
+        1.0,
+        &[
+            (skia::Color::new(0xff000000), 0.5),
+            (skia::Color::new(0x00000000), 1.0),
+        ],
+    )));
+
+    let mut content = shape(12, rect(), (0.0, 0.0, 200.0, 200.0));
+    content.add_fill(Fill::LinearGradient(Gradient::new(
+        (0.0, 0.5),
+        (1.0, 0.5),
+        255,
+        1.0,
+        &[
+            (skia::Color::new(0xff31efb8), 0.0),
+            (skia::Color::new(0xff7a3fe0), 1.0),
+        ],
+    )));
+
+    let mut children = vec![mask, content];
+    append_children(&mut group, &mut children);
+
+    fixture("masks", vec![group], children)
+}
+
+fn id(value: u64) -> Uuid {
+    Uuid::from_u64_pair(0, value)
+}
+
+fn shape(value: u64, shape_type: Type, (left, top, right, bottom): (f32, f32, f32, f32)) -> Shape {
+    let mut shape = Shape::new(id(value));
+    // The selrect is set before the type so texts aren't laid out yet: the
+    // layout needs the fonts of the state, and is done when loading the scene.
+    shape.set_selrect(left, top, right, bottom);
+    shape.set_shape_type(shape_type);
+    shape
+}
+
+fn rect() -> Type {
+    Type::Rect(RectShape::default())
+}
+
+fn solid(argb: u32) -> Fill {
+    Fill::Solid(SolidColor(skia::Color::new(argb)))
+}
+
+fn stroke(mut stroke: Stroke, argb: u32) -> Stroke {
+    stroke.fill = solid(argb);
+    stroke
+}
+
+fn append_children(parent: &mut Shape, children: &mut [Shape]) {
+    for child in children.iter_mut() {
+        child.set_parent(parent.id);
+        parent.add_child(child.id);
+    }
+}
+
+/// Puts `children` in a white frame inside the root shape. `descendants` are
+/// the shapes already appended to one of the children.
+fn fixture(name: &'static str, children: Vec<Shape>, descendants: Vec<Shape>) -> Fixture {
+    fixture_at(
+        name,
+        (0.0, 0.0, FRAME_SIZE, FRAME_SIZE),
+        children,
+        descendants,
+    )
+}
+
+/// Like `fixture`, with the frame at `bounds`.
+fn fixture_at(
+    name: &'static str,
+    bounds: (f32, f32, f32, f32),
+    mut children: Vec<Shape>,
+    descendants: Vec<Shape>,
+) -> Fixture {
+    let mut root = Shape::new(Uuid::nil());
+    root.set_shape_type(Type::Frame(Frame::default()));
+
+    let mut frame = shape(FRAME_ID, Type::Frame(Frame::default()), bounds);
+    frame.add_fill(solid(0xffffffff));
+    frame.set_clip(true);
+
+    append_children(&mut frame, &mut children);
+    append_children(&mut root, std::slice::from_mut(&mut frame));
+
+    let mut shapes = vec![root, frame];
+    shapes.extend(children);
+    shapes.extend(descendants);
+
+    Fixture {
+        name,
+        scene: Scene {
+            fonts: vec![],
+            images: vec![],
+            shapes,
+            modifiers: vec![],
+            structure: vec![],
+            scale_content: vec![],
+        },
+        frame: id(FRAME_ID),
+    }
+}
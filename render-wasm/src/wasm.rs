@@ -1,9 +1,12 @@
 pub mod blend;
 pub mod blurs;
+pub mod export;
 pub mod fills;
 pub mod fonts;
 pub mod layouts;
+pub mod noise;
 pub mod paths;
+pub mod scene;
 pub mod shadows;
 pub mod shapes;
 pub mod strokes;
@@ -1,6 +1,6 @@
 use macros::ToJs;
 
-use crate::shapes::{Blur, BlurType};
+use crate::shapes::{Blur, BlurProgression, BlurProgressionType, BlurType};
 use crate::{with_current_shape_mut, STATE};
 
 #[derive(Debug, Clone, Copy, PartialEq, ToJs)]
@@ -8,6 +8,7 @@ use crate::{with_current_shape_mut, STATE};
 #[allow(dead_code)]
 pub enum RawBlurType {
     LayerBlur = 0, // odd naming to comply with cljs value
+    BackgroundBlur = 1,
 }
 
 impl From<u8> for RawBlurType {
@@ -20,6 +21,7 @@ impl From<RawBlurType> for BlurType {
     fn from(value: RawBlurType) -> Self {
         match value {
             RawBlurType::LayerBlur => BlurType::LayerBlur,
+            RawBlurType::BackgroundBlur => BlurType::BackgroundBlur,
         }
     }
 }
@@ -32,6 +34,63 @@ pub extern "C" fn set_shape_blur(blur_type: u8, hidden: bool, value: f32) {
     });
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, ToJs)]
+#[repr(u8)]
+#[allow(dead_code)]
+pub enum RawBlurProgressionType {
+    Linear = 0,
+    Radial = 1,
+}
+
+impl From<u8> for RawBlurProgressionType {
+    fn from(value: u8) -> Self {
+        unsafe { std::mem::transmute(value) }
+    }
+}
+
+impl From<RawBlurProgressionType> for BlurProgressionType {
+    fn from(value: RawBlurProgressionType) -> Self {
+        match value {
+            RawBlurProgressionType::Linear => BlurProgressionType::Linear,
+            RawBlurProgressionType::Radial => BlurProgressionType::Radial,
+        }
+    }
+}
+
+/// Makes the current blur progressive, going from `start_value` at the start
+/// point to the blur value at the end point.
+#[no_mangle]
+pub extern "C" fn set_shape_blur_progression(
+    progression_type: u8,
+    start_x: f32,
+    start_y: f32,
+    end_x: f32,
+    end_y: f32,
+    start_value: f32,
+) {
+    with_current_shape_mut!(state, |shape: &mut Shape| {
+        let progression = BlurProgression {
+            progression_type: RawBlurProgressionType::from(progression_type).into(),
+            start: (start_x, start_y),
+            end: (end_x, end_y),
+            start_value,
+        };
+        shape
+            .set_blur_progression(progression)
+            .expect("could not set blur progression");
+    });
+}
+
+/// Sets the saturation change of the current blur, used by background blurs.
+#[no_mangle]
+pub extern "C" fn set_shape_blur_saturation(saturation: f32) {
+    with_current_shape_mut!(state, |shape: &mut Shape| {
+        shape
+            .set_blur_saturation(saturation)
+            .expect("could not set blur saturation");
+    });
+}
+
 #[no_mangle]
 pub extern "C" fn clear_shape_blur() {
     with_current_shape_mut!(state, |shape: &mut Shape| {
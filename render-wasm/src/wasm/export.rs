@@ -0,0 +1,118 @@
+use macros::ToJs;
+use skia_safe as skia;
+
+use crate::mem::{self, SerializableResult};
+use crate::utils::uuid_from_u32_quartet;
+use crate::uuid::Uuid;
+use crate::{performance, with_state_mut, STATE};
+
+#[derive(Debug, Clone, Copy, PartialEq, ToJs)]
+#[repr(u8)]
+#[allow(dead_code)]
+pub enum RawImageFormat {
+    Png = 0,
+    Jpeg = 1,
+    Webp = 2,
+}
+
+impl From<u8> for RawImageFormat {
+    fn from(value: u8) -> Self {
+        unsafe { std::mem::transmute(value) }
+    }
+}
+
+impl From<RawImageFormat> for skia::EncodedImageFormat {
+    fn from(value: RawImageFormat) -> Self {
+        match value {
+            RawImageFormat::Png => skia::EncodedImageFormat::PNG,
+            RawImageFormat::Jpeg => skia::EncodedImageFormat::JPEG,
+            RawImageFormat::Webp => skia::EncodedImageFormat::WEBP,
+        }
+    }
+}
+
+/*
+  Renders the shape subtree off-screen and returns the encoded image. The
+  first 4 bytes are the length of the encoded data, which follows right
+  after. An empty result means the shape couldn't be exported.
+*/
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub extern "C" fn render_shape_image(
+    a: u32,
+    b: u32,
+    c: u32,
+    d: u32,
+    scale: f32,
+    raw_format: u8,
+    quality: u8,
+    has_background: bool,
+    raw_background: u32,
+) -> *mut u8 {
+    let id = uuid_from_u32_quartet(a, b, c, d);
+    let format = RawImageFormat::from(raw_format).into();
+    let background = has_background.then(|| skia::Color::new(raw_background));
+
+    with_state_mut!(state, {
+        let data = state
+            .render_shape_image(
+                &id,
+                scale,
+                format,
+                quality.min(100) as u32,
+                background,
+                performance::get_time(),
+            )
+            .unwrap_or_else(|e| {
+                eprintln!("Error exporting shape {}: {}", id, e);
+                Vec::new()
+            });
+
+        mem::write_length_prefixed(&data)
+    })
+}
+
+/*
+  Renders the frames whose ids are in the shared memory buffer as the pages of a
+  PDF document, in the same order. The result has the same layout as the one of
+  `render_shape_image`.
+*/
+#[no_mangle]
+pub extern "C" fn render_frames_pdf() -> *mut u8 {
+    let bytes = mem::bytes();
+
+    let ids: Vec<Uuid> = bytes
+        .chunks(size_of::<<Uuid as SerializableResult>::BytesType>())
+        .map(|data| Uuid::try_from(data).unwrap())
+        .collect();
+
+    with_state_mut!(state, {
+        let data = state.render_frames_pdf(&ids).unwrap_or_else(|e| {
+            eprintln!("Error exporting frames to PDF: {}", e);
+            Vec::new()
+        });
+
+        mem::write_length_prefixed(&data)
+    })
+}
+
+/*
+  Writes the shape subtree as an SVG document. With `text_as_paths` the texts
+  are converted to outlines. The result has the same layout as the one of
+  `render_shape_image`, with the document encoded as UTF-8.
+*/
+#[no_mangle]
+pub extern "C" fn render_shape_svg(a: u32, b: u32, c: u32, d: u32, text_as_paths: bool) -> *mut u8 {
+    let id = uuid_from_u32_quartet(a, b, c, d);
+
+    with_state_mut!(state, {
+        let data = state
+            .render_shape_svg(&id, text_as_paths)
+            .unwrap_or_else(|e| {
+                eprintln!("Error exporting shape {} to SVG: {}", id, e);
+                String::new()
+            });
+
+        mem::write_length_prefixed(data.as_bytes())
+    })
+}
@@ -1,5 +1,6 @@
 use macros::ToJs;
 
+use super::blend::RawBlendMode;
 use crate::mem;
 use crate::shapes;
 use crate::with_current_shape_mut;
@@ -7,9 +8,11 @@ use crate::STATE;
 
 mod gradient;
 mod image;
+mod mesh;
 mod solid;
 
 const RAW_FILL_DATA_SIZE: usize = std::mem::size_of::<RawFillData>();
+const RAW_FILL_LAYER_HEADER_SIZE: usize = 4;
 
 #[repr(C, u8, align(4))]
 #[derive(Debug, PartialEq, Clone, Copy, ToJs)]
@@ -19,21 +22,8 @@ pub enum RawFillData {
     Linear(gradient::RawGradientData) = 0x01,
     Radial(gradient::RawGradientData) = 0x02,
     Image(image::RawImageFillData) = 0x03,
-}
-
-impl From<RawFillData> for shapes::Fill {
-    fn from(fill_data: RawFillData) -> Self {
-        match fill_data {
-            RawFillData::Solid(solid_fill_data) => shapes::Fill::Solid(solid_fill_data.into()),
-            RawFillData::Linear(linear_fill_data) => {
-                shapes::Fill::LinearGradient(linear_fill_data.into())
-            }
-            RawFillData::Radial(radial_fill_data) => {
-                shapes::Fill::RadialGradient(radial_fill_data.into())
-            }
-            RawFillData::Image(image_fill_data) => shapes::Fill::Image(image_fill_data.into()),
-        }
-    }
+    Angular(gradient::RawGradientData) = 0x04,
+    Mesh(mesh::RawMeshData) = 0x05,
 }
 
 impl From<[u8; RAW_FILL_DATA_SIZE]> for RawFillData {
@@ -53,17 +43,86 @@ impl TryFrom<&[u8]> for RawFillData {
     }
 }
 
-// FIXME: return Result
-pub fn parse_fills_from_bytes(buffer: &[u8], num_fills: usize) -> Vec<shapes::Fill> {
-    buffer
-        .chunks_exact(RAW_FILL_DATA_SIZE)
-        .take(num_fills)
-        .map(|bytes| {
-            RawFillData::try_from(bytes)
-                .expect("Invalid fill data")
-                .into()
+/// Blend mode and opacity of a fill layer. Shape and stroke fills are preceded
+/// by this header: blend mode (u8), opacity (u8) and 16 bits of padding.
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct RawFillLayerHeader {
+    blend_mode: u8,
+    opacity: u8,
+}
+
+impl RawFillLayerHeader {
+    fn layer(&self, fill: shapes::Fill) -> shapes::FillLayer {
+        shapes::FillLayer::new(
+            fill,
+            RawBlendMode::from(self.blend_mode).into(),
+            self.opacity as f32 / 255.,
+        )
+    }
+}
+
+impl TryFrom<&[u8]> for RawFillLayerHeader {
+    type Error = String;
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let header = bytes
+            .get(0..RAW_FILL_LAYER_HEADER_SIZE)
+            .ok_or("Invalid fill layer data")?;
+        Ok(Self {
+            blend_mode: header[0],
+            opacity: header[1],
         })
-        .collect()
+    }
+}
+
+/// Reads the fill at the start of `bytes`. Gradient stops and mesh patches
+/// follow the fill data, so the fill is returned along with the number of bytes
+/// it takes.
+pub fn parse_fill(bytes: &[u8]) -> Result<(shapes::Fill, usize), String> {
+    let raw_fill = RawFillData::try_from(bytes)?;
+    let trailing = &bytes[RAW_FILL_DATA_SIZE..];
+
+    let (fill, trailing_size) = match raw_fill {
+        RawFillData::Solid(solid) => (shapes::Fill::Solid(solid.into()), 0),
+        RawFillData::Linear(gradient) => (
+            shapes::Fill::LinearGradient(gradient.to_gradient(trailing)?),
+            gradient.stops_size(),
+        ),
+        RawFillData::Radial(gradient) => (
+            shapes::Fill::RadialGradient(gradient.to_gradient(trailing)?),
+            gradient.stops_size(),
+        ),
+        RawFillData::Image(image) => (shapes::Fill::Image(image.into()), 0),
+        RawFillData::Angular(gradient) => (
+            shapes::Fill::AngularGradient(gradient.to_gradient(trailing)?),
+            gradient.stops_size(),
+        ),
+        RawFillData::Mesh(mesh) => (
+            shapes::Fill::MeshGradient(mesh.to_mesh(trailing)?),
+            mesh.patches_size(),
+        ),
+    };
+    Ok((fill, RAW_FILL_DATA_SIZE + trailing_size))
+}
+
+/// Reads the fill layer at the start of `bytes`, see `parse_fill`.
+pub fn parse_fill_layer(bytes: &[u8]) -> Result<(shapes::FillLayer, usize), String> {
+    let header = RawFillLayerHeader::try_from(bytes)?;
+    let (fill, size) = parse_fill(&bytes[RAW_FILL_LAYER_HEADER_SIZE..])?;
+    Ok((header.layer(fill), RAW_FILL_LAYER_HEADER_SIZE + size))
+}
+
+pub fn parse_fills_from_bytes(
+    buffer: &[u8],
+    num_fills: usize,
+) -> Result<Vec<shapes::FillLayer>, String> {
+    let mut fills = Vec::with_capacity(num_fills);
+    let mut offset = 0;
+    for _ in 0..num_fills {
+        let (layer, size) = parse_fill_layer(&buffer[offset..])?;
+        fills.push(layer);
+        offset += size;
+    }
+    Ok(fills)
 }
 
 #[no_mangle]
@@ -73,7 +132,7 @@ pub extern "C" fn set_shape_fills() {
         // The first byte contains the actual number of fills
         let num_fills = bytes.first().copied().unwrap_or(0) as usize;
         // Skip the first 4 bytes (header with fill count) and parse only the actual fills
-        let fills = parse_fills_from_bytes(&bytes[4..], num_fills);
+        let fills = parse_fills_from_bytes(&bytes[4..], num_fills).expect("Invalid fill data");
         shape.set_fills(fills);
         mem::free_bytes();
     });
@@ -83,8 +142,8 @@ pub extern "C" fn set_shape_fills() {
 pub extern "C" fn add_shape_fill() {
     with_current_shape_mut!(state, |shape: &mut Shape| {
         let bytes = mem::bytes();
-        let raw_fill = RawFillData::try_from(&bytes[..]).expect("Invalid fill data");
-        shape.add_fill(raw_fill.into());
+        let (layer, _) = parse_fill_layer(&bytes).expect("Invalid fill data");
+        shape.add_fill(layer);
     });
 }
 
@@ -103,11 +162,57 @@ mod tests {
     fn test_raw_fill_data_layout() {
         assert_eq!(
             std::mem::size_of::<RawFillData>(),
-            4 + std::mem::size_of::<gradient::RawGradientData>()
+            4 + std::mem::size_of::<image::RawImageFillData>()
         );
         assert_eq!(std::mem::align_of::<RawFillData>(), 4);
     }
 
+    #[test]
+    fn test_fill_layer_from_bytes() {
+        let size = RAW_FILL_LAYER_HEADER_SIZE + RAW_FILL_DATA_SIZE;
+        let mut bytes = vec![0x00; size];
+        bytes[0] = RawBlendMode::Multiply as u8;
+        bytes[1] = 0x80;
+        bytes[8..12].copy_from_slice(&0xfffabada_u32.to_le_bytes());
+
+        let (layer, layer_size) = parse_fill_layer(&bytes).unwrap();
+
+        assert_eq!(layer_size, size);
+        assert_eq!(
+            layer.fill,
+            shapes::Fill::Solid(shapes::SolidColor(shapes::Color::new(0xfffabada)))
+        );
+        assert_eq!(
+            layer.blend_mode,
+            shapes::BlendMode(skia_safe::BlendMode::Multiply)
+        );
+        assert_eq!(layer.opacity, 128. / 255.);
+        assert!(parse_fill_layer(&bytes[..size - 1]).is_err());
+    }
+
+    #[test]
+    fn test_gradient_fill_with_many_stops() {
+        let stop_count = 20;
+        let mut bytes = vec![0x00; RAW_FILL_DATA_SIZE];
+        bytes[0] = 0x01;
+        bytes[28..32].copy_from_slice(&(stop_count as u32).to_le_bytes());
+        for i in 0..stop_count {
+            bytes.extend_from_slice(&0xff000000_u32.to_le_bytes());
+            bytes.extend_from_slice(&(i as f32 / stop_count as f32).to_le_bytes());
+        }
+
+        let (fill, size) = parse_fill(&bytes).unwrap();
+
+        assert_eq!(size, bytes.len());
+        match fill {
+            shapes::Fill::LinearGradient(gradient) => {
+                assert_eq!(gradient.stops().count(), stop_count)
+            }
+            _ => panic!("Expected a linear gradient"),
+        }
+        assert!(parse_fill(&bytes[..bytes.len() - 1]).is_err());
+    }
+
     #[test]
     fn test_raw_fill_data_from_bytes_to_solid_fill() {
         let mut bytes = vec![0x00; std::mem::size_of::<RawFillData>()];
@@ -1,6 +1,63 @@
-use crate::shapes::{Color, Gradient};
+use macros::ToJs;
 
-const MAX_GRADIENT_STOPS: usize = 16;
+use crate::shapes::{Color, Gradient, GradientInterpolation, HueInterpolation};
+
+const RAW_STOP_DATA_SIZE: usize = std::mem::size_of::<RawStopData>();
+const FLAG_DITHER: u8 = 1 << 0;
+
+#[derive(Debug, PartialEq, Clone, Copy, ToJs)]
+#[repr(u8)]
+#[allow(dead_code)]
+pub enum RawGradientInterpolation {
+    Srgb = 0,
+    LinearSrgb = 1,
+    Oklab = 2,
+    Oklch = 3,
+}
+
+impl From<u8> for RawGradientInterpolation {
+    fn from(value: u8) -> Self {
+        unsafe { std::mem::transmute(value) }
+    }
+}
+
+impl From<RawGradientInterpolation> for GradientInterpolation {
+    fn from(value: RawGradientInterpolation) -> Self {
+        match value {
+            RawGradientInterpolation::Srgb => GradientInterpolation::Srgb,
+            RawGradientInterpolation::LinearSrgb => GradientInterpolation::LinearSrgb,
+            RawGradientInterpolation::Oklab => GradientInterpolation::Oklab,
+            RawGradientInterpolation::Oklch => GradientInterpolation::Oklch,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, ToJs)]
+#[repr(u8)]
+#[allow(dead_code)]
+pub enum RawHueInterpolation {
+    Shorter = 0,
+    Longer = 1,
+    Increasing = 2,
+    Decreasing = 3,
+}
+
+impl From<u8> for RawHueInterpolation {
+    fn from(value: u8) -> Self {
+        unsafe { std::mem::transmute(value) }
+    }
+}
+
+impl From<RawHueInterpolation> for HueInterpolation {
+    fn from(value: RawHueInterpolation) -> Self {
+        match value {
+            RawHueInterpolation::Shorter => HueInterpolation::Shorter,
+            RawHueInterpolation::Longer => HueInterpolation::Longer,
+            RawHueInterpolation::Increasing => HueInterpolation::Increasing,
+            RawHueInterpolation::Decreasing => HueInterpolation::Decreasing,
+        }
+    }
+}
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 #[repr(C)]
@@ -11,10 +68,11 @@ pub struct RawGradientData {
     end_x: f32,
     end_y: f32,
     opacity: u8,
-    // 24-bit padding here, reserved for future use
+    interpolation: u8,
+    hue_interpolation: u8,
+    flags: u8,
     width: f32,
-    stop_count: u8,
-    stops: [RawStopData; MAX_GRADIENT_STOPS],
+    stop_count: u32,
 }
 
 impl RawGradientData {
@@ -25,6 +83,34 @@ impl RawGradientData {
     pub fn end(&self) -> (f32, f32) {
         (self.end_x, self.end_y)
     }
+
+    /// Size of the stops following the gradient data.
+    pub fn stops_size(&self) -> usize {
+        self.stop_count as usize * RAW_STOP_DATA_SIZE
+    }
+
+    /// Builds the gradient with the stops at the start of `bytes`.
+    pub fn to_gradient(&self, bytes: &[u8]) -> Result<Gradient, String> {
+        let stops = bytes
+            .get(0..self.stops_size())
+            .ok_or("Invalid gradient stops")?
+            .chunks_exact(RAW_STOP_DATA_SIZE)
+            .map(|chunk| {
+                let data: [u8; RAW_STOP_DATA_SIZE] = chunk.try_into().unwrap();
+                let stop = RawStopData::from(data);
+                (stop.color(), stop.offset())
+            })
+            .collect::<Vec<_>>();
+
+        let mut gradient =
+            Gradient::new(self.start(), self.end(), self.opacity, self.width, &stops);
+        gradient.set_interpolation(
+            RawGradientInterpolation::from(self.interpolation).into(),
+            RawHueInterpolation::from(self.hue_interpolation).into(),
+        );
+        gradient.set_dither(self.flags & FLAG_DITHER != 0);
+        Ok(gradient)
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -34,6 +120,12 @@ struct RawStopData {
     offset: f32,
 }
 
+impl From<[u8; RAW_STOP_DATA_SIZE]> for RawStopData {
+    fn from(bytes: [u8; RAW_STOP_DATA_SIZE]) -> Self {
+        unsafe { std::mem::transmute(bytes) }
+    }
+}
+
 impl RawStopData {
     pub fn color(&self) -> Color {
         Color::from(self.color)
@@ -43,22 +135,3 @@ impl RawStopData {
         self.offset
     }
 }
-
-impl From<RawGradientData> for Gradient {
-    fn from(raw_gradient: RawGradientData) -> Self {
-        let stops = raw_gradient
-            .stops
-            .iter()
-            .take(raw_gradient.stop_count as usize)
-            .map(|stop| (stop.color(), stop.offset()))
-            .collect::<Vec<_>>();
-
-        Gradient::new(
-            raw_gradient.start(),
-            raw_gradient.end(),
-            raw_gradient.opacity,
-            raw_gradient.width,
-            &stops,
-        )
-    }
-}
@@ -1,14 +1,34 @@
+use macros::ToJs;
+
 use crate::mem;
 // use crate::mem::SerializableResult;
+use crate::shapes::{ImageAdjustments, ImageFill, ImageScaleMode, ImageTransform};
+use crate::utils::uuid_from_u32_quartet;
 use crate::uuid::Uuid;
 use crate::with_state_mut;
 use crate::STATE;
-use crate::{shapes::ImageFill, utils::uuid_from_u32_quartet};
 
 const FLAG_KEEP_ASPECT_RATIO: u8 = 1 << 0;
 const IMAGE_IDS_SIZE: usize = 32;
 const IMAGE_HEADER_SIZE: usize = 36; // 32 bytes for IDs + 4 bytes for is_thumbnail flag
 
+#[derive(Debug, Clone, Copy, PartialEq, ToJs)]
+#[repr(u8)]
+#[allow(dead_code)]
+pub enum RawImageScaleMode {
+    Stretch = 0,
+    Fill = 1,
+    Fit = 2,
+    Crop = 3,
+    Tile = 4,
+}
+
+impl From<u8> for RawImageScaleMode {
+    fn from(value: u8) -> Self {
+        unsafe { std::mem::transmute(value) }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[repr(C)]
 #[repr(align(4))]
@@ -19,23 +39,69 @@ pub struct RawImageFillData {
     d: u32,
     opacity: u8,
     flags: u8,
-    // 16-bit padding here, reserved for future use
+    scale_mode: u8,
+    // 8-bit padding here, reserved for future use
     width: i32,
     height: i32,
+    offset_x: f32,
+    offset_y: f32,
+    scale: f32,
+    rotation: f32,
+    exposure: f32,
+    contrast: f32,
+    saturation: f32,
+    temperature: f32,
+    tint: f32,
+    highlights: f32,
+    shadows: f32,
+}
+
+impl RawImageFillData {
+    fn scale_mode(&self) -> ImageScaleMode {
+        match RawImageScaleMode::from(self.scale_mode) {
+            // Stretched images keeping their aspect ratio are the ones
+            // covering the shape.
+            RawImageScaleMode::Stretch if self.flags & FLAG_KEEP_ASPECT_RATIO != 0 => {
+                ImageScaleMode::Fill
+            }
+            RawImageScaleMode::Stretch => ImageScaleMode::Stretch,
+            RawImageScaleMode::Fill => ImageScaleMode::Fill,
+            RawImageScaleMode::Fit => ImageScaleMode::Fit,
+            RawImageScaleMode::Crop => ImageScaleMode::Crop(ImageTransform {
+                offset: (self.offset_x, self.offset_y),
+                scale: self.scale,
+                rotation: self.rotation,
+            }),
+            RawImageScaleMode::Tile => ImageScaleMode::Tile(self.scale),
+        }
+    }
+
+    fn adjustments(&self) -> ImageAdjustments {
+        ImageAdjustments {
+            exposure: self.exposure,
+            contrast: self.contrast,
+            saturation: self.saturation,
+            temperature: self.temperature,
+            tint: self.tint,
+            highlights: self.highlights,
+            shadows: self.shadows,
+        }
+    }
 }
 
 impl From<RawImageFillData> for ImageFill {
     fn from(value: RawImageFillData) -> Self {
         let id = uuid_from_u32_quartet(value.a, value.b, value.c, value.d);
-        let keep_aspect_ratio = value.flags & FLAG_KEEP_ASPECT_RATIO != 0;
 
-        Self::new(
+        let mut image_fill = Self::new(
             id,
             value.opacity,
             value.width,
             value.height,
-            keep_aspect_ratio,
-        )
+            value.scale_mode(),
+        );
+        image_fill.set_adjustments(value.adjustments());
+        image_fill
     }
 }
 
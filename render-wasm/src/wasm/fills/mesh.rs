@@ -0,0 +1,93 @@
+use crate::shapes::{Color, MeshGradient, MeshPatch};
+
+const RAW_MESH_PATCH_SIZE: usize = std::mem::size_of::<RawMeshPatchData>();
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[repr(C)]
+#[repr(align(4))]
+pub struct RawMeshData {
+    opacity: u8,
+    // 24-bit padding here, reserved for future use
+    _padding: [u8; 3],
+    patch_count: u32,
+}
+
+impl RawMeshData {
+    /// Size of the patches following the mesh data.
+    pub fn patches_size(&self) -> usize {
+        self.patch_count as usize * RAW_MESH_PATCH_SIZE
+    }
+
+    /// Builds the mesh gradient with the patches at the start of `bytes`.
+    pub fn to_mesh(&self, bytes: &[u8]) -> Result<MeshGradient, String> {
+        let patches = bytes
+            .get(0..self.patches_size())
+            .ok_or("Invalid mesh gradient patches")?
+            .chunks_exact(RAW_MESH_PATCH_SIZE)
+            .map(|chunk| {
+                let data: [u8; RAW_MESH_PATCH_SIZE] = chunk.try_into().unwrap();
+                RawMeshPatchData::from(data).into()
+            })
+            .collect();
+
+        Ok(MeshGradient::new(self.opacity, patches))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[repr(C)]
+#[repr(align(4))]
+struct RawMeshPatchData {
+    cubics: [f32; 24],
+    colors: [u32; 4],
+}
+
+impl From<[u8; RAW_MESH_PATCH_SIZE]> for RawMeshPatchData {
+    fn from(bytes: [u8; RAW_MESH_PATCH_SIZE]) -> Self {
+        unsafe { std::mem::transmute(bytes) }
+    }
+}
+
+impl From<RawMeshPatchData> for MeshPatch {
+    fn from(raw_patch: RawMeshPatchData) -> Self {
+        let mut cubics = [(0.0, 0.0); 12];
+        for (point, coords) in cubics.iter_mut().zip(raw_patch.cubics.chunks_exact(2)) {
+            *point = (coords[0], coords[1]);
+        }
+
+        Self {
+            cubics,
+            colors: raw_patch.colors.map(Color::new),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mesh_gradient_from_bytes() {
+        let raw_mesh = RawMeshData {
+            opacity: 0xff,
+            _padding: [0; 3],
+            patch_count: 1,
+        };
+        let mut bytes = vec![];
+        for i in 0..24 {
+            bytes.extend_from_slice(&(i as f32 / 24.0).to_le_bytes());
+        }
+        for color in [0xffff0000u32, 0xff00ff00, 0xff0000ff, 0xffffffff] {
+            bytes.extend_from_slice(&color.to_le_bytes());
+        }
+
+        assert_eq!(raw_mesh.patches_size(), bytes.len());
+        let mesh = raw_mesh.to_mesh(&bytes).unwrap();
+        assert_eq!(mesh.opacity(), 0xff);
+        assert_eq!(mesh.patches().len(), 1);
+        assert_eq!(mesh.patches()[0].cubics[1], (2.0 / 24.0, 3.0 / 24.0));
+        assert_eq!(mesh.patches()[0].colors[2], Color::new(0xff0000ff));
+
+        assert!(raw_mesh.to_mesh(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
@@ -0,0 +1,70 @@
+use macros::ToJs;
+use skia_safe as skia;
+
+use crate::shapes::{Noise, NoiseType};
+use crate::{with_current_shape_mut, STATE};
+
+#[derive(Debug, Clone, Copy, PartialEq, ToJs)]
+#[repr(u8)]
+#[allow(dead_code)]
+pub enum RawNoiseType {
+    Mono = 0,
+    Duotone = 1,
+    Multicolor = 2,
+}
+
+impl From<u8> for RawNoiseType {
+    fn from(value: u8) -> Self {
+        unsafe { std::mem::transmute(value) }
+    }
+}
+
+impl From<RawNoiseType> for NoiseType {
+    fn from(value: RawNoiseType) -> Self {
+        match value {
+            RawNoiseType::Mono => NoiseType::Mono,
+            RawNoiseType::Duotone => NoiseType::Duotone,
+            RawNoiseType::Multicolor => NoiseType::Multicolor,
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn set_shape_noise(
+    noise_type: u8,
+    hidden: bool,
+    size: f32,
+    density: f32,
+    opacity: f32,
+) {
+    with_current_shape_mut!(state, |shape: &mut Shape| {
+        let noise_type = RawNoiseType::from(noise_type);
+        shape.set_noise(Some(Noise::new(
+            noise_type.into(),
+            hidden,
+            size,
+            density,
+            opacity,
+        )));
+    });
+}
+
+/// Sets the colors of the current noise. Mono noise only uses the first one.
+#[no_mangle]
+pub extern "C" fn set_shape_noise_colors(raw_color: u32, raw_secondary_color: u32) {
+    with_current_shape_mut!(state, |shape: &mut Shape| {
+        shape
+            .set_noise_colors(
+                skia::Color::new(raw_color),
+                skia::Color::new(raw_secondary_color),
+            )
+            .expect("could not set noise colors");
+    });
+}
+
+#[no_mangle]
+pub extern "C" fn clear_shape_noise() {
+    with_current_shape_mut!(state, |shape: &mut Shape| {
+        shape.set_noise(None);
+    });
+}
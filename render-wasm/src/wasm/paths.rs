@@ -4,8 +4,8 @@ use mem::SerializableResult;
 use std::mem::size_of;
 use std::sync::{Mutex, OnceLock};
 
-use crate::shapes::{Path, Segment, ToPath};
-use crate::{mem, with_current_shape, with_current_shape_mut, STATE};
+use crate::shapes::{Path, Segment, Stroke, ToPath};
+use crate::{mem, render, with_current_shape, with_current_shape_mut, STATE};
 
 const RAW_SEGMENT_DATA_SIZE: usize = size_of::<RawSegmentData>();
 
@@ -220,6 +220,64 @@ pub extern "C" fn current_to_path() -> *mut u8 {
     mem::write_vec(result)
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, ToJs)]
+#[repr(u8)]
+#[allow(dead_code)]
+pub enum RawOutlineSource {
+    Fills = 0,
+    Stroke = 1,
+}
+
+/// Converts the fills and the visible strokes of the current shape into filled
+/// paths. Every outline has its source (`u8` and 3 reserved bytes), the `u32`
+/// index of the stroke and the `u32` count of its segments, followed by them.
+/// The result starts with the `u32` count of outlines.
+#[no_mangle]
+pub extern "C" fn current_strokes_to_paths() -> *mut u8 {
+    let mut outlines: Vec<(RawOutlineSource, u32, Path)> = vec![];
+    with_current_shape!(state, |shape: &Shape| {
+        // Back to the coordinates of the shape path, like `current_to_path`.
+        let to_path_coords = |mut path: skia_safe::Path| {
+            if let Some(matrix) = shape.to_path_transform().and_then(|m| m.invert()) {
+                path.transform(&matrix);
+            }
+            Path::from_skia_path(path)
+        };
+
+        if shape.has_fills() {
+            if let Some(geometry) = shape.geometry_path() {
+                outlines.push((RawOutlineSource::Fills, 0, to_path_coords(geometry)));
+            }
+        }
+
+        let visible_strokes: Vec<&Stroke> = shape.visible_strokes().collect();
+        for (index, stroke) in shape.strokes.iter().enumerate() {
+            if !visible_strokes.contains(&stroke) {
+                continue;
+            }
+            if let Some(outline) = render::stroke_outline(shape, stroke) {
+                outlines.push((
+                    RawOutlineSource::Stroke,
+                    index as u32,
+                    to_path_coords(outline),
+                ));
+            }
+        }
+    });
+
+    let mut bytes = (outlines.len() as u32).to_le_bytes().to_vec();
+    for (source, index, path) in outlines {
+        bytes.extend_from_slice(&[source as u8, 0, 0, 0]);
+        bytes.extend_from_slice(&index.to_le_bytes());
+        bytes.extend_from_slice(&(path.segments().len() as u32).to_le_bytes());
+        for segment in path.segments() {
+            let data: [u8; RAW_SEGMENT_DATA_SIZE] = RawSegmentData::from_segment(*segment).into();
+            bytes.extend_from_slice(&data);
+        }
+    }
+    mem::write_bytes(bytes)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,37 @@
+use crate::mem;
+use crate::scene::Scene;
+use crate::{with_state, with_state_mut, STATE};
+
+/*
+  Returns a snapshot of the shapes pool as a scene file (see
+  docs/scene_files.md). The first 4 bytes are the length of the snapshot, which
+  follows right after. Fonts and images are referenced by id, without their data.
+*/
+#[no_mangle]
+pub extern "C" fn dump_state() -> *mut u8 {
+    with_state!(state, {
+        let data = state.dump_scene().to_bytes();
+
+        mem::write_length_prefixed(&data)
+    })
+}
+
+/*
+  Replaces the shapes pool with the scene file in the shared memory buffer, like
+  the ones returned by `dump_state`. The fonts and images it references by id
+  have to be stored before. Returns false if the scene couldn't be loaded.
+*/
+#[no_mangle]
+pub extern "C" fn load_state() -> bool {
+    let bytes = mem::bytes();
+
+    with_state_mut!(state, {
+        match Scene::from_bytes(&bytes).and_then(|scene| state.load_scene(scene)) {
+            Ok(()) => true,
+            Err(e) => {
+                eprintln!("Error loading state: {}", e);
+                false
+            }
+        }
+    })
+}
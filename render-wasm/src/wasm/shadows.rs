@@ -1,7 +1,8 @@
 use macros::ToJs;
 use skia_safe as skia;
 
-use crate::shapes::{Shadow, ShadowStyle};
+use super::blend::RawBlendMode;
+use crate::shapes::{GlowSource, GlowTechnique, Shadow, ShadowStyle};
 use crate::{with_current_shape_mut, STATE};
 
 #[derive(Debug, Clone, Copy, PartialEq, ToJs)]
@@ -11,6 +12,8 @@ pub enum RawShadowStyle {
     // NOTE: Odd naming to comply with cljs value
     DropShadow = 0,
     InnerShadow = 1,
+    OuterGlow = 2,
+    InnerGlow = 3,
 }
 
 impl From<u8> for RawShadowStyle {
@@ -24,6 +27,54 @@ impl From<RawShadowStyle> for ShadowStyle {
         match value {
             RawShadowStyle::DropShadow => Self::Drop,
             RawShadowStyle::InnerShadow => Self::Inner,
+            RawShadowStyle::OuterGlow => Self::OuterGlow,
+            RawShadowStyle::InnerGlow => Self::InnerGlow,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ToJs)]
+#[repr(u8)]
+#[allow(dead_code)]
+pub enum RawGlowTechnique {
+    Soft = 0,
+    Precise = 1,
+}
+
+impl From<u8> for RawGlowTechnique {
+    fn from(value: u8) -> Self {
+        unsafe { std::mem::transmute(value) }
+    }
+}
+
+impl From<RawGlowTechnique> for GlowTechnique {
+    fn from(value: RawGlowTechnique) -> Self {
+        match value {
+            RawGlowTechnique::Soft => Self::Soft,
+            RawGlowTechnique::Precise => Self::Precise,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ToJs)]
+#[repr(u8)]
+#[allow(dead_code)]
+pub enum RawGlowSource {
+    Edge = 0,
+    Center = 1,
+}
+
+impl From<u8> for RawGlowSource {
+    fn from(value: u8) -> Self {
+        unsafe { std::mem::transmute(value) }
+    }
+}
+
+impl From<RawGlowSource> for GlowSource {
+    fn from(value: RawGlowSource) -> Self {
+        match value {
+            RawGlowSource::Edge => Self::Edge,
+            RawGlowSource::Center => Self::Center,
         }
     }
 }
@@ -46,6 +97,41 @@ pub extern "C" fn add_shape_shadow(
     });
 }
 
+/// Sets the technique of the last shadow, and where it starts from when it's
+/// an inner glow.
+#[no_mangle]
+pub extern "C" fn set_shape_shadow_glow(technique: u8, source: u8) {
+    with_current_shape_mut!(state, |shape: &mut Shape| {
+        shape
+            .set_shadow_glow(
+                RawGlowTechnique::from(technique).into(),
+                RawGlowSource::from(source).into(),
+            )
+            .expect("could not set shadow glow");
+    });
+}
+
+/// Sets the blend mode the last shadow is composited with.
+#[no_mangle]
+pub extern "C" fn set_shape_shadow_blend_mode(blend_mode: u8) {
+    with_current_shape_mut!(state, |shape: &mut Shape| {
+        shape
+            .set_shadow_blend_mode(RawBlendMode::from(blend_mode).into())
+            .expect("could not set shadow blend mode");
+    });
+}
+
+/// Sets whether the last drop shadow shows through the semi-transparent parts
+/// of the shape.
+#[no_mangle]
+pub extern "C" fn set_shape_shadow_show_behind(show_behind: bool) {
+    with_current_shape_mut!(state, |shape: &mut Shape| {
+        shape
+            .set_shadow_show_behind(show_behind)
+            .expect("could not set shadow show behind");
+    });
+}
+
 #[no_mangle]
 pub extern "C" fn clear_shape_shadows() {
     with_current_shape_mut!(state, |shape: &mut Shape| {
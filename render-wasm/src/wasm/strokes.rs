@@ -1,10 +1,17 @@
 use macros::ToJs;
 
 use crate::mem;
-use crate::shapes::{self, StrokeCap, StrokeStyle};
+use crate::shapes::{
+    self, StrokeCap, StrokeDash, StrokeLineJoin, StrokeSides, StrokeStyle, StrokeWidthProfile,
+};
 use crate::with_current_shape_mut;
 use crate::STATE;
 
+use super::svg_attrs::RawStrokeLineCap;
+
+const RAW_STROKE_DASH_DATA_SIZE: usize = std::mem::size_of::<RawStrokeDashData>();
+const FLAG_ADJUST: u8 = 1 << 0;
+
 #[derive(Debug, Clone, PartialEq, Copy, ToJs)]
 #[repr(u8)]
 #[allow(dead_code)]
@@ -13,6 +20,7 @@ pub enum RawStrokeStyle {
     Dotted = 1,
     Dashed = 2,
     Mixed = 3,
+    Custom = 4,
 }
 
 impl From<u8> for RawStrokeStyle {
@@ -28,10 +36,27 @@ impl From<RawStrokeStyle> for StrokeStyle {
             RawStrokeStyle::Dotted => StrokeStyle::Dotted,
             RawStrokeStyle::Dashed => StrokeStyle::Dashed,
             RawStrokeStyle::Mixed => StrokeStyle::Mixed,
+            RawStrokeStyle::Custom => StrokeStyle::Custom,
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, ToJs)]
+#[repr(u8)]
+#[allow(dead_code)]
+pub enum RawStrokeWidthProfile {
+    Points = 0,
+    TaperStart = 1,
+    TaperEnd = 2,
+    TaperBoth = 3,
+}
+
+impl From<u8> for RawStrokeWidthProfile {
+    fn from(value: u8) -> Self {
+        unsafe { std::mem::transmute(value) }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, ToJs)]
 #[repr(u8)]
 #[allow(dead_code)]
@@ -44,6 +69,12 @@ pub enum RawStrokeCap {
     DiamondMarker = 5,
     Round = 6,
     Square = 7,
+    Bar = 8,
+    ReversedArrow = 9,
+    HalfArrow = 10,
+    CircleOutline = 11,
+    DiamondOutline = 12,
+    TriangleOutline = 13,
 }
 
 impl From<u8> for RawStrokeCap {
@@ -65,55 +96,172 @@ impl TryFrom<RawStrokeCap> for StrokeCap {
             RawStrokeCap::DiamondMarker => Ok(StrokeCap::DiamondMarker),
             RawStrokeCap::Round => Ok(StrokeCap::Round),
             RawStrokeCap::Square => Ok(StrokeCap::Square),
+            RawStrokeCap::Bar => Ok(StrokeCap::Bar),
+            RawStrokeCap::ReversedArrow => Ok(StrokeCap::ReversedArrow),
+            RawStrokeCap::HalfArrow => Ok(StrokeCap::HalfArrow),
+            RawStrokeCap::CircleOutline => Ok(StrokeCap::CircleOutline),
+            RawStrokeCap::DiamondOutline => Ok(StrokeCap::DiamondOutline),
+            RawStrokeCap::TriangleOutline => Ok(StrokeCap::TriangleOutline),
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, ToJs)]
+#[repr(u8)]
+#[allow(dead_code)]
+pub enum RawStrokeJoin {
+    Inherit = 0,
+    Miter = 1,
+    Round = 2,
+    Bevel = 3,
+}
+
+impl From<u8> for RawStrokeJoin {
+    fn from(value: u8) -> Self {
+        unsafe { std::mem::transmute(value) }
+    }
+}
+
+impl TryFrom<RawStrokeJoin> for StrokeLineJoin {
+    type Error = ();
+
+    fn try_from(value: RawStrokeJoin) -> Result<Self, Self::Error> {
+        match value {
+            RawStrokeJoin::Inherit => Err(()),
+            RawStrokeJoin::Miter => Ok(StrokeLineJoin::Miter),
+            RawStrokeJoin::Round => Ok(StrokeLineJoin::Round),
+            RawStrokeJoin::Bevel => Ok(StrokeLineJoin::Bevel),
+        }
+    }
+}
+
+/// Header of the dash pattern of custom strokes, followed by `count` `f32`
+/// intervals.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[repr(C)]
+struct RawStrokeDashData {
+    offset: f32,
+    flags: u8,
+    cap: u8,
+    _reserved: [u8; 2],
+    count: u32,
+}
+
+impl From<[u8; RAW_STROKE_DASH_DATA_SIZE]> for RawStrokeDashData {
+    fn from(bytes: [u8; RAW_STROKE_DASH_DATA_SIZE]) -> Self {
+        unsafe { std::mem::transmute(bytes) }
+    }
+}
+
+fn parse_stroke_dash(bytes: &[u8]) -> Result<StrokeDash, String> {
+    let header: [u8; RAW_STROKE_DASH_DATA_SIZE] = bytes
+        .get(0..RAW_STROKE_DASH_DATA_SIZE)
+        .and_then(|data| data.try_into().ok())
+        .ok_or("Invalid stroke dash data")?;
+    let data = RawStrokeDashData::from(header);
+
+    let end = RAW_STROKE_DASH_DATA_SIZE + data.count as usize * 4;
+    let intervals = bytes
+        .get(RAW_STROKE_DASH_DATA_SIZE..end)
+        .ok_or("Invalid stroke dash intervals")?
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
+        .collect();
+
+    Ok(StrokeDash {
+        intervals,
+        offset: data.offset,
+        adjust: data.flags & FLAG_ADJUST != 0,
+        cap: RawStrokeLineCap::from(data.cap).into(),
+    })
+}
+
+/// Sets the join of the stroke. Custom strokes read their dash pattern from
+/// the shared memory.
+fn with_options(
+    mut stroke: shapes::Stroke,
+    style: RawStrokeStyle,
+    join: u8,
+    miter_limit: f32,
+) -> shapes::Stroke {
+    if style == RawStrokeStyle::Custom {
+        let bytes = mem::bytes();
+        stroke.dash = Some(parse_stroke_dash(&bytes).expect("Invalid stroke dash data"));
+    }
+    stroke.join = RawStrokeJoin::from(join).try_into().ok();
+    stroke.miter_limit = miter_limit;
+    stroke
+}
+
 #[no_mangle]
-pub extern "C" fn add_shape_center_stroke(width: f32, style: u8, cap_start: u8, cap_end: u8) {
+pub extern "C" fn add_shape_center_stroke(
+    width: f32,
+    style: u8,
+    cap_start: u8,
+    cap_end: u8,
+    join: u8,
+    miter_limit: f32,
+) {
     let stroke_style = RawStrokeStyle::from(style);
     let cap_start = RawStrokeCap::from(cap_start);
     let cap_end = RawStrokeCap::from(cap_end);
 
     with_current_shape_mut!(state, |shape: &mut Shape| {
-        shape.add_stroke(shapes::Stroke::new_center_stroke(
+        let stroke = shapes::Stroke::new_center_stroke(
             width,
             stroke_style.into(),
             cap_start.try_into().ok(),
             cap_end.try_into().ok(),
-        ));
+        );
+        shape.add_stroke(with_options(stroke, stroke_style, join, miter_limit));
     });
 }
 
 #[no_mangle]
-pub extern "C" fn add_shape_inner_stroke(width: f32, style: u8, cap_start: u8, cap_end: u8) {
+pub extern "C" fn add_shape_inner_stroke(
+    width: f32,
+    style: u8,
+    cap_start: u8,
+    cap_end: u8,
+    join: u8,
+    miter_limit: f32,
+) {
     let stroke_style = RawStrokeStyle::from(style);
     let cap_start = RawStrokeCap::from(cap_start);
     let cap_end = RawStrokeCap::from(cap_end);
 
     with_current_shape_mut!(state, |shape: &mut Shape| {
-        shape.add_stroke(shapes::Stroke::new_inner_stroke(
+        let stroke = shapes::Stroke::new_inner_stroke(
             width,
             stroke_style.into(),
             cap_start.try_into().ok(),
             cap_end.try_into().ok(),
-        ));
+        );
+        shape.add_stroke(with_options(stroke, stroke_style, join, miter_limit));
     });
 }
 
 #[no_mangle]
-pub extern "C" fn add_shape_outer_stroke(width: f32, style: u8, cap_start: u8, cap_end: u8) {
+pub extern "C" fn add_shape_outer_stroke(
+    width: f32,
+    style: u8,
+    cap_start: u8,
+    cap_end: u8,
+    join: u8,
+    miter_limit: f32,
+) {
     let stroke_style = RawStrokeStyle::from(style);
     let cap_start = RawStrokeCap::from(cap_start);
     let cap_end = RawStrokeCap::from(cap_end);
 
     with_current_shape_mut!(state, |shape: &mut Shape| {
-        shape.add_stroke(shapes::Stroke::new_outer_stroke(
+        let stroke = shapes::Stroke::new_outer_stroke(
             width,
             stroke_style.into(),
             cap_start.try_into().ok(),
             cap_end.try_into().ok(),
-        ));
+        );
+        shape.add_stroke(with_options(stroke, stroke_style, join, miter_limit));
     });
 }
 
@@ -121,13 +269,67 @@ pub extern "C" fn add_shape_outer_stroke(width: f32, style: u8, cap_start: u8, c
 pub extern "C" fn add_shape_stroke_fill() {
     with_current_shape_mut!(state, |shape: &mut Shape| {
         let bytes = mem::bytes();
-        let raw_fill = super::fills::RawFillData::try_from(&bytes[..]).expect("Invalid fill data");
+        let (layer, _) = super::fills::parse_fill_layer(&bytes).expect("Invalid fill data");
         shape
-            .set_stroke_fill(raw_fill.into())
+            .set_stroke_fill(layer)
             .expect("could not add stroke fill");
     });
 }
 
+/// Sets the width profile of the last stroke. `Points` profiles read their
+/// `(f32, f32)` points from the shared memory.
+#[no_mangle]
+pub extern "C" fn set_shape_stroke_width_profile(profile: u8) {
+    let profile = match RawStrokeWidthProfile::from(profile) {
+        RawStrokeWidthProfile::Points => {
+            let bytes = mem::bytes();
+            let points = bytes
+                .chunks_exact(8)
+                .map(|chunk| {
+                    let position = f32::from_le_bytes(chunk[0..4].try_into().unwrap());
+                    let factor = f32::from_le_bytes(chunk[4..8].try_into().unwrap());
+                    (position, factor)
+                })
+                .collect();
+            StrokeWidthProfile::Points(points)
+        }
+        RawStrokeWidthProfile::TaperStart => StrokeWidthProfile::TaperStart,
+        RawStrokeWidthProfile::TaperEnd => StrokeWidthProfile::TaperEnd,
+        RawStrokeWidthProfile::TaperBoth => StrokeWidthProfile::TaperBoth,
+    };
+
+    with_current_shape_mut!(state, |shape: &mut Shape| {
+        shape
+            .set_stroke_width_profile(profile)
+            .expect("could not set stroke width profile");
+    });
+}
+
+/// Sets the size of the caps of the last stroke, relative to the default one.
+#[no_mangle]
+pub extern "C" fn set_shape_stroke_cap_scales(start: f32, end: f32) {
+    with_current_shape_mut!(state, |shape: &mut Shape| {
+        shape
+            .set_stroke_cap_scales(start, end)
+            .expect("could not set stroke cap scales");
+    });
+}
+
+/// Sets different side widths on the last stroke of a rect or a frame.
+#[no_mangle]
+pub extern "C" fn set_shape_stroke_sides(top: f32, right: f32, bottom: f32, left: f32) {
+    with_current_shape_mut!(state, |shape: &mut Shape| {
+        shape
+            .set_stroke_sides(StrokeSides {
+                top,
+                right,
+                bottom,
+                left,
+            })
+            .expect("could not set stroke sides");
+    });
+}
+
 #[no_mangle]
 pub extern "C" fn clear_shape_strokes() {
     with_current_shape_mut!(state, |shape: &mut Shape| {
@@ -1,6 +1,6 @@
 use macros::ToJs;
 
-use super::{fills::RawFillData, fonts::RawFontStyle};
+use super::{fills, fonts::RawFontStyle};
 use crate::math::{Matrix, Point};
 use crate::mem::{self, SerializableResult};
 use crate::shapes::{
@@ -15,8 +15,6 @@ use crate::{
 const RAW_SPAN_DATA_SIZE: usize = std::mem::size_of::<RawTextSpan>();
 const RAW_PARAGRAPH_DATA_SIZE: usize = std::mem::size_of::<RawParagraphData>();
 
-const MAX_TEXT_FILLS: usize = 8;
-
 #[derive(Debug, PartialEq, Clone, Copy, ToJs)]
 #[repr(u8)]
 pub enum RawTextAlign {
@@ -138,8 +136,8 @@ pub struct RawTextSpan {
     font_family: [u8; 4],
     font_variant_id: [u32; 4], // TODO: maybe add RawUUID type
     text_length: u32,
+    // The fills follow the span data, see `fills::parse_fill`.
     fill_count: u32,
-    fills: [RawFillData; MAX_TEXT_FILLS],
 }
 
 impl From<[u8; RAW_SPAN_DATA_SIZE]> for RawTextSpan {
@@ -159,34 +157,27 @@ impl TryFrom<&[u8]> for RawTextSpan {
     }
 }
 
-impl From<RawTextSpan> for shapes::TextSpan {
-    fn from(value: RawTextSpan) -> Self {
+impl RawTextSpan {
+    fn to_text_span(&self, fills: Vec<shapes::Fill>) -> shapes::TextSpan {
         let text = String::default();
 
         let font_family = shapes::FontFamily::new(
-            uuid_from_u32(value.font_id),
-            value.font_weight as u32,
-            value.font_style.into(),
+            uuid_from_u32(self.font_id),
+            self.font_weight as u32,
+            self.font_style.into(),
         );
 
-        let fills = value
-            .fills
-            .into_iter()
-            .take(value.fill_count as usize)
-            .map(|fill| fill.into())
-            .collect();
-
-        Self::new(
+        shapes::TextSpan::new(
             text,
             font_family,
-            value.font_size,
-            value.line_height,
-            value.letter_spacing,
-            value.text_decoration.into(),
-            value.text_transform.into(),
-            value.text_direction.into(),
-            value.font_weight,
-            uuid_from_u32(value.font_variant_id),
+            self.font_size,
+            self.line_height,
+            self.letter_spacing,
+            self.text_decoration.into(),
+            self.text_transform.into(),
+            self.text_direction.into(),
+            self.font_weight,
+            uuid_from_u32(self.font_variant_id),
             fills,
         )
     }
@@ -196,7 +187,7 @@ impl From<RawTextSpan> for shapes::TextSpan {
 #[derive(Debug, Clone)]
 pub struct RawParagraph {
     attrs: RawParagraphData,
-    spans: Vec<RawTextSpan>,
+    spans: Vec<(RawTextSpan, Vec<shapes::Fill>)>,
     text_buffer: Vec<u8>,
 }
 
@@ -207,12 +198,19 @@ impl TryFrom<&Vec<u8>> for RawParagraph {
     fn try_from(bytes: &Vec<u8>) -> Result<Self, Self::Error> {
         let attrs = RawParagraphData::try_from(&bytes[..RAW_PARAGRAPH_DATA_SIZE])?;
         let mut offset = RAW_PARAGRAPH_DATA_SIZE;
-        let mut raw_text_spans: Vec<RawTextSpan> = Vec::new();
+        let mut raw_text_spans = Vec::new();
 
         for _ in 0..attrs.span_count {
             let text_span = RawTextSpan::try_from(&bytes[offset..(offset + RAW_SPAN_DATA_SIZE)])?;
             offset += RAW_SPAN_DATA_SIZE;
-            raw_text_spans.push(text_span);
+
+            let mut span_fills = Vec::with_capacity(text_span.fill_count as usize);
+            for _ in 0..text_span.fill_count {
+                let (fill, size) = fills::parse_fill(&bytes[offset..])?;
+                span_fills.push(fill);
+                offset += size;
+            }
+            raw_text_spans.push((text_span, span_fills));
         }
 
         let text_buffer = &bytes[offset..];
@@ -230,11 +228,11 @@ impl From<RawParagraph> for shapes::Paragraph {
         let mut spans = vec![];
 
         let mut offset = 0;
-        for raw_span in value.spans.into_iter() {
+        for (raw_span, fills) in value.spans.into_iter() {
             let delta = raw_span.text_length as usize;
             let text_buffer = &value.text_buffer[offset..offset + delta];
 
-            let mut span = shapes::TextSpan::from(raw_span);
+            let mut span = raw_span.to_text_span(fills);
             if !text_buffer.is_empty() {
                 span.set_text(String::from_utf8_lossy(text_buffer).to_string());
             }